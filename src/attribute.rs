@@ -0,0 +1,347 @@
+//! Decoding of [`CSOEconItemAttribute`] values.
+
+use protobuf::Message;
+use tf2_protobuf::{
+    base_gcmessages::CSOEconItemAttribute,
+    tf_gcmessages::{
+        CAttribute_String,
+        CAttribute_DynamicRecipeComponent,
+        CAttribute_ItemSlotCriteria,
+        CAttribute_WorldItemPlacement,
+    },
+};
+use crate::schema::Schema;
+
+/// Set on the [`CAttribute_DynamicRecipeComponent`] describing what a recipe produces.
+pub const DYNAMIC_RECIPE_FLAG_IS_OUTPUT: u32 = 1 << 0;
+/// Untradable items may be used for the component.
+pub const DYNAMIC_RECIPE_FLAG_IS_UNTRADABLE: u32 = 1 << 1;
+/// The component requires the item definition in `def_index`.
+pub const DYNAMIC_RECIPE_FLAG_PARAM_ITEM_DEF_SET: u32 = 1 << 2;
+/// The component requires the quality in `item_quality`.
+pub const DYNAMIC_RECIPE_FLAG_PARAM_QUALITY_SET: u32 = 1 << 3;
+/// The component requires all attributes in `attributes_string`.
+pub const DYNAMIC_RECIPE_FLAG_PARAM_ATTRIBUTE_SET_ALL: u32 = 1 << 4;
+/// The component requires any of the attributes in `attributes_string`.
+pub const DYNAMIC_RECIPE_FLAG_PARAM_ATTRIBUTE_SET_ANY: u32 = 1 << 5;
+
+/// How an attribute's value is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    /// `value` is an integer.
+    Integer,
+    /// `value` is the bit-pattern of a float.
+    Float,
+    /// `value_bytes` is a [`CAttribute_String`].
+    String,
+    /// `value_bytes` is a [`CAttribute_DynamicRecipeComponent`].
+    DynamicRecipeComponent,
+    /// `value_bytes` is a [`CAttribute_ItemSlotCriteria`].
+    ItemSlotCriteria,
+    /// `value_bytes` is a [`CAttribute_WorldItemPlacement`].
+    WorldItemPlacement,
+    /// `value_bytes` is a little-endian 64-bit integer.
+    UInt64,
+}
+
+impl AttributeType {
+    
+    /// Whether the value is stored in `value_bytes` rather than `value`.
+    pub fn uses_bytes(&self) -> bool {
+        !matches!(self, Self::Integer | Self::Float)
+    }
+}
+
+/// A decoded attribute value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Integer(u32),
+    Float(f32),
+    String(String),
+    DynamicRecipeComponent(CAttribute_DynamicRecipeComponent),
+    ItemSlotCriteria(CAttribute_ItemSlotCriteria),
+    WorldItemPlacement(CAttribute_WorldItemPlacement),
+    UInt64(u64),
+    /// The type of the attribute is not known. Holds `value`.
+    Raw(u32),
+    /// The type of the attribute is not known or the bytes could not be decoded. Holds
+    /// `value_bytes`.
+    Bytes(Vec<u8>),
+}
+
+impl AttributeValue {
+    
+    /// The value as an integer. Floats are truncated, e.g. a particle effect id stored as
+    /// `13.0` gives `13`.
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            Self::Integer(value) => Some(*value),
+            Self::Float(value) => Some(*value as u32),
+            Self::Raw(value) => Some(*value),
+            _ => None,
+        }
+    }
+    
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Self::Integer(value) => Some(*value as f32),
+            Self::Float(value) => Some(*value),
+            Self::Raw(value) => Some(f32::from_bits(*value)),
+            _ => None,
+        }
+    }
+    
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A well-known TF2 attribute.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KnownAttribute {
+    pub def_index: u32,
+    pub name: &'static str,
+    pub attribute_type: AttributeType,
+}
+
+macro_rules! known_attributes {
+    ($($def_index:literal => $name:literal, $attribute_type:ident;)*) => {
+        /// Attributes decoded without a loaded schema.
+        pub const KNOWN_ATTRIBUTES: &[KnownAttribute] = &[
+            $(KnownAttribute {
+                def_index: $def_index,
+                name: $name,
+                attribute_type: AttributeType::$attribute_type,
+            },)*
+        ];
+    };
+}
+
+known_attributes! {
+    134 => "attach particle effect", Float;
+    142 => "set item tint RGB", Float;
+    143 => "custom employee number", Float;
+    153 => "cannot trade", Float;
+    186 => "gifter account id", Integer;
+    187 => "set supply crate series", Float;
+    214 => "kill eater", Integer;
+    228 => "makers mark id", Integer;
+    229 => "unique craftindex", Integer;
+    261 => "set item tint RGB 2", Float;
+    292 => "kill eater score type", Float;
+    293 => "kill eater score type 2", Float;
+    294 => "kill eater 2", Integer;
+    370 => "attach particle effect static", Float;
+    379 => "kill eater user 1", Integer;
+    380 => "kill eater user score type 1", Float;
+    381 => "kill eater user 2", Integer;
+    382 => "kill eater user score type 2", Float;
+    383 => "kill eater user 3", Integer;
+    384 => "kill eater user score type 3", Float;
+    449 => "never craftable", Float;
+    494 => "kill eater 3", Integer;
+    495 => "kill eater score type 3", Float;
+    500 => "custom name attr", String;
+    501 => "custom desc attr", String;
+    725 => "set_item_texture_wear", Float;
+    834 => "paintkit_proto_def_index", Integer;
+    866 => "custom_paintkit_seed_lo", Integer;
+    867 => "custom_paintkit_seed_hi", Integer;
+    1004 => "SPELL: set item tint RGB", Float;
+    1005 => "SPELL: set Halloween footstep type", Float;
+    1006 => "SPELL: Halloween voice modulation", Float;
+    1007 => "SPELL: Halloween pumpkin explosions", Float;
+    1008 => "SPELL: Halloween green flames", Float;
+    1009 => "SPELL: Halloween death ghosts", Float;
+    2000 => "recipe component defined item 1", DynamicRecipeComponent;
+    2001 => "recipe component defined item 2", DynamicRecipeComponent;
+    2002 => "recipe component defined item 3", DynamicRecipeComponent;
+    2003 => "recipe component defined item 4", DynamicRecipeComponent;
+    2004 => "recipe component defined item 5", DynamicRecipeComponent;
+    2005 => "recipe component defined item 6", DynamicRecipeComponent;
+    2006 => "recipe component defined item 7", DynamicRecipeComponent;
+    2007 => "recipe component defined item 8", DynamicRecipeComponent;
+    2008 => "recipe component defined item 9", DynamicRecipeComponent;
+    2009 => "recipe component defined item 10", DynamicRecipeComponent;
+    2012 => "tool target item", Float;
+    2013 => "killstreak effect", Float;
+    2014 => "killstreak idleeffect", Float;
+    2025 => "killstreak tier", Float;
+    2027 => "is australium item", Float;
+    2041 => "taunt attach particle index", Float;
+    2053 => "is_festivized", Float;
+}
+
+pub fn known_attribute(def_index: u32) -> Option<&'static KnownAttribute> {
+    KNOWN_ATTRIBUTES
+        .iter()
+        .find(|attribute| attribute.def_index == def_index)
+}
+
+/// The type of an attribute, from `schema` if given and otherwise from [`KNOWN_ATTRIBUTES`].
+pub fn attribute_type(def_index: u32, schema: Option<&Schema>) -> Option<AttributeType> {
+    let from_schema = schema
+        .and_then(|schema| schema.attribute(def_index))
+        .map(|attribute| match attribute.attribute_type.as_deref() {
+            Some("string") => AttributeType::String,
+            Some("dynamic_recipe_component_defined_item") => AttributeType::DynamicRecipeComponent,
+            Some("item_slot_criteria") => AttributeType::ItemSlotCriteria,
+            Some("world_item_placement") => AttributeType::WorldItemPlacement,
+            Some("uint64") => AttributeType::UInt64,
+            Some("uint32") => AttributeType::Integer,
+            _ if attribute.stored_as_integer => AttributeType::Integer,
+            _ => AttributeType::Float,
+        });
+    
+    from_schema.or_else(|| {
+        known_attribute(def_index).map(|attribute| attribute.attribute_type)
+    })
+}
+
+/// Decodes the value of `attribute`.
+pub fn decode(
+    attribute: &CSOEconItemAttribute,
+    schema: Option<&Schema>,
+) -> AttributeValue {
+    fn parse<Msg: Message>(bytes: &[u8]) -> Option<Msg> {
+        Msg::parse_from_bytes(bytes).ok()
+    }
+    
+    let attribute_type = attribute_type(attribute.get_def_index(), schema);
+    let bytes = attribute.get_value_bytes();
+    
+    let uses_bytes = match attribute_type {
+        Some(attribute_type) => attribute_type.uses_bytes(),
+        None => true,
+    };
+    
+    if !uses_bytes || !attribute.has_value_bytes() {
+        let value = attribute.get_value();
+        
+        return match attribute_type {
+            Some(AttributeType::Integer) => AttributeValue::Integer(value),
+            Some(AttributeType::Float) => AttributeValue::Float(f32::from_bits(value)),
+            _ => AttributeValue::Raw(value),
+        };
+    }
+    
+    let decoded = match attribute_type {
+        Some(AttributeType::String) => parse::<CAttribute_String>(bytes)
+            .map(|mut message| AttributeValue::String(message.take_value())),
+        Some(AttributeType::DynamicRecipeComponent) => parse(bytes)
+            .map(AttributeValue::DynamicRecipeComponent),
+        Some(AttributeType::ItemSlotCriteria) => parse(bytes)
+            .map(AttributeValue::ItemSlotCriteria),
+        Some(AttributeType::WorldItemPlacement) => parse(bytes)
+            .map(AttributeValue::WorldItemPlacement),
+        Some(AttributeType::UInt64) => <[u8; 8]>::try_from(bytes)
+            .ok()
+            .map(|bytes| AttributeValue::UInt64(u64::from_le_bytes(bytes))),
+        _ => None,
+    };
+    
+    decoded.unwrap_or_else(|| AttributeValue::Bytes(bytes.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const ITEMS_GAME: &str = r#"
+        "items_game"
+        {
+            "attributes"
+            {
+                "134"
+                {
+                    "name" "attach particle effect"
+                    "stored_as_integer" "1"
+                }
+                "9000"
+                {
+                    "name" "custom string"
+                    "attribute_type" "string"
+                }
+                "9001"
+                {
+                    "name" "custom uint64"
+                    "attribute_type" "uint64"
+                }
+                "9002"
+                {
+                    "name" "custom uint32"
+                    "attribute_type" "uint32"
+                }
+            }
+        }
+    "#;
+    
+    fn attribute(def_index: u32, value: u32, value_bytes: Option<Vec<u8>>) -> CSOEconItemAttribute {
+        let mut attribute = CSOEconItemAttribute::new();
+        
+        attribute.set_def_index(def_index);
+        attribute.set_value(value);
+        
+        if let Some(value_bytes) = value_bytes {
+            attribute.set_value_bytes(value_bytes);
+        }
+        
+        attribute
+    }
+    
+    fn string_bytes(value: &str) -> Vec<u8> {
+        let mut message = CAttribute_String::new();
+        
+        message.set_value(value.to_string());
+        message.write_to_bytes().unwrap()
+    }
+    
+    #[test]
+    fn decodes_known_attributes() {
+        let particle = attribute(134, 13f32.to_bits(), None);
+        let kill_eater = attribute(214, 25, None);
+        let name = attribute(500, 0, Some(string_bytes("Rocket")));
+        
+        assert_eq!(decode(&particle, None), AttributeValue::Float(13.0));
+        assert_eq!(decode(&particle, None).as_u32(), Some(13));
+        assert_eq!(decode(&kill_eater, None), AttributeValue::Integer(25));
+        assert_eq!(decode(&name, None).as_str(), Some("Rocket"));
+    }
+    
+    #[test]
+    fn decodes_uint64_bytes() {
+        let schema = Schema::parse(ITEMS_GAME).unwrap();
+        let value = attribute(9001, 0, Some(7u64.to_le_bytes().to_vec()));
+        let short = attribute(9001, 0, Some(vec![7]));
+        
+        assert_eq!(decode(&value, Some(&schema)), AttributeValue::UInt64(7));
+        assert_eq!(decode(&short, Some(&schema)), AttributeValue::Bytes(vec![7]));
+    }
+    
+    #[test]
+    fn schema_overrides_known_types() {
+        let schema = Schema::parse(ITEMS_GAME).unwrap();
+        let particle = attribute(134, 13, None);
+        let custom = attribute(9000, 0, Some(string_bytes("Custom")));
+        
+        assert_eq!(attribute_type(134, Some(&schema)), Some(AttributeType::Integer));
+        assert_eq!(decode(&particle, Some(&schema)), AttributeValue::Integer(13));
+        assert_eq!(decode(&custom, Some(&schema)), AttributeValue::String("Custom".into()));
+        assert_eq!(decode(&custom, None), AttributeValue::Bytes(string_bytes("Custom")));
+        // uint32 attributes need not be flagged as stored_as_integer
+        assert_eq!(attribute_type(9002, Some(&schema)), Some(AttributeType::Integer));
+        assert_eq!(decode(&attribute(9002, 5, None), Some(&schema)), AttributeValue::Integer(5));
+    }
+    
+    #[test]
+    fn unknown_and_undecodable_values_are_kept() {
+        let unknown = attribute(9002, 5, None);
+        let invalid = attribute(500, 0, Some(vec![0xff]));
+        
+        assert_eq!(decode(&unknown, None), AttributeValue::Raw(5));
+        assert_eq!(decode(&invalid, None), AttributeValue::Bytes(vec![0xff]));
+    }
+}
\ No newline at end of file
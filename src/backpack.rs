@@ -0,0 +1,289 @@
+use std::cmp::Ordering;
+use tf2_protobuf::base_gcmessages::CSOEconItem;
+use crate::request::SetItemPosition;
+
+/// Set on the inventory value of items which have not been acknowledged.
+const UNACKNOWLEDGED_FLAG: u32 = 1 << 30;
+/// The bits of the inventory value holding the slot, or the acquisition reason of new items.
+const POSITION_MASK: u32 = 0xFFFF;
+
+/// How a new item was acquired, stored in the inventory value until the item is acknowledged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcquisitionReason {
+    Dropped,
+    Crafted,
+    Traded,
+    Purchased,
+    FoundInCrate,
+    Gifted,
+    Support,
+    Promotion,
+    Earned,
+    Refunded,
+    GiftWrapped,
+    Foreign,
+    CollectionReward,
+    PreviewItem,
+    PreviewItemPurchased,
+    PeriodicScoreReward,
+    MvMMissionCompletionReward,
+    MvMSquadSurplusReward,
+    FoundHolidayGift,
+    CommunityMarketPurchase,
+    RecipeOutput,
+    HiddenQuestItem,
+    QuestOutput,
+    QuestLoaner,
+    TradeUp,
+    QuestMerasmissionOutput,
+    ViralCompetitiveBetaPassSpread,
+    Other(u32),
+}
+
+impl From<u32> for AcquisitionReason {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Self::Dropped,
+            2 => Self::Crafted,
+            3 => Self::Traded,
+            4 => Self::Purchased,
+            5 => Self::FoundInCrate,
+            6 => Self::Gifted,
+            7 => Self::Support,
+            8 => Self::Promotion,
+            9 => Self::Earned,
+            10 => Self::Refunded,
+            11 => Self::GiftWrapped,
+            12 => Self::Foreign,
+            13 => Self::CollectionReward,
+            14 => Self::PreviewItem,
+            15 => Self::PreviewItemPurchased,
+            16 => Self::PeriodicScoreReward,
+            17 => Self::MvMMissionCompletionReward,
+            18 => Self::MvMSquadSurplusReward,
+            19 => Self::FoundHolidayGift,
+            20 => Self::CommunityMarketPurchase,
+            21 => Self::RecipeOutput,
+            22 => Self::HiddenQuestItem,
+            23 => Self::QuestOutput,
+            24 => Self::QuestLoaner,
+            25 => Self::TradeUp,
+            26 => Self::QuestMerasmissionOutput,
+            27 => Self::ViralCompetitiveBetaPassSpread,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// The decoded `inventory` value of a [`CSOEconItem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryPosition {
+    /// The item is placed in this backpack slot, starting from 1.
+    Backpack(u32),
+    /// The item is new and has not been acknowledged.
+    New(AcquisitionReason),
+}
+
+impl InventoryPosition {
+    
+    pub fn is_new(&self) -> bool {
+        matches!(self, Self::New(_))
+    }
+    
+    pub fn backpack_slot(&self) -> Option<u32> {
+        match self {
+            Self::Backpack(slot) => Some(*slot),
+            Self::New(_) => None,
+        }
+    }
+    
+    pub fn acquisition_reason(&self) -> Option<AcquisitionReason> {
+        match self {
+            Self::Backpack(_) => None,
+            Self::New(reason) => Some(*reason),
+        }
+    }
+}
+
+impl From<u32> for InventoryPosition {
+    fn from(inventory: u32) -> Self {
+        if inventory == 0 {
+            Self::New(AcquisitionReason::Other(0))
+        } else if inventory & UNACKNOWLEDGED_FLAG != 0 {
+            Self::New(AcquisitionReason::from(inventory & POSITION_MASK))
+        } else {
+            Self::Backpack(inventory & POSITION_MASK)
+        }
+    }
+}
+
+impl From<&CSOEconItem> for InventoryPosition {
+    fn from(item: &CSOEconItem) -> Self {
+        Self::from(item.get_inventory())
+    }
+}
+
+/// Picks a free backpack slot for each of `count` items, lowest slots first.
+pub fn free_positions<'a, I>(
+    items: I,
+    count: usize,
+) -> Vec<u32>
+where
+    I: IntoIterator<Item = &'a CSOEconItem>,
+{
+    let mut used = items
+        .into_iter()
+        .filter_map(|item| InventoryPosition::from(item).backpack_slot())
+        .collect::<Vec<_>>();
+    
+    used.sort_unstable();
+    used.dedup();
+    
+    let mut used = used.into_iter().peekable();
+    
+    (1..)
+        .filter(|position| {
+            while used.next_if(|used| used < position).is_some() {}
+            used.next_if_eq(position).is_none()
+        })
+        .take(count)
+        .collect()
+}
+
+/// Orders the placed items in `items` by `compare` and lays them out from the first backpack
+/// slot. Only items whose position changes are returned. Items that compare equal keep their
+/// current relative order.
+pub fn organize<'a, I, F>(
+    items: I,
+    mut compare: F,
+) -> Vec<SetItemPosition>
+where
+    I: IntoIterator<Item = &'a CSOEconItem>,
+    F: FnMut(&CSOEconItem, &CSOEconItem) -> Ordering,
+{
+    let mut items = items
+        .into_iter()
+        .filter_map(|item| {
+            InventoryPosition::from(item)
+                .backpack_slot()
+                .map(|position| (position, item))
+        })
+        .collect::<Vec<_>>();
+    
+    items.sort_by_key(|(position, _item)| *position);
+    items.sort_by(|(_, a), (_, b)| compare(a, b));
+    items
+        .into_iter()
+        .zip(1..)
+        .filter(|((position, _item), new_position)| position != new_position)
+        .map(|((_position, item), position)| SetItemPosition {
+            item_id: item.get_id(),
+            position,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn item(item_id: u64, def_index: u32, inventory: u32) -> CSOEconItem {
+        let mut item = CSOEconItem::new();
+        
+        item.set_id(item_id);
+        item.set_def_index(def_index);
+        item.set_inventory(inventory);
+        item
+    }
+    
+    fn by_def_index(a: &CSOEconItem, b: &CSOEconItem) -> Ordering {
+        a.get_def_index().cmp(&b.get_def_index())
+    }
+    
+    #[test]
+    fn decodes_inventory_positions() {
+        assert_eq!(InventoryPosition::from(0x8000_0012), InventoryPosition::Backpack(0x12));
+        assert_eq!(
+            InventoryPosition::from(UNACKNOWLEDGED_FLAG | 3),
+            InventoryPosition::New(AcquisitionReason::Traded),
+        );
+        // bits other than the flag and the reason are ignored
+        assert_eq!(
+            InventoryPosition::from(0x8000_0000 | UNACKNOWLEDGED_FLAG | 0x0001_0000 | 25),
+            InventoryPosition::New(AcquisitionReason::TradeUp),
+        );
+        assert_eq!(InventoryPosition::from(0), InventoryPosition::New(AcquisitionReason::Other(0)));
+    }
+    
+    #[test]
+    fn free_positions_fill_gaps_first() {
+        let items = vec![
+            item(1, 1, 1),
+            item(2, 1, 3),
+            item(3, 1, 3),
+            item(4, 1, UNACKNOWLEDGED_FLAG | 1),
+        ];
+        
+        assert_eq!(free_positions(&items, 3), vec![2, 4, 5]);
+    }
+    
+    #[test]
+    fn free_positions_skip_full_pages() {
+        let items = (1..=50)
+            .map(|i| item(i, 1, i as u32))
+            .collect::<Vec<_>>();
+        
+        assert_eq!(free_positions(&items, 2), vec![51, 52]);
+        assert!(free_positions(&items, 0).is_empty());
+    }
+    
+    #[test]
+    fn organize_only_moves_misplaced_items() {
+        let items = vec![
+            item(1, 20, 1),
+            item(2, 10, 2),
+            item(3, 30, 3),
+        ];
+        let positions = organize(&items, by_def_index);
+        
+        assert_eq!(positions, vec![
+            SetItemPosition { item_id: 2, position: 1 },
+            SetItemPosition { item_id: 1, position: 2 },
+        ]);
+    }
+    
+    #[test]
+    fn organize_leaves_sorted_backpack_alone() {
+        let items = (1..=3)
+            .map(|i| item(i, i as u32, i as u32))
+            .collect::<Vec<_>>();
+        
+        assert!(organize(&items, by_def_index).is_empty());
+    }
+    
+    #[test]
+    fn organize_skips_unacknowledged_items() {
+        let items = vec![
+            item(1, 20, 5),
+            item(2, 10, UNACKNOWLEDGED_FLAG | 1),
+            item(3, 30, 0),
+        ];
+        
+        assert_eq!(organize(&items, by_def_index), vec![
+            SetItemPosition { item_id: 1, position: 1 },
+        ]);
+    }
+    
+    #[test]
+    fn organize_compacts_full_pages() {
+        // two full pages of 50 with a gap after the first page
+        let items = (1..=100)
+            .map(|i| item(i, 1, if i <= 50 { i as u32 } else { i as u32 + 10 }))
+            .collect::<Vec<_>>();
+        let positions = organize(&items, by_def_index);
+        
+        assert_eq!(positions.len(), 50);
+        assert_eq!(positions[0], SetItemPosition { item_id: 51, position: 51 });
+        assert_eq!(positions[49], SetItemPosition { item_id: 100, position: 100 });
+    }
+}
\ No newline at end of file
@@ -0,0 +1,381 @@
+//! Planning inputs for tools with dynamic recipes, such as chemistry sets and strangifier
+//! chemistry sets.
+
+use tf2_protobuf::base_gcmessages::CSOEconItem;
+use tf2_protobuf::tf_gcmessages::CAttribute_DynamicRecipeComponent;
+use crate::attribute::{
+    self,
+    AttributeValue,
+    DYNAMIC_RECIPE_FLAG_IS_OUTPUT,
+    DYNAMIC_RECIPE_FLAG_IS_UNTRADABLE,
+    DYNAMIC_RECIPE_FLAG_PARAM_ITEM_DEF_SET,
+    DYNAMIC_RECIPE_FLAG_PARAM_QUALITY_SET,
+    DYNAMIC_RECIPE_FLAG_PARAM_ATTRIBUTE_SET_ALL,
+    DYNAMIC_RECIPE_FLAG_PARAM_ATTRIBUTE_SET_ANY,
+};
+use crate::item::{Item, Quality};
+use crate::request::RecipeComponent;
+use crate::schema::Schema;
+
+/// An input or output of a dynamic recipe, read from one of the tool's attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicRecipeComponent {
+    /// The definition index of the attribute describing this component.
+    pub attribute_index: u32,
+    pub def_index: u32,
+    pub quality: Quality,
+    pub flags: u32,
+    /// Attribute definition indexes and values the item must have.
+    pub attributes: Vec<(u32, String)>,
+    pub required: u32,
+    pub fulfilled: u32,
+}
+
+impl DynamicRecipeComponent {
+    
+    pub fn new(attribute_index: u32, component: &CAttribute_DynamicRecipeComponent) -> Self {
+        Self {
+            attribute_index,
+            def_index: component.get_def_index(),
+            quality: Quality::from(component.get_item_quality()),
+            flags: component.get_component_flags(),
+            attributes: parse_attributes_string(component.get_attributes_string()),
+            required: component.get_num_required(),
+            fulfilled: component.get_num_fulfilled(),
+        }
+    }
+    
+    pub fn is_output(&self) -> bool {
+        self.flags & DYNAMIC_RECIPE_FLAG_IS_OUTPUT != 0
+    }
+    
+    /// The number of items still needed.
+    pub fn remaining(&self) -> u32 {
+        self.required.saturating_sub(self.fulfilled)
+    }
+    
+    /// Whether `item` can be used for this component.
+    pub fn matches(&self, item: &CSOEconItem, schema: Option<&Schema>) -> bool {
+        self.matches_decoded(item, &Item::new(item, schema), schema)
+    }
+    
+    /// Like [`DynamicRecipeComponent::matches`], with `decoded` being `item` already decoded.
+    fn matches_decoded(&self, item: &CSOEconItem, decoded: &Item, schema: Option<&Schema>) -> bool {
+        if self.flags & DYNAMIC_RECIPE_FLAG_PARAM_ITEM_DEF_SET != 0 && decoded.def_index != self.def_index {
+            return false;
+        }
+        
+        if self.flags & DYNAMIC_RECIPE_FLAG_PARAM_QUALITY_SET != 0 && decoded.quality != self.quality {
+            return false;
+        }
+        
+        if self.flags & DYNAMIC_RECIPE_FLAG_IS_UNTRADABLE == 0 && !decoded.tradable {
+            return false;
+        }
+        
+        let mut has_attributes = self.attributes
+            .iter()
+            .map(|(def_index, value)| has_attribute(item, *def_index, value, schema));
+        
+        if self.flags & DYNAMIC_RECIPE_FLAG_PARAM_ATTRIBUTE_SET_ALL != 0 {
+            has_attributes.all(|has| has)
+        } else if self.flags & DYNAMIC_RECIPE_FLAG_PARAM_ATTRIBUTE_SET_ANY != 0 {
+            self.attributes.is_empty() || has_attributes.any(|has| has)
+        } else {
+            true
+        }
+    }
+}
+
+/// A component still missing items after planning.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingComponent {
+    pub component: DynamicRecipeComponent,
+    pub count: u32,
+}
+
+/// The items chosen to fulfill a dynamic recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicRecipePlan {
+    pub tool_id: u64,
+    /// The components to send with `fulfill_recipe`.
+    pub components: Vec<RecipeComponent>,
+    /// The inputs for which not enough matching items were found.
+    pub missing: Vec<MissingComponent>,
+    /// What the recipe produces.
+    pub outputs: Vec<DynamicRecipeComponent>,
+}
+
+impl DynamicRecipePlan {
+    
+    /// Whether the chosen items complete the recipe.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Reads the input and output components of `tool`.
+pub fn components(tool: &CSOEconItem, schema: Option<&Schema>) -> Vec<DynamicRecipeComponent> {
+    tool.get_attribute()
+        .iter()
+        .filter_map(|attribute| match attribute::decode(attribute, schema) {
+            AttributeValue::DynamicRecipeComponent(component) => {
+                Some(DynamicRecipeComponent::new(attribute.get_def_index(), &component))
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Picks items from `items` for the unfulfilled inputs of `tool`. Components that are already
+/// partially fulfilled only need their remaining count. Equipped items are never picked.
+pub fn plan<'a, I>(
+    tool: &CSOEconItem,
+    items: I,
+    schema: Option<&Schema>,
+) -> DynamicRecipePlan
+where
+    I: IntoIterator<Item = &'a CSOEconItem>,
+{
+    let (outputs, mut inputs): (Vec<_>, Vec<_>) = components(tool, schema)
+        .into_iter()
+        .partition(DynamicRecipeComponent::is_output);
+    // each item is decoded once rather than for every component it is checked against
+    let mut candidates = items
+        .into_iter()
+        .filter(|item| item.get_id() != tool.get_id() && item.get_equipped_state().is_empty())
+        .map(|item| (item, Item::new(item, schema)))
+        .collect::<Vec<_>>();
+    let mut components = Vec::new();
+    let mut missing = Vec::new();
+    
+    // specific components go first so they are not starved by components accepting any item
+    inputs.sort_by_key(|component| component.flags & DYNAMIC_RECIPE_FLAG_PARAM_ITEM_DEF_SET == 0);
+    
+    for component in inputs {
+        let mut remaining = component.remaining();
+        
+        while remaining > 0 {
+            let position = candidates
+                .iter()
+                .position(|(item, decoded)| component.matches_decoded(item, decoded, schema));
+            
+            match position {
+                Some(position) => {
+                    let (item, _decoded) = candidates.remove(position);
+                    
+                    components.push(RecipeComponent {
+                        subject_item_id: item.get_id(),
+                        attribute_index: component.attribute_index as u64,
+                    });
+                    remaining -= 1;
+                },
+                None => break,
+            }
+        }
+        
+        if remaining > 0 {
+            missing.push(MissingComponent {
+                component,
+                count: remaining,
+            });
+        }
+    }
+    
+    DynamicRecipePlan {
+        tool_id: tool.get_id(),
+        components,
+        missing,
+        outputs,
+    }
+}
+
+/// Parses an encoded attribute string of `|` separated definition index and value pairs.
+fn parse_attributes_string(attributes: &str) -> Vec<(u32, String)> {
+    let parts = attributes
+        .split('|')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    
+    parts
+        .chunks_exact(2)
+        .filter_map(|pair| {
+            pair[0].trim().parse::<u32>()
+                .ok()
+                .map(|def_index| (def_index, pair[1].to_string()))
+        })
+        .collect()
+}
+
+fn has_attribute(item: &CSOEconItem, def_index: u32, value: &str, schema: Option<&Schema>) -> bool {
+    item.get_attribute()
+        .iter()
+        .filter(|attribute| attribute.get_def_index() == def_index)
+        .any(|attribute| match attribute::decode(attribute, schema) {
+            AttributeValue::String(string) => string == value,
+            decoded => match (decoded.as_f32(), value.trim().parse::<f32>()) {
+                (Some(decoded), Ok(expected)) => decoded == expected,
+                _ => false,
+            },
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protobuf::Message;
+    use tf2_protobuf::base_gcmessages::{CSOEconItemAttribute, CSOEconItemEquipped};
+    use crate::item::FLAG_CANNOT_TRADE;
+    
+    const CHEMISTRY_SET: u64 = 1;
+    
+    fn component_attribute(
+        def_index: u32,
+        item_def_index: u32,
+        quality: u32,
+        flags: u32,
+        attributes: &str,
+        required: u32,
+        fulfilled: u32,
+    ) -> CSOEconItemAttribute {
+        let mut component = CAttribute_DynamicRecipeComponent::new();
+        let mut attribute = CSOEconItemAttribute::new();
+        
+        component.set_def_index(item_def_index);
+        component.set_item_quality(quality);
+        component.set_component_flags(flags);
+        component.set_attributes_string(attributes.to_string());
+        component.set_num_required(required);
+        component.set_num_fulfilled(fulfilled);
+        attribute.set_def_index(def_index);
+        attribute.set_value_bytes(component.write_to_bytes().unwrap());
+        attribute
+    }
+    
+    fn item(item_id: u64, def_index: u32, quality: u32) -> CSOEconItem {
+        let mut item = CSOEconItem::new();
+        
+        item.set_id(item_id);
+        item.set_def_index(def_index);
+        item.set_quality(quality);
+        item
+    }
+    
+    fn tool(components: Vec<CSOEconItemAttribute>) -> CSOEconItem {
+        let mut tool = item(CHEMISTRY_SET, 20005, 6);
+        
+        tool.mut_attribute().extend(components);
+        tool
+    }
+    
+    fn picked(plan: &DynamicRecipePlan) -> Vec<(u64, u64)> {
+        plan.components
+            .iter()
+            .map(|component| (component.subject_item_id, component.attribute_index))
+            .collect()
+    }
+    
+    #[test]
+    fn parses_attributes_strings() {
+        assert_eq!(
+            parse_attributes_string("214|0|292|64|"),
+            vec![(214, "0".to_string()), (292, "64".to_string())],
+        );
+        assert!(parse_attributes_string("").is_empty());
+    }
+    
+    #[test]
+    fn plans_specific_components_first() {
+        let item_def_flags = DYNAMIC_RECIPE_FLAG_PARAM_ITEM_DEF_SET
+            | DYNAMIC_RECIPE_FLAG_PARAM_QUALITY_SET;
+        let tool = tool(vec![
+            // any unique item
+            component_attribute(2000, 0, 6, DYNAMIC_RECIPE_FLAG_PARAM_QUALITY_SET, "", 1, 0),
+            // two unique Team Captains, one already given
+            component_attribute(2001, 378, 6, item_def_flags, "", 2, 1),
+            component_attribute(2002, 6526, 6, DYNAMIC_RECIPE_FLAG_IS_OUTPUT, "", 1, 0),
+        ]);
+        let mut equipped = item(12, 378, 6);
+        
+        equipped.mut_equipped_state().push(CSOEconItemEquipped::new());
+        
+        let items = [
+            tool.clone(),
+            item(10, 378, 6),
+            equipped,
+            item(11, 378, 6),
+            item(13, 5021, 3),
+        ];
+        let plan = plan(&tool, &items, None);
+        
+        assert_eq!(picked(&plan), vec![(10, 2001), (11, 2000)]);
+        assert!(plan.is_complete());
+        assert_eq!(plan.outputs.len(), 1);
+        assert_eq!(plan.outputs[0].def_index, 6526);
+    }
+    
+    #[test]
+    fn reports_missing_components() {
+        let tool = tool(vec![
+            component_attribute(2000, 378, 6, DYNAMIC_RECIPE_FLAG_PARAM_ITEM_DEF_SET, "", 3, 0),
+        ]);
+        let items = [item(10, 378, 6), item(11, 5021, 6)];
+        let plan = plan(&tool, &items, None);
+        
+        assert_eq!(picked(&plan), vec![(10, 2000)]);
+        assert!(!plan.is_complete());
+        assert_eq!(plan.missing[0].count, 2);
+    }
+    
+    #[test]
+    fn rejects_untradable_items_unless_allowed() {
+        let mut untradable = item(10, 378, 6);
+        
+        untradable.set_flags(FLAG_CANNOT_TRADE);
+        
+        let component = DynamicRecipeComponent::new(2000, &{
+            let mut component = CAttribute_DynamicRecipeComponent::new();
+            
+            component.set_def_index(378);
+            component.set_component_flags(DYNAMIC_RECIPE_FLAG_PARAM_ITEM_DEF_SET);
+            component
+        });
+        
+        assert!(!component.matches(&untradable, None));
+        
+        let component = DynamicRecipeComponent {
+            flags: component.flags | DYNAMIC_RECIPE_FLAG_IS_UNTRADABLE,
+            ..component
+        };
+        
+        assert!(component.matches(&untradable, None));
+    }
+    
+    #[test]
+    fn matches_attributes() {
+        let mut strange = item(10, 378, 11);
+        let mut attribute = CSOEconItemAttribute::new();
+        
+        attribute.set_def_index(292);
+        attribute.set_value(64f32.to_bits());
+        strange.mut_attribute().push(attribute);
+        
+        let component = |flags: u32, attributes: &str| DynamicRecipeComponent {
+            attribute_index: 2000,
+            def_index: 0,
+            quality: Quality::Unique,
+            flags,
+            attributes: parse_attributes_string(attributes),
+            required: 1,
+            fulfilled: 0,
+        };
+        
+        let all = DYNAMIC_RECIPE_FLAG_PARAM_ATTRIBUTE_SET_ALL;
+        let any = DYNAMIC_RECIPE_FLAG_PARAM_ATTRIBUTE_SET_ANY;
+        
+        assert!(component(all, "292|64").matches(&strange, None));
+        assert!(!component(all, "292|64|214|1").matches(&strange, None));
+        assert!(component(any, "292|64|214|1").matches(&strange, None));
+        assert!(!component(any, "292|1").matches(&strange, None));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+use steam_vent::net::NetworkError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{}", .0)]
+    Network(#[from] NetworkError),
+    #[error("{}", .0)]
+    IO(#[from] std::io::Error),
+    #[error("Expected {} items, got {}", .expected, .got)]
+    InvalidItemCount {
+        expected: usize,
+        got: usize,
+    },
+    #[error("Item {} was given more than once", .0)]
+    DuplicateItem(u64),
+    #[error("Expected {} items with defindex {}, got {}", .expected, .def_index, .got)]
+    InvalidRecipeInput {
+        def_index: u32,
+        expected: usize,
+        got: usize,
+    },
+    #[error("Metal worth {} scrap is not enough to make {} scrap", .have, .target)]
+    InsufficientMetal {
+        have: u32,
+        target: u32,
+    },
+    #[error("Item {} is not in the cache", .0)]
+    UnknownItem(u64),
+    #[error("Unexpected craft response for blueprint {}", .0)]
+    UnexpectedCraftResponse(i16),
+    #[error("Notification {} is not in the cache", .0)]
+    UnknownNotification(u64),
+    #[error("Not in a party")]
+    NotInParty,
+    #[error("Quest {} is not in the cache", .0)]
+    UnknownQuest(u64),
+}
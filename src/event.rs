@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use crate::notification::Notification;
+use crate::response::CraftingResponse;
+use crate::item::Item;
+use crate::party::{Party, PartyInvite, PartyChatMessage, PendingMember, QueueEntry};
+use crate::matchmaking::{MatchmakingProgress, MatchmakerStats};
+use crate::lobby::Lobby;
+use crate::quest::QuestResponse;
+use crate::war::{WarStats, WarScoreUpdate, DuckLeaderboardUpdate};
+
+/// Events produced by [`crate::TeamFortress2::handle_message`].
+#[derive(Debug)]
+pub enum GCEvent {
+    /// The GC finished a sort started with [`crate::TeamFortress2::sort_backpack`].
+    BackpackSortFinished,
+    /// The GC reported an item schema version other than the one loaded with
+    /// [`crate::TeamFortress2::set_schema`].
+    ItemSchemaUpdated {
+        version: u32,
+        items_game_url: String,
+    },
+    /// The items created by [`crate::TeamFortress2::craft_collection_upgrade`],
+    /// [`crate::TeamFortress2::craft_halloween_offering`] or
+    /// [`crate::TeamFortress2::craft_stat_clock`].
+    CraftingResponse(CraftingResponse),
+    /// Persona names by account id, in reply to
+    /// [`crate::TeamFortress2::lookup_account_names`].
+    AccountNames(HashMap<u32, String>),
+    /// Notifications added to the queue. They keep showing until acknowledged with
+    /// [`crate::TeamFortress2::acknowledge_notification`].
+    Notifications(Vec<Notification>),
+    /// The GC accepted an acknowledgement sent with
+    /// [`crate::TeamFortress2::acknowledge_notification`]. Holds the id of the notification,
+    /// which has been removed from the cache.
+    NotificationAcknowledged(u64),
+    /// The item requested with [`crate::TeamFortress2::inspect_item`].
+    ItemInspected(Box<Item>),
+    /// Invites to other parties or requests to join ours.
+    PartyInvites(Vec<PartyInvite>),
+    /// The client joined or created a party.
+    PartyJoined(Box<Party>),
+    /// The client left the party with this id or was kicked from it.
+    PartyLeft(u64),
+    /// A player joined the party.
+    PartyMemberJoined(u64),
+    /// A player left or was kicked with [`crate::TeamFortress2::kick_party_member`].
+    PartyMemberLeft(u64),
+    /// The leader changed, e.g. after [`crate::TeamFortress2::promote_to_party_leader`].
+    PartyLeaderChanged(u64),
+    /// A player was invited with [`crate::TeamFortress2::invite_to_party`] or asked to join.
+    PartyPendingMemberAdded(PendingMember),
+    /// An invite or join request for this player was accepted, declined or cancelled.
+    PartyPendingMemberRemoved(u64),
+    PartyChat(PartyChatMessage),
+    /// The GC applied options set with [`crate::TeamFortress2::set_party_options`].
+    PartyOptionsSet,
+    /// The party joined or left matchmaking queues.
+    MatchmakingQueuesChanged {
+        joined: Vec<QueueEntry>,
+        left: Vec<QueueEntry>,
+    },
+    MatchmakingProgress(MatchmakingProgress),
+    /// The GC accepted [`crate::TeamFortress2::queue_for_match`].
+    QueuedForMatch,
+    /// The GC accepted [`crate::TeamFortress2::leave_queue`].
+    RemovedFromQueue,
+    /// The GC accepted [`crate::TeamFortress2::queue_for_standby`].
+    QueuedForStandby,
+    /// The GC accepted [`crate::TeamFortress2::leave_standby_queue`].
+    RemovedFromStandbyQueue,
+    /// Population per match group, datacenter and map, in reply to
+    /// [`crate::TeamFortress2::request_matchmaker_stats`].
+    MatchmakerStats(Box<MatchmakerStats>),
+    /// The party was removed from all matchmaking queues by the GC.
+    KickedFromMatchmakingQueue,
+    /// A lobby was created, or its server or game state changed.
+    LobbyChanged(Box<Lobby>),
+    /// The lobby with this id was removed, e.g. after the match ended.
+    LobbyRemoved(u64),
+    /// The GC accepted [`crate::TeamFortress2::accept_lobby_invite`].
+    LobbyInviteAccepted,
+    /// The GC replied to [`crate::TeamFortress2::unlock_quest_node`],
+    /// [`crate::TeamFortress2::purchase_quest_reward`] or
+    /// [`crate::TeamFortress2::turn_in_quest_node`].
+    QuestResponse(QuestResponse),
+    /// Scores of each side, in reply to [`crate::TeamFortress2::war_global_stats`].
+    WarGlobalStats(WarStats),
+    WarScoreUpdated(WarScoreUpdate),
+    DuckLeaderboardUpdated(DuckLeaderboardUpdate),
+}
@@ -0,0 +1,133 @@
+//! Inspect links, e.g.
+//! `steam://rungame/440/76561202255233023/+tf_econ_item_preview%20S76561198000000000A1234D5678`.
+
+use std::str::FromStr;
+
+const PREVIEW_COMMAND: &str = "tf_econ_item_preview";
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ParseInspectLinkError {
+    #[error("Not an item preview link")]
+    NotAnInspectLink,
+    #[error("Missing \"{}\" parameter", .0)]
+    MissingParameter(char),
+    #[error("Invalid \"{}\" parameter", .0)]
+    InvalidParameter(char),
+}
+
+/// Where the inspected item is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InspectOwner {
+    /// In the inventory of this SteamID (the `S` parameter).
+    SteamId(u64),
+    /// In the Steam Community Market listing with this id (the `M` parameter).
+    MarketListing(u64),
+}
+
+/// The parameters of an inspect link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InspectLink {
+    pub owner: InspectOwner,
+    pub asset_id: u64,
+    pub d: u64,
+}
+
+impl FromStr for InspectLink {
+    type Err = ParseInspectLinkError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let start = s.find(PREVIEW_COMMAND)
+            .ok_or(ParseInspectLinkError::NotAnInspectLink)? + PREVIEW_COMMAND.len();
+        let params = s[start..].replace("%20", " ");
+        let mut params = params.trim();
+        let mut owner = None;
+        let mut asset_id = None;
+        let mut d = None;
+        
+        while let Some(key) = params.chars().next() {
+            if !key.is_ascii_alphabetic() {
+                return Err(ParseInspectLinkError::InvalidParameter(key));
+            }
+            
+            let value_len = params[1..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(params.len() - 1);
+            let value = params[1..1 + value_len]
+                .parse::<u64>()
+                .map_err(|_| ParseInspectLinkError::InvalidParameter(key))?;
+            
+            match key.to_ascii_uppercase() {
+                'S' => owner = Some(InspectOwner::SteamId(value)),
+                'M' => owner = Some(InspectOwner::MarketListing(value)),
+                'A' => asset_id = Some(value),
+                'D' => d = Some(value),
+                _ => return Err(ParseInspectLinkError::InvalidParameter(key)),
+            }
+            
+            params = &params[1 + value_len..];
+        }
+        
+        Ok(Self {
+            owner: owner.ok_or(ParseInspectLinkError::MissingParameter('S'))?,
+            asset_id: asset_id.ok_or(ParseInspectLinkError::MissingParameter('A'))?,
+            d: d.ok_or(ParseInspectLinkError::MissingParameter('D'))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const PREFIX: &str = "steam://rungame/440/76561202255233023/+tf_econ_item_preview";
+    
+    fn parse(params: &str) -> Result<InspectLink, ParseInspectLinkError> {
+        format!("{}{}", PREFIX, params).parse()
+    }
+    
+    #[test]
+    fn parses_inventory_links() {
+        assert_eq!(parse("%20S76561198000000000A1234D5678"), Ok(InspectLink {
+            owner: InspectOwner::SteamId(76561198000000000),
+            asset_id: 1234,
+            d: 5678,
+        }));
+    }
+    
+    #[test]
+    fn parses_market_links() {
+        assert_eq!(parse("%20M4141A1234D5678"), Ok(InspectLink {
+            owner: InspectOwner::MarketListing(4141),
+            asset_id: 1234,
+            d: 5678,
+        }));
+    }
+    
+    #[test]
+    fn accepts_spaces_and_lowercase_keys() {
+        let expected = parse("%20S76561198000000000A1234D5678").unwrap();
+        
+        assert_eq!(parse(" S76561198000000000A1234D5678"), Ok(expected));
+        assert_eq!(parse("%20s76561198000000000a1234d5678"), Ok(expected));
+    }
+    
+    #[test]
+    fn rejects_missing_parameters() {
+        assert_eq!(
+            parse("%20S76561198000000000A1234"),
+            Err(ParseInspectLinkError::MissingParameter('D')),
+        );
+        assert_eq!(parse("%20A1234D5678"), Err(ParseInspectLinkError::MissingParameter('S')));
+        assert_eq!(parse("%20S1A"), Err(ParseInspectLinkError::InvalidParameter('A')));
+    }
+    
+    #[test]
+    fn rejects_other_links() {
+        assert_eq!(
+            "https://steamcommunity.com/id/someone/inventory/#440".parse::<InspectLink>(),
+            Err(ParseInspectLinkError::NotAnInspectLink),
+        );
+        assert_eq!(parse("%20X1A1234D5678"), Err(ParseInspectLinkError::InvalidParameter('X')));
+        assert_eq!(parse("%20S1-A1D1"), Err(ParseInspectLinkError::InvalidParameter('-')));
+    }
+}
\ No newline at end of file
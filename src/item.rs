@@ -0,0 +1,816 @@
+//! A high-level view of [`CSOEconItem`].
+
+use tf2_protobuf::base_gcmessages::CSOEconItem;
+use crate::attribute::{self, AttributeValue, DYNAMIC_RECIPE_FLAG_IS_OUTPUT};
+use crate::backpack::InventoryPosition;
+use crate::schema::Schema;
+
+/// Set on items which cannot be traded.
+pub const FLAG_CANNOT_TRADE: u32 = 1 << 0;
+/// Set on items which cannot be used in crafting.
+pub const FLAG_CANNOT_BE_USED_IN_CRAFTING: u32 = 1 << 1;
+
+pub const ATTRIBUTE_PARTICLE_EFFECT: u32 = 134;
+pub const ATTRIBUTE_PAINT: u32 = 142;
+pub const ATTRIBUTE_CANNOT_TRADE: u32 = 153;
+pub const ATTRIBUTE_GIFTER_ACCOUNT_ID: u32 = 186;
+pub const ATTRIBUTE_CRATE_SERIES: u32 = 187;
+pub const ATTRIBUTE_KILL_EATER: u32 = 214;
+pub const ATTRIBUTE_MAKERS_MARK_ID: u32 = 228;
+pub const ATTRIBUTE_UNIQUE_CRAFT_INDEX: u32 = 229;
+pub const ATTRIBUTE_PAINT_SECONDARY: u32 = 261;
+pub const ATTRIBUTE_KILL_EATER_SCORE_TYPE: u32 = 292;
+pub const ATTRIBUTE_NEVER_CRAFTABLE: u32 = 449;
+pub const ATTRIBUTE_CUSTOM_NAME: u32 = 500;
+pub const ATTRIBUTE_CUSTOM_DESC: u32 = 501;
+pub const ATTRIBUTE_TEXTURE_WEAR: u32 = 725;
+pub const ATTRIBUTE_PAINT_KIT: u32 = 834;
+pub const ATTRIBUTE_SPELL_PAINT: u32 = 1004;
+pub const ATTRIBUTE_SPELL_FOOTSTEPS: u32 = 1005;
+pub const ATTRIBUTE_SPELL_VOICES: u32 = 1006;
+pub const ATTRIBUTE_SPELL_PUMPKIN_BOMBS: u32 = 1007;
+pub const ATTRIBUTE_SPELL_HALLOWEEN_FIRE: u32 = 1008;
+pub const ATTRIBUTE_SPELL_EXORCISM: u32 = 1009;
+pub const ATTRIBUTE_TOOL_TARGET_ITEM: u32 = 2012;
+pub const ATTRIBUTE_KILLSTREAKER: u32 = 2013;
+pub const ATTRIBUTE_SHEEN: u32 = 2014;
+pub const ATTRIBUTE_KILLSTREAK_TIER: u32 = 2025;
+pub const ATTRIBUTE_AUSTRALIUM: u32 = 2027;
+pub const ATTRIBUTE_TAUNT_PARTICLE_EFFECT: u32 = 2041;
+pub const ATTRIBUTE_FESTIVIZED: u32 = 2053;
+
+/// The (score type, count) attribute pairs of the counters an item comes with. The first pair
+/// is the item's own counter.
+pub const STRANGE_COUNTER_ATTRIBUTES: [(u32, u32); 3] = [
+    (ATTRIBUTE_KILL_EATER_SCORE_TYPE, ATTRIBUTE_KILL_EATER),
+    (293, 294),
+    (495, 494),
+];
+
+/// The (score type, count) attribute pairs of strange parts applied by the user.
+pub const STRANGE_PART_ATTRIBUTES: [(u32, u32); 3] = [
+    (380, 379),
+    (382, 381),
+    (384, 383),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Quality {
+    Normal,
+    Genuine,
+    Vintage,
+    Unusual,
+    Unique,
+    Community,
+    Valve,
+    SelfMade,
+    Customized,
+    Strange,
+    Completed,
+    Haunted,
+    Collectors,
+    DecoratedWeapon,
+    Other(u32),
+}
+
+impl Quality {
+    
+    /// The English name, as used in item names.
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Self::Normal => Some("Normal"),
+            Self::Genuine => Some("Genuine"),
+            Self::Vintage => Some("Vintage"),
+            Self::Unusual => Some("Unusual"),
+            Self::Unique => Some("Unique"),
+            Self::Community => Some("Community"),
+            Self::Valve => Some("Valve"),
+            Self::SelfMade => Some("Self-Made"),
+            Self::Customized => Some("Customized"),
+            Self::Strange => Some("Strange"),
+            Self::Completed => Some("Completed"),
+            Self::Haunted => Some("Haunted"),
+            Self::Collectors => Some("Collector's"),
+            Self::DecoratedWeapon => Some("Decorated Weapon"),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+impl From<u32> for Quality {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::Normal,
+            1 => Self::Genuine,
+            3 => Self::Vintage,
+            5 => Self::Unusual,
+            6 => Self::Unique,
+            7 => Self::Community,
+            8 => Self::Valve,
+            9 => Self::SelfMade,
+            10 => Self::Customized,
+            11 => Self::Strange,
+            12 => Self::Completed,
+            13 => Self::Haunted,
+            14 => Self::Collectors,
+            15 => Self::DecoratedWeapon,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<Quality> for u32 {
+    fn from(quality: Quality) -> Self {
+        match quality {
+            Quality::Normal => 0,
+            Quality::Genuine => 1,
+            Quality::Vintage => 3,
+            Quality::Unusual => 5,
+            Quality::Unique => 6,
+            Quality::Community => 7,
+            Quality::Valve => 8,
+            Quality::SelfMade => 9,
+            Quality::Customized => 10,
+            Quality::Strange => 11,
+            Quality::Completed => 12,
+            Quality::Haunted => 13,
+            Quality::Collectors => 14,
+            Quality::DecoratedWeapon => 15,
+            Quality::Other(other) => other,
+        }
+    }
+}
+
+/// How an item was originally created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Origin {
+    TimedDrop,
+    Achievement,
+    Purchased,
+    Traded,
+    Crafted,
+    StorePromotion,
+    Gifted,
+    SupportGranted,
+    FoundInCrate,
+    Earned,
+    ThirdPartyPromotion,
+    GiftWrapped,
+    HalloweenDrop,
+    SteamPurchase,
+    ForeignItem,
+    CDKey,
+    CollectionReward,
+    PreviewItem,
+    SteamWorkshopContribution,
+    PeriodicScoreReward,
+    MvMMissionCompletionReward,
+    MvMSquadSurplusReward,
+    RecipeOutput,
+    QuestDrop,
+    QuestLoanerItem,
+    TradeUp,
+    ViralCompetitiveBetaPassSpread,
+    Other(u32),
+}
+
+impl From<u32> for Origin {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::TimedDrop,
+            1 => Self::Achievement,
+            2 => Self::Purchased,
+            3 => Self::Traded,
+            4 => Self::Crafted,
+            5 => Self::StorePromotion,
+            6 => Self::Gifted,
+            7 => Self::SupportGranted,
+            8 => Self::FoundInCrate,
+            9 => Self::Earned,
+            10 => Self::ThirdPartyPromotion,
+            11 => Self::GiftWrapped,
+            12 => Self::HalloweenDrop,
+            13 => Self::SteamPurchase,
+            14 => Self::ForeignItem,
+            15 => Self::CDKey,
+            16 => Self::CollectionReward,
+            17 => Self::PreviewItem,
+            18 => Self::SteamWorkshopContribution,
+            19 => Self::PeriodicScoreReward,
+            20 => Self::MvMMissionCompletionReward,
+            21 => Self::MvMSquadSurplusReward,
+            22 => Self::RecipeOutput,
+            23 => Self::QuestDrop,
+            24 => Self::QuestLoanerItem,
+            25 => Self::TradeUp,
+            26 => Self::ViralCompetitiveBetaPassSpread,
+            other => Self::Other(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KillstreakTier {
+    Killstreak = 1,
+    Specialized = 2,
+    Professional = 3,
+}
+
+impl KillstreakTier {
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Killstreak => "Killstreak",
+            Self::Specialized => "Specialized Killstreak",
+            Self::Professional => "Professional Killstreak",
+        }
+    }
+}
+
+impl TryFrom<u32> for KillstreakTier {
+    type Error = u32;
+    
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Killstreak),
+            2 => Ok(Self::Specialized),
+            3 => Ok(Self::Professional),
+            _ => Err(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sheen {
+    TeamShine = 1,
+    DeadlyDaffodil = 2,
+    Manndarin = 3,
+    MeanGreen = 4,
+    AgonizingEmerald = 5,
+    VillainousViolet = 6,
+    HotRod = 7,
+}
+
+impl TryFrom<u32> for Sheen {
+    type Error = u32;
+    
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::TeamShine),
+            2 => Ok(Self::DeadlyDaffodil),
+            3 => Ok(Self::Manndarin),
+            4 => Ok(Self::MeanGreen),
+            5 => Ok(Self::AgonizingEmerald),
+            6 => Ok(Self::VillainousViolet),
+            7 => Ok(Self::HotRod),
+            _ => Err(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Killstreaker {
+    FireHorns = 2002,
+    CerebralDischarge = 2003,
+    Tornado = 2004,
+    Flames = 2005,
+    Singularity = 2006,
+    Incinerator = 2007,
+    HypnoBeam = 2008,
+}
+
+impl TryFrom<u32> for Killstreaker {
+    type Error = u32;
+    
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            2002 => Ok(Self::FireHorns),
+            2003 => Ok(Self::CerebralDischarge),
+            2004 => Ok(Self::Tornado),
+            2005 => Ok(Self::Flames),
+            2006 => Ok(Self::Singularity),
+            2007 => Ok(Self::Incinerator),
+            2008 => Ok(Self::HypnoBeam),
+            _ => Err(value),
+        }
+    }
+}
+
+/// The wear of a war paint or decorated weapon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Wear {
+    FactoryNew = 1,
+    MinimalWear = 2,
+    FieldTested = 3,
+    WellWorn = 4,
+    BattleScarred = 5,
+}
+
+impl Wear {
+    
+    /// Converts the `set_item_texture_wear` value, which steps by 0.2 from factory new.
+    pub fn from_float(value: f32) -> Option<Self> {
+        Self::try_from((value * 5.0).round() as u32).ok()
+    }
+    
+    pub fn as_float(&self) -> f32 {
+        *self as u32 as f32 / 5.0
+    }
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FactoryNew => "Factory New",
+            Self::MinimalWear => "Minimal Wear",
+            Self::FieldTested => "Field-Tested",
+            Self::WellWorn => "Well-Worn",
+            Self::BattleScarred => "Battle Scarred",
+        }
+    }
+}
+
+impl TryFrom<u32> for Wear {
+    type Error = u32;
+    
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::FactoryNew),
+            2 => Ok(Self::MinimalWear),
+            3 => Ok(Self::FieldTested),
+            4 => Ok(Self::WellWorn),
+            5 => Ok(Self::BattleScarred),
+            _ => Err(value),
+        }
+    }
+}
+
+/// A Halloween spell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Spell {
+    DieJob,
+    ChromaticCorruption,
+    PutrescentPigmentation,
+    SpectralSpectrum,
+    SinisterStaining,
+    TeamSpiritFootprints,
+    GangreenFootprints,
+    CorpseGrayFootprints,
+    ViolentVioletFootprints,
+    RottenOrangeFootprints,
+    BruisedPurpleFootprints,
+    HeadlessHorseshoes,
+    VoicesFromBeyond,
+    PumpkinBombs,
+    HalloweenFire,
+    Exorcism,
+}
+
+impl Spell {
+    
+    fn paint(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Self::DieJob),
+            1 => Some(Self::ChromaticCorruption),
+            2 => Some(Self::PutrescentPigmentation),
+            3 => Some(Self::SpectralSpectrum),
+            4 => Some(Self::SinisterStaining),
+            _ => None,
+        }
+    }
+    
+    fn footprints(value: u32) -> Option<Self> {
+        match value {
+            1 => Some(Self::TeamSpiritFootprints),
+            2 => Some(Self::HeadlessHorseshoes),
+            3100495 => Some(Self::CorpseGrayFootprints),
+            5322826 => Some(Self::ViolentVioletFootprints),
+            8208497 => Some(Self::BruisedPurpleFootprints),
+            8421376 => Some(Self::GangreenFootprints),
+            13595446 => Some(Self::RottenOrangeFootprints),
+            _ => None,
+        }
+    }
+}
+
+/// A strange counter, e.g. the item's kills or a strange part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrangeCounter {
+    /// The `kill_eater_score_types` id of what is counted.
+    pub score_type: u32,
+    pub count: u32,
+}
+
+/// An item with its attributes decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub id: u64,
+    pub original_id: u64,
+    pub account_id: u32,
+    pub def_index: u32,
+    pub quantity: u32,
+    pub level: u32,
+    pub quality: Quality,
+    pub origin: Origin,
+    pub style: u32,
+    pub position: InventoryPosition,
+    pub tradable: bool,
+    pub craftable: bool,
+    pub custom_name: Option<String>,
+    pub custom_desc: Option<String>,
+    pub killstreak_tier: Option<KillstreakTier>,
+    pub sheen: Option<Sheen>,
+    pub killstreaker: Option<Killstreaker>,
+    /// The unusual effect id, for both cosmetics and taunts.
+    pub particle_effect: Option<u32>,
+    /// The paint color as `0xRRGGBB`.
+    pub paint: Option<u32>,
+    /// The BLU paint color for team paints.
+    pub paint_secondary: Option<u32>,
+    pub spells: Vec<Spell>,
+    /// The item's own strange counter, if it has one.
+    pub strange_counter: Option<StrangeCounter>,
+    /// Strange parts applied to the item.
+    pub strange_parts: Vec<StrangeCounter>,
+    pub australium: bool,
+    pub festivized: bool,
+    pub paint_kit: Option<u32>,
+    pub wear: Option<Wear>,
+    pub crate_series: Option<u32>,
+    pub craft_number: Option<u32>,
+    /// The definition index of the item a tool such as a strangifier or killstreak kit applies to.
+    pub target_def_index: Option<u32>,
+    /// The definition index of the item a chemistry set produces.
+    pub output_def_index: Option<u32>,
+    pub output_quality: Option<Quality>,
+    /// The account id of the crafter.
+    pub crafted_by: Option<u32>,
+    /// The account id of the gifter.
+    pub gifted_by: Option<u32>,
+    /// The item inside a wrapped gift.
+    pub interior_item: Option<Box<Item>>,
+}
+
+impl Item {
+    
+    /// Decodes `item`, using `schema` for attributes missing from the built-in table.
+    pub fn new(item: &CSOEconItem, schema: Option<&Schema>) -> Self {
+        let attribute = |def_index: u32| -> Option<AttributeValue> {
+            item.get_attribute()
+                .iter()
+                .find(|attribute| attribute.get_def_index() == def_index)
+                .map(|attribute| attribute::decode(attribute, schema))
+        };
+        let attribute_u32 = |def_index: u32| attribute(def_index).and_then(|value| value.as_u32());
+        let attribute_string = |def_index: u32| {
+            attribute(def_index).and_then(|value| value.as_str().map(String::from))
+        };
+        let has_attribute = |def_index: u32| attribute_u32(def_index).is_some_and(|value| value != 0);
+        let counter = |(score_type, count): &(u32, u32)| {
+            attribute_u32(*count).map(|count| StrangeCounter {
+                score_type: attribute_u32(*score_type).unwrap_or_default(),
+                count,
+            })
+        };
+        let strange_counter = counter(&STRANGE_COUNTER_ATTRIBUTES[0]);
+        let strange_parts = STRANGE_PART_ATTRIBUTES
+            .iter()
+            .filter_map(counter)
+            .collect();
+        let spells = [
+            attribute_u32(ATTRIBUTE_SPELL_PAINT).and_then(Spell::paint),
+            attribute_u32(ATTRIBUTE_SPELL_FOOTSTEPS).and_then(Spell::footprints),
+            attribute(ATTRIBUTE_SPELL_VOICES).map(|_| Spell::VoicesFromBeyond),
+            attribute(ATTRIBUTE_SPELL_PUMPKIN_BOMBS).map(|_| Spell::PumpkinBombs),
+            attribute(ATTRIBUTE_SPELL_HALLOWEEN_FIRE).map(|_| Spell::HalloweenFire),
+            attribute(ATTRIBUTE_SPELL_EXORCISM).map(|_| Spell::Exorcism),
+        ]
+            .into_iter()
+            .flatten()
+            .collect();
+        let output = item.get_attribute()
+            .iter()
+            .filter_map(|attribute| match attribute::decode(attribute, schema) {
+                AttributeValue::DynamicRecipeComponent(component) => Some(component),
+                _ => None,
+            })
+            .find(|component| component.get_component_flags() & DYNAMIC_RECIPE_FLAG_IS_OUTPUT != 0);
+        let custom_name = Some(item.get_custom_name())
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .or_else(|| attribute_string(ATTRIBUTE_CUSTOM_NAME));
+        let custom_desc = Some(item.get_custom_desc())
+            .filter(|desc| !desc.is_empty())
+            .map(String::from)
+            .or_else(|| attribute_string(ATTRIBUTE_CUSTOM_DESC));
+        
+        Self {
+            id: item.get_id(),
+            original_id: item.get_original_id(),
+            account_id: item.get_account_id(),
+            def_index: item.get_def_index(),
+            quantity: item.get_quantity(),
+            level: item.get_level(),
+            quality: Quality::from(item.get_quality()),
+            origin: Origin::from(item.get_origin()),
+            style: item.get_style(),
+            position: InventoryPosition::from(item),
+            tradable: item.get_flags() & FLAG_CANNOT_TRADE == 0
+                && !has_attribute(ATTRIBUTE_CANNOT_TRADE),
+            craftable: item.get_flags() & FLAG_CANNOT_BE_USED_IN_CRAFTING == 0
+                && !has_attribute(ATTRIBUTE_NEVER_CRAFTABLE),
+            custom_name,
+            custom_desc,
+            killstreak_tier: attribute_u32(ATTRIBUTE_KILLSTREAK_TIER)
+                .and_then(|value| KillstreakTier::try_from(value).ok()),
+            sheen: attribute_u32(ATTRIBUTE_SHEEN)
+                .and_then(|value| Sheen::try_from(value).ok()),
+            killstreaker: attribute_u32(ATTRIBUTE_KILLSTREAKER)
+                .and_then(|value| Killstreaker::try_from(value).ok()),
+            // taunts can carry an unset cosmetic effect alongside their taunt effect
+            particle_effect: attribute_u32(ATTRIBUTE_PARTICLE_EFFECT)
+                .filter(|effect| *effect != 0)
+                .or_else(|| attribute_u32(ATTRIBUTE_TAUNT_PARTICLE_EFFECT))
+                .filter(|effect| *effect != 0),
+            paint: attribute_u32(ATTRIBUTE_PAINT),
+            paint_secondary: attribute_u32(ATTRIBUTE_PAINT_SECONDARY),
+            spells,
+            strange_counter,
+            strange_parts,
+            australium: has_attribute(ATTRIBUTE_AUSTRALIUM),
+            festivized: has_attribute(ATTRIBUTE_FESTIVIZED),
+            paint_kit: attribute_u32(ATTRIBUTE_PAINT_KIT),
+            wear: attribute(ATTRIBUTE_TEXTURE_WEAR)
+                .and_then(|value| value.as_f32())
+                .and_then(Wear::from_float),
+            crate_series: attribute_u32(ATTRIBUTE_CRATE_SERIES),
+            craft_number: attribute_u32(ATTRIBUTE_UNIQUE_CRAFT_INDEX),
+            target_def_index: attribute_u32(ATTRIBUTE_TOOL_TARGET_ITEM),
+            output_def_index: output.as_ref().map(|component| component.get_def_index()),
+            output_quality: output.as_ref().map(|component| Quality::from(component.get_item_quality())),
+            crafted_by: attribute_u32(ATTRIBUTE_MAKERS_MARK_ID),
+            gifted_by: attribute_u32(ATTRIBUTE_GIFTER_ACCOUNT_ID),
+            interior_item: if item.has_interior_item() {
+                Some(Box::new(Self::new(item.get_interior_item(), schema)))
+            } else {
+                None
+            },
+        }
+    }
+    
+    /// Whether the item has a strange counter, either by quality or as an elevated quality.
+    pub fn is_strange(&self) -> bool {
+        self.quality == Quality::Strange || self.strange_counter.is_some()
+    }
+}
+
+impl From<&CSOEconItem> for Item {
+    fn from(item: &CSOEconItem) -> Self {
+        Self::new(item, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protobuf::Message;
+    use tf2_protobuf::base_gcmessages::CSOEconItemAttribute;
+    use tf2_protobuf::tf_gcmessages::CAttribute_String;
+    
+    fn with_attributes(attributes: &[(u32, u32)]) -> CSOEconItem {
+        let mut item = CSOEconItem::new();
+        
+        item.set_quality(11);
+        
+        for (def_index, value) in attributes {
+            let mut attribute = CSOEconItemAttribute::new();
+            
+            attribute.set_def_index(*def_index);
+            attribute.set_value(*value);
+            item.mut_attribute().push(attribute);
+        }
+        
+        item
+    }
+    
+    fn float(value: f32) -> u32 {
+        value.to_bits()
+    }
+    
+    fn string_attribute(def_index: u32, value: &str) -> CSOEconItemAttribute {
+        let mut message = CAttribute_String::new();
+        let mut attribute = CSOEconItemAttribute::new();
+        
+        message.set_value(value.to_string());
+        attribute.set_def_index(def_index);
+        attribute.set_value_bytes(message.write_to_bytes().unwrap());
+        attribute
+    }
+    
+    #[test]
+    fn reads_quality_and_origin() {
+        let mut item = with_attributes(&[]);
+        
+        item.set_origin(4);
+        
+        let decoded = Item::from(&item);
+        
+        assert_eq!(decoded.quality, Quality::Strange);
+        assert_eq!(decoded.origin, Origin::Crafted);
+        
+        item.set_quality(99);
+        item.set_origin(99);
+        
+        let decoded = Item::from(&item);
+        
+        assert_eq!(decoded.quality, Quality::Other(99));
+        assert_eq!(decoded.origin, Origin::Other(99));
+    }
+    
+    #[test]
+    fn reads_tradable_and_craftable() {
+        let item = Item::from(&with_attributes(&[]));
+        
+        assert!(item.tradable);
+        assert!(item.craftable);
+        
+        let item = Item::from(&with_attributes(&[
+            (ATTRIBUTE_CANNOT_TRADE, float(1.0)),
+            (ATTRIBUTE_NEVER_CRAFTABLE, float(1.0)),
+        ]));
+        
+        assert!(!item.tradable);
+        assert!(!item.craftable);
+        
+        let mut item = with_attributes(&[]);
+        
+        item.set_flags(FLAG_CANNOT_TRADE | FLAG_CANNOT_BE_USED_IN_CRAFTING);
+        
+        let item = Item::from(&item);
+        
+        assert!(!item.tradable);
+        assert!(!item.craftable);
+    }
+    
+    #[test]
+    fn reads_killstreaks() {
+        let item = Item::from(&with_attributes(&[
+            (ATTRIBUTE_KILLSTREAK_TIER, float(3.0)),
+            (ATTRIBUTE_SHEEN, float(7.0)),
+            (ATTRIBUTE_KILLSTREAKER, float(2008.0)),
+        ]));
+        
+        assert_eq!(item.killstreak_tier, Some(KillstreakTier::Professional));
+        assert_eq!(item.sheen, Some(Sheen::HotRod));
+        assert_eq!(item.killstreaker, Some(Killstreaker::HypnoBeam));
+        
+        let item = Item::from(&with_attributes(&[(ATTRIBUTE_KILLSTREAK_TIER, float(9.0))]));
+        
+        assert_eq!(item.killstreak_tier, None);
+    }
+    
+    #[test]
+    fn reads_unusual_effects() {
+        let hat = Item::from(&with_attributes(&[(ATTRIBUTE_PARTICLE_EFFECT, float(13.0))]));
+        let taunt = Item::from(&with_attributes(&[
+            (ATTRIBUTE_PARTICLE_EFFECT, float(0.0)),
+            (ATTRIBUTE_TAUNT_PARTICLE_EFFECT, float(3001.0)),
+        ]));
+        let unset = Item::from(&with_attributes(&[(ATTRIBUTE_PARTICLE_EFFECT, float(0.0))]));
+        
+        assert_eq!(hat.particle_effect, Some(13));
+        assert_eq!(taunt.particle_effect, Some(3001));
+        assert_eq!(unset.particle_effect, None);
+    }
+    
+    #[test]
+    fn reads_paint_and_spells() {
+        let item = Item::from(&with_attributes(&[
+            (ATTRIBUTE_PAINT, float(12073019.0)),
+            (ATTRIBUTE_PAINT_SECONDARY, float(5801378.0)),
+            (ATTRIBUTE_SPELL_PAINT, float(3.0)),
+            (ATTRIBUTE_SPELL_FOOTSTEPS, float(8421376.0)),
+            (ATTRIBUTE_SPELL_EXORCISM, float(1.0)),
+        ]));
+        
+        assert_eq!(item.paint, Some(12073019));
+        assert_eq!(item.paint_secondary, Some(5801378));
+        assert_eq!(item.spells, vec![
+            Spell::SpectralSpectrum,
+            Spell::GangreenFootprints,
+            Spell::Exorcism,
+        ]);
+    }
+    
+    #[test]
+    fn reads_australium_and_festivized() {
+        let item = Item::from(&with_attributes(&[
+            (ATTRIBUTE_AUSTRALIUM, float(1.0)),
+            (ATTRIBUTE_FESTIVIZED, float(1.0)),
+        ]));
+        let unset = Item::from(&with_attributes(&[(ATTRIBUTE_AUSTRALIUM, float(0.0))]));
+        
+        assert!(item.australium);
+        assert!(item.festivized);
+        assert!(!unset.australium);
+        assert!(!unset.festivized);
+    }
+    
+    #[test]
+    fn reads_war_paints() {
+        let item = Item::from(&with_attributes(&[
+            (ATTRIBUTE_PAINT_KIT, 102),
+            (ATTRIBUTE_TEXTURE_WEAR, float(0.6)),
+        ]));
+        
+        assert_eq!(item.paint_kit, Some(102));
+        assert_eq!(item.wear, Some(Wear::FieldTested));
+    }
+    
+    #[test]
+    fn reads_custom_name_and_description() {
+        let mut item = with_attributes(&[]);
+        
+        item.mut_attribute().push(string_attribute(ATTRIBUTE_CUSTOM_NAME, "Attribute Name"));
+        item.mut_attribute().push(string_attribute(ATTRIBUTE_CUSTOM_DESC, "Attribute Desc"));
+        
+        let decoded = Item::from(&item);
+        
+        assert_eq!(decoded.custom_name.as_deref(), Some("Attribute Name"));
+        assert_eq!(decoded.custom_desc.as_deref(), Some("Attribute Desc"));
+        
+        // the message fields take precedence over the attributes
+        item.set_custom_name("Field Name".into());
+        item.set_custom_desc("Field Desc".into());
+        
+        let decoded = Item::from(&item);
+        
+        assert_eq!(decoded.custom_name.as_deref(), Some("Field Name"));
+        assert_eq!(decoded.custom_desc.as_deref(), Some("Field Desc"));
+    }
+    
+    #[test]
+    fn reads_crafter_and_gifter() {
+        let item = Item::from(&with_attributes(&[
+            (ATTRIBUTE_MAKERS_MARK_ID, 1234),
+            (ATTRIBUTE_GIFTER_ACCOUNT_ID, 5678),
+        ]));
+        
+        assert_eq!(item.crafted_by, Some(1234));
+        assert_eq!(item.gifted_by, Some(5678));
+    }
+    
+    #[test]
+    fn reads_interior_item() {
+        let mut gift = with_attributes(&[(ATTRIBUTE_GIFTER_ACCOUNT_ID, 5678)]);
+        let mut interior = with_attributes(&[(ATTRIBUTE_PARTICLE_EFFECT, float(13.0))]);
+        
+        interior.set_id(2);
+        interior.set_def_index(126);
+        gift.set_id(1);
+        gift.set_interior_item(interior);
+        
+        let gift = Item::from(&gift);
+        let interior = gift.interior_item.as_deref().unwrap();
+        
+        assert_eq!(gift.id, 1);
+        assert_eq!(interior.id, 2);
+        assert_eq!(interior.def_index, 126);
+        assert_eq!(interior.particle_effect, Some(13));
+        assert!(interior.interior_item.is_none());
+    }
+    
+    #[test]
+    fn reads_strange_parts_from_user_slots() {
+        let item = Item::from(&with_attributes(&[
+            (ATTRIBUTE_KILL_EATER, 1500),
+            (ATTRIBUTE_KILL_EATER_SCORE_TYPE, float(0.0)),
+            // a built-in counter, not a part
+            (294, 12),
+            (293, float(64.0)),
+            (379, 40),
+            (380, float(17.0)),
+            (381, 7),
+            (382, float(31.0)),
+            (383, 200),
+            (384, float(1.0)),
+        ]));
+        
+        assert_eq!(item.strange_counter, Some(StrangeCounter { score_type: 0, count: 1500 }));
+        assert_eq!(item.strange_parts, vec![
+            StrangeCounter { score_type: 17, count: 40 },
+            StrangeCounter { score_type: 31, count: 7 },
+            StrangeCounter { score_type: 1, count: 200 },
+        ]);
+        assert!(item.is_strange());
+    }
+    
+    #[test]
+    fn ignores_built_in_counters_as_parts() {
+        let item = Item::from(&with_attributes(&[
+            (494, 3),
+            (495, float(87.0)),
+        ]));
+        
+        assert_eq!(item.strange_counter, None);
+        assert!(item.strange_parts.is_empty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,380 @@
+//! Competitive ratings, ladder stats and match history.
+
+use std::io::{self, Write};
+use protobuf::ProtobufEnum;
+use tf2_protobuf::tf_gcmessages::{
+    CSOTFRatingData,
+    CSOTFLadderPlayerStats,
+    CSOTFMatchResultPlayerStats,
+    ETFMatchGroup,
+};
+
+/// A matchmaking rating kept by the GC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RatingType {
+    Drillo6v6 = 0,
+    Drillo6v6PlayerAcknowledged = 1,
+    Glicko6v6 = 2,
+    Drillo12v12 = 3,
+    CasualGlicko12v12 = 4,
+    CasualXP = 5,
+    CasualXPPlayerAcknowledged = 6,
+    Rank6v6 = 7,
+    Rank6v6PlayerAcknowledged = 8,
+    CasualRank12v12 = 9,
+    CasualRank12v12PlayerAcknowledged = 10,
+}
+
+impl TryFrom<i32> for RatingType {
+    type Error = i32;
+    
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Drillo6v6),
+            1 => Ok(Self::Drillo6v6PlayerAcknowledged),
+            2 => Ok(Self::Glicko6v6),
+            3 => Ok(Self::Drillo12v12),
+            4 => Ok(Self::CasualGlicko12v12),
+            5 => Ok(Self::CasualXP),
+            6 => Ok(Self::CasualXPPlayerAcknowledged),
+            7 => Ok(Self::Rank6v6),
+            8 => Ok(Self::Rank6v6PlayerAcknowledged),
+            9 => Ok(Self::CasualRank12v12),
+            10 => Ok(Self::CasualRank12v12PlayerAcknowledged),
+            _ => Err(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rating {
+    pub rating_type: RatingType,
+    /// The rating, rank or experience depending on the type.
+    pub primary: u32,
+    pub secondary: u32,
+    pub tertiary: u32,
+}
+
+impl TryFrom<&CSOTFRatingData> for Rating {
+    /// The unknown rating type.
+    type Error = i32;
+    
+    fn try_from(rating: &CSOTFRatingData) -> Result<Self, Self::Error> {
+        Ok(Self {
+            rating_type: RatingType::try_from(rating.get_rating_type())?,
+            primary: rating.get_rating_primary(),
+            secondary: rating.get_rating_secondary(),
+            tertiary: rating.get_rating_tertiary(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Medal {
+    None = 0,
+    Bronze = 1,
+    Silver = 2,
+    Gold = 3,
+}
+
+impl From<u32> for Medal {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Self::Bronze,
+            2 => Self::Silver,
+            3 => Self::Gold,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Medals earned for a stat over a season.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MedalCounts {
+    pub bronze: u32,
+    pub silver: u32,
+    pub gold: u32,
+}
+
+/// Stats for a season in a match group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LadderStats {
+    pub match_group: Option<ETFMatchGroup>,
+    pub season_id: u32,
+    pub games: u32,
+    pub score: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub damage: u32,
+    pub healing: u32,
+    pub support: u32,
+    pub score_medals: MedalCounts,
+    pub kills_medals: MedalCounts,
+    pub damage_medals: MedalCounts,
+    pub healing_medals: MedalCounts,
+    pub support_medals: MedalCounts,
+}
+
+impl From<&CSOTFLadderPlayerStats> for LadderStats {
+    fn from(stats: &CSOTFLadderPlayerStats) -> Self {
+        Self {
+            match_group: ETFMatchGroup::from_i32(stats.get_match_group()),
+            season_id: stats.get_season_id(),
+            games: stats.get_games(),
+            score: stats.get_score(),
+            kills: stats.get_kills(),
+            deaths: stats.get_deaths(),
+            damage: stats.get_damage(),
+            healing: stats.get_healing(),
+            support: stats.get_support(),
+            score_medals: MedalCounts {
+                bronze: stats.get_score_bronze(),
+                silver: stats.get_score_silver(),
+                gold: stats.get_score_gold(),
+            },
+            kills_medals: MedalCounts {
+                bronze: stats.get_kills_bronze(),
+                silver: stats.get_kills_silver(),
+                gold: stats.get_kills_gold(),
+            },
+            damage_medals: MedalCounts {
+                bronze: stats.get_damage_bronze(),
+                silver: stats.get_damage_silver(),
+                gold: stats.get_damage_gold(),
+            },
+            healing_medals: MedalCounts {
+                bronze: stats.get_healing_bronze(),
+                silver: stats.get_healing_silver(),
+                gold: stats.get_healing_gold(),
+            },
+            support_medals: MedalCounts {
+                bronze: stats.get_support_bronze(),
+                silver: stats.get_support_silver(),
+                gold: stats.get_support_gold(),
+            },
+        }
+    }
+}
+
+/// A player's result in a past match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchResult {
+    pub match_id: u64,
+    pub match_group: Option<ETFMatchGroup>,
+    /// Unix timestamp of when the match ended.
+    pub endtime: u32,
+    pub season_id: u32,
+    pub status: u32,
+    pub team: u32,
+    pub winning_team: u32,
+    pub map_index: u32,
+    pub score: u32,
+    pub ping: u32,
+    pub display_rating: u32,
+    pub display_rating_change: i32,
+    pub rank: u32,
+    /// Bitset of the classes played.
+    pub classes_played: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub damage: u32,
+    pub healing: u32,
+    pub support: u32,
+    pub score_medal: Medal,
+    pub kills_medal: Medal,
+    pub damage_medal: Medal,
+    pub healing_medal: Medal,
+    pub support_medal: Medal,
+}
+
+impl MatchResult {
+    
+    pub fn is_win(&self) -> bool {
+        self.team == self.winning_team
+    }
+}
+
+impl From<&CSOTFMatchResultPlayerStats> for MatchResult {
+    fn from(stats: &CSOTFMatchResultPlayerStats) -> Self {
+        Self {
+            match_id: stats.get_match_id(),
+            match_group: ETFMatchGroup::from_i32(stats.get_match_group()),
+            endtime: stats.get_endtime(),
+            season_id: stats.get_season_id(),
+            status: stats.get_status(),
+            team: stats.get_team(),
+            winning_team: stats.get_winning_team(),
+            map_index: stats.get_map_index(),
+            score: stats.get_score(),
+            ping: stats.get_ping(),
+            display_rating: stats.get_display_rating(),
+            display_rating_change: stats.get_display_rating_change(),
+            rank: stats.get_rank(),
+            classes_played: stats.get_classes_played(),
+            kills: stats.get_kills(),
+            deaths: stats.get_deaths(),
+            damage: stats.get_damage(),
+            healing: stats.get_healing(),
+            support: stats.get_support(),
+            score_medal: stats.get_score_medal().into(),
+            kills_medal: stats.get_kills_medal().into(),
+            damage_medal: stats.get_damage_medal().into(),
+            healing_medal: stats.get_healing_medal().into(),
+            support_medal: stats.get_support_medal().into(),
+        }
+    }
+}
+
+fn match_group_value(match_group: Option<ETFMatchGroup>) -> i32 {
+    match_group
+        .unwrap_or(ETFMatchGroup::k_eTFMatchGroup_Invalid)
+        .value()
+}
+
+/// Writes match results as CSV with a header row.
+pub fn write_match_history_csv<'a, W, I>(writer: &mut W, results: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a MatchResult>,
+{
+    writeln!(
+        writer,
+        "match_id,match_group,endtime,season_id,win,map_index,score,rank,display_rating,\
+        display_rating_change,kills,deaths,damage,healing,support,score_medal,kills_medal,\
+        damage_medal,healing_medal,support_medal",
+    )?;
+    
+    for result in results {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            result.match_id,
+            match_group_value(result.match_group),
+            result.endtime,
+            result.season_id,
+            result.is_win() as u8,
+            result.map_index,
+            result.score,
+            result.rank,
+            result.display_rating,
+            result.display_rating_change,
+            result.kills,
+            result.deaths,
+            result.damage,
+            result.healing,
+            result.support,
+            result.score_medal as u32,
+            result.kills_medal as u32,
+            result.damage_medal as u32,
+            result.healing_medal as u32,
+            result.support_medal as u32,
+        )?;
+    }
+    
+    Ok(())
+}
+
+/// Writes ladder stats as CSV with a header row. Medal counts are written as
+/// `bronze/silver/gold`.
+pub fn write_ladder_stats_csv<'a, W, I>(writer: &mut W, stats: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a LadderStats>,
+{
+    fn medals(counts: &MedalCounts) -> String {
+        format!("{}/{}/{}", counts.bronze, counts.silver, counts.gold)
+    }
+    
+    writeln!(
+        writer,
+        "match_group,season_id,games,score,kills,deaths,damage,healing,support,score_medals,\
+        kills_medals,damage_medals,healing_medals,support_medals",
+    )?;
+    
+    for stats in stats {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            match_group_value(stats.match_group),
+            stats.season_id,
+            stats.games,
+            stats.score,
+            stats.kills,
+            stats.deaths,
+            stats.damage,
+            stats.healing,
+            stats.support,
+            medals(&stats.score_medals),
+            medals(&stats.kills_medals),
+            medals(&stats.damage_medals),
+            medals(&stats.healing_medals),
+            medals(&stats.support_medals),
+        )?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn writes_match_history_csv() {
+        let mut stats = CSOTFMatchResultPlayerStats::new();
+        
+        stats.set_match_id(5);
+        stats.set_match_group(ETFMatchGroup::k_eTFMatchGroup_Casual_Default as i32);
+        stats.set_endtime(1600000000);
+        stats.set_season_id(3);
+        stats.set_team(2);
+        stats.set_winning_team(2);
+        stats.set_map_index(12);
+        stats.set_score(40);
+        stats.set_display_rating_change(-25);
+        stats.set_kills(10);
+        stats.set_deaths(4);
+        stats.set_kills_medal(3);
+        
+        let mut csv = Vec::new();
+        
+        write_match_history_csv(&mut csv, &[MatchResult::from(&stats)]).unwrap();
+        
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+        assert!(lines[0].starts_with("match_id,match_group,endtime,season_id,win,"));
+        assert_eq!(lines[1], "5,7,1600000000,3,1,12,40,0,0,-25,10,4,0,0,0,0,3,0,0,0");
+    }
+    
+    #[test]
+    fn writes_ladder_stats_csv() {
+        let mut stats = CSOTFLadderPlayerStats::new();
+        
+        stats.set_match_group(ETFMatchGroup::k_eTFMatchGroup_Ladder_Default as i32);
+        stats.set_season_id(4);
+        stats.set_games(20);
+        stats.set_kills(150);
+        stats.set_kills_bronze(3);
+        stats.set_kills_silver(2);
+        stats.set_kills_gold(1);
+        
+        let mut stats = LadderStats::from(&stats);
+        let mut csv = Vec::new();
+        
+        write_ladder_stats_csv(&mut csv, [&stats]).unwrap();
+        stats.match_group = None;
+        write_ladder_stats_csv(&mut csv, [&stats]).unwrap();
+        
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+        assert_eq!(lines[1], "2,4,20,0,150,0,0,0,0,0/0/0,3/2/1,0/0/0,0/0/0,0/0/0");
+        // an unknown match group is written as invalid
+        assert!(lines[3].starts_with("-1,4,"));
+    }
+}
\ No newline at end of file
@@ -1,9 +1,37 @@
 mod message;
 mod team_fortress_2;
+mod name;
 
 pub mod response;
 pub mod app;
 pub mod request;
+pub mod error;
+pub mod recipe;
+pub mod metal;
+pub mod so_cache;
+pub mod loadout;
+pub mod backpack;
+pub mod event;
+pub mod vdf;
+pub mod schema;
+pub mod attribute;
+pub mod item;
+pub mod sku;
+pub mod localization;
+pub mod dynamic_recipe;
+pub mod notification;
+pub mod inspect;
+pub mod party;
+pub mod matchmaking;
+pub mod lobby;
+pub mod ladder;
+pub mod quest;
+pub mod text_format;
+pub mod protodef;
+pub mod paint_kit;
+pub mod xp;
+pub mod war;
 
 pub use tf2_protobuf as proto;
-pub use team_fortress_2::{TeamFortress2, };
\ No newline at end of file
+pub use team_fortress_2::{TeamFortress2, };
+pub use error::Error;
\ No newline at end of file
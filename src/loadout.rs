@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use tf2_protobuf::base_gcmessages::{
+    CSOEconItem,
+    CSOClassPresetClientData,
+    CSOEconItemPresetInstance,
+};
+
+/// A player class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TFClass {
+    Scout = 1,
+    Sniper = 2,
+    Soldier = 3,
+    Demoman = 4,
+    Medic = 5,
+    Heavy = 6,
+    Pyro = 7,
+    Spy = 8,
+    Engineer = 9,
+}
+
+impl TFClass {
+    
+    pub const ALL: [TFClass; 9] = [
+        TFClass::Scout,
+        TFClass::Soldier,
+        TFClass::Pyro,
+        TFClass::Demoman,
+        TFClass::Heavy,
+        TFClass::Engineer,
+        TFClass::Medic,
+        TFClass::Sniper,
+        TFClass::Spy,
+    ];
+    
+    /// Parses a class name as used in the item schema, e.g. `demoman`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "scout" => Some(Self::Scout),
+            "sniper" => Some(Self::Sniper),
+            "soldier" => Some(Self::Soldier),
+            "demoman" => Some(Self::Demoman),
+            "medic" => Some(Self::Medic),
+            "heavy" => Some(Self::Heavy),
+            "pyro" => Some(Self::Pyro),
+            "spy" => Some(Self::Spy),
+            "engineer" => Some(Self::Engineer),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u32> for TFClass {
+    type Error = u32;
+    
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Scout),
+            2 => Ok(Self::Sniper),
+            3 => Ok(Self::Soldier),
+            4 => Ok(Self::Demoman),
+            5 => Ok(Self::Medic),
+            6 => Ok(Self::Heavy),
+            7 => Ok(Self::Pyro),
+            8 => Ok(Self::Spy),
+            9 => Ok(Self::Engineer),
+            _ => Err(value),
+        }
+    }
+}
+
+/// A loadout position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LoadoutSlot {
+    Primary = 0,
+    Secondary = 1,
+    Melee = 2,
+    Utility = 3,
+    Building = 4,
+    PDA = 5,
+    PDA2 = 6,
+    Head = 7,
+    Misc = 8,
+    Action = 9,
+    Misc2 = 10,
+    Taunt = 11,
+    Taunt2 = 12,
+    Taunt3 = 13,
+    Taunt4 = 14,
+    Taunt5 = 15,
+    Taunt6 = 16,
+    Taunt7 = 17,
+    Taunt8 = 18,
+}
+
+impl LoadoutSlot {
+    
+    /// Parses a slot name as used in the item schema, e.g. `primary`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "primary" => Some(Self::Primary),
+            "secondary" => Some(Self::Secondary),
+            "melee" => Some(Self::Melee),
+            "utility" => Some(Self::Utility),
+            "building" => Some(Self::Building),
+            "pda" => Some(Self::PDA),
+            "pda2" => Some(Self::PDA2),
+            "head" => Some(Self::Head),
+            "misc" => Some(Self::Misc),
+            "action" => Some(Self::Action),
+            "misc2" => Some(Self::Misc2),
+            "taunt" => Some(Self::Taunt),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u32> for LoadoutSlot {
+    type Error = u32;
+    
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Primary),
+            1 => Ok(Self::Secondary),
+            2 => Ok(Self::Melee),
+            3 => Ok(Self::Utility),
+            4 => Ok(Self::Building),
+            5 => Ok(Self::PDA),
+            6 => Ok(Self::PDA2),
+            7 => Ok(Self::Head),
+            8 => Ok(Self::Misc),
+            9 => Ok(Self::Action),
+            10 => Ok(Self::Misc2),
+            11 => Ok(Self::Taunt),
+            12 => Ok(Self::Taunt2),
+            13 => Ok(Self::Taunt3),
+            14 => Ok(Self::Taunt4),
+            15 => Ok(Self::Taunt5),
+            16 => Ok(Self::Taunt6),
+            17 => Ok(Self::Taunt7),
+            18 => Ok(Self::Taunt8),
+            _ => Err(value),
+        }
+    }
+}
+
+/// The items equipped by a class, keyed by slot. Slots without an item use the stock item.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Loadout {
+    pub items: HashMap<LoadoutSlot, u64>,
+}
+
+impl Loadout {
+    
+    pub fn item(&self, slot: LoadoutSlot) -> Option<u64> {
+        self.items.get(&slot).copied()
+    }
+}
+
+/// The loadout presets of a class.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ClassPresets {
+    /// The preset in use, from 0 to 3 for presets A to D.
+    pub active_preset_id: u32,
+    /// The items of each preset, keyed by preset id.
+    pub presets: HashMap<u32, Loadout>,
+}
+
+impl ClassPresets {
+    
+    /// The items of the preset in use.
+    pub fn active(&self) -> Option<&Loadout> {
+        self.presets.get(&self.active_preset_id)
+    }
+}
+
+/// Builds the presets of every class from the active preset of each class and the items
+/// placed in presets.
+pub fn presets<'a, C, P>(class_data: C, instances: P) -> HashMap<TFClass, ClassPresets>
+where
+    C: IntoIterator<Item = &'a CSOClassPresetClientData>,
+    P: IntoIterator<Item = &'a CSOEconItemPresetInstance>,
+{
+    let mut presets: HashMap<TFClass, ClassPresets> = HashMap::new();
+    
+    for data in class_data {
+        if let Ok(class) = TFClass::try_from(data.get_class_id()) {
+            presets.entry(class).or_default().active_preset_id = data.get_active_preset_id();
+        }
+    }
+    
+    for instance in instances {
+        let class = TFClass::try_from(instance.get_class_id());
+        let slot = LoadoutSlot::try_from(instance.get_slot_id());
+        
+        if let (Ok(class), Ok(slot)) = (class, slot) {
+            presets
+                .entry(class)
+                .or_default()
+                .presets
+                .entry(instance.get_preset_id())
+                .or_default()
+                .items
+                .insert(slot, instance.get_item_id());
+        }
+    }
+    
+    presets
+}
+
+/// Builds the loadout of every class from the equipped state of `items`.
+pub fn loadouts<'a, I>(items: I) -> HashMap<TFClass, Loadout>
+where
+    I: IntoIterator<Item = &'a CSOEconItem>,
+{
+    let mut loadouts: HashMap<TFClass, Loadout> = HashMap::new();
+    
+    for item in items {
+        for equipped in item.get_equipped_state() {
+            let class = TFClass::try_from(equipped.get_new_class());
+            let slot = LoadoutSlot::try_from(equipped.get_new_slot());
+            
+            if let (Ok(class), Ok(slot)) = (class, slot) {
+                loadouts
+                    .entry(class)
+                    .or_default()
+                    .items
+                    .insert(slot, item.get_id());
+            }
+        }
+    }
+    
+    loadouts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tf2_protobuf::base_gcmessages::CSOEconItemEquipped;
+    
+    fn item(id: u64, equipped: &[(u32, u32)]) -> CSOEconItem {
+        let mut item = CSOEconItem::new();
+        
+        item.set_id(id);
+        
+        for (class, slot) in equipped {
+            let mut equipped = CSOEconItemEquipped::new();
+            
+            equipped.set_new_class(*class);
+            equipped.set_new_slot(*slot);
+            item.mut_equipped_state().push(equipped);
+        }
+        
+        item
+    }
+    
+    #[test]
+    fn parses_names() {
+        assert_eq!(TFClass::from_name("Demoman"), Some(TFClass::Demoman));
+        assert_eq!(TFClass::from_name("demo"), None);
+        assert_eq!(LoadoutSlot::from_name("PDA2"), Some(LoadoutSlot::PDA2));
+        assert_eq!(LoadoutSlot::from_name("wearable"), None);
+    }
+    
+    #[test]
+    fn converts_values() {
+        for class in TFClass::ALL {
+            assert_eq!(TFClass::try_from(class as u32), Ok(class));
+        }
+        
+        assert_eq!(TFClass::try_from(0), Err(0));
+        assert_eq!(LoadoutSlot::try_from(18), Ok(LoadoutSlot::Taunt8));
+        assert_eq!(LoadoutSlot::try_from(19), Err(19));
+    }
+    
+    #[test]
+    fn builds_loadouts_from_equipped_state() {
+        let items = [
+            item(1, &[(3, 0)]),
+            // Shared between classes.
+            item(2, &[(1, 7), (3, 7)]),
+            // Unknown classes and slots are ignored.
+            item(3, &[(0, 0), (3, 65535)]),
+            item(4, &[]),
+        ];
+        let loadouts = loadouts(&items);
+        let soldier = &loadouts[&TFClass::Soldier];
+        
+        assert_eq!(loadouts.len(), 2);
+        assert_eq!(soldier.items.len(), 2);
+        assert_eq!(soldier.item(LoadoutSlot::Primary), Some(1));
+        assert_eq!(soldier.item(LoadoutSlot::Head), Some(2));
+        assert_eq!(loadouts[&TFClass::Scout].item(LoadoutSlot::Head), Some(2));
+        assert_eq!(soldier.item(LoadoutSlot::Melee), None);
+    }
+    
+    #[test]
+    fn builds_presets() {
+        let mut soldier = CSOClassPresetClientData::new();
+        let instance = |class_id: u32, preset_id: u32, slot_id: u32, item_id: u64| {
+            let mut instance = CSOEconItemPresetInstance::new();
+            
+            instance.set_class_id(class_id);
+            instance.set_preset_id(preset_id);
+            instance.set_slot_id(slot_id);
+            instance.set_item_id(item_id);
+            instance
+        };
+        
+        soldier.set_class_id(3);
+        soldier.set_active_preset_id(1);
+        
+        let instances = [
+            instance(3, 0, 0, 10),
+            instance(3, 1, 0, 11),
+            instance(3, 1, 7, 12),
+            instance(1, 2, 2, 13),
+            instance(0, 0, 0, 14),
+        ];
+        let presets = presets([&soldier], &instances);
+        let soldier = &presets[&TFClass::Soldier];
+        let scout = &presets[&TFClass::Scout];
+        
+        assert_eq!(presets.len(), 2);
+        assert_eq!(soldier.active_preset_id, 1);
+        assert_eq!(soldier.active().and_then(|loadout| loadout.item(LoadoutSlot::Head)), Some(12));
+        assert_eq!(soldier.presets[&0].item(LoadoutSlot::Primary), Some(10));
+        assert_eq!(scout.active_preset_id, 0);
+        assert!(scout.active().is_none());
+        assert_eq!(scout.presets[&2].item(LoadoutSlot::Melee), Some(13));
+    }
+}
\ No newline at end of file
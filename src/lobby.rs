@@ -0,0 +1,165 @@
+//! A view of the [`CSOTFGameServerLobby`] created when a match is found.
+
+use protobuf::ProtobufEnum;
+use tf2_protobuf::tf_gcmessages::{
+    CSOTFGameServerLobby,
+    CSOTFGameServerLobby_State,
+    CTFLobbyPlayerProto,
+    CTFLobbyPlayerProto_ConnectState,
+    CTFLobbyPlayerProto_Type,
+    ETFMatchGroup,
+    TF_GC_GameState,
+    TF_GC_TEAM,
+};
+
+/// A player in a lobby.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LobbyPlayer {
+    pub steamid: u64,
+    pub name: String,
+    pub team: TF_GC_TEAM,
+    pub connect_state: CTFLobbyPlayerProto_ConnectState,
+    pub player_type: CTFLobbyPlayerProto_Type,
+    /// The party the player was in when matched.
+    pub original_party_id: u64,
+    pub badge_level: u32,
+    pub rank: u32,
+    /// Unix timestamp of when the player last connected to the server.
+    pub last_connect_time: u32,
+}
+
+impl From<&CTFLobbyPlayerProto> for LobbyPlayer {
+    fn from(player: &CTFLobbyPlayerProto) -> Self {
+        Self {
+            steamid: player.get_id(),
+            name: player.get_name().to_string(),
+            team: player.get_team(),
+            connect_state: player.get_connect_state(),
+            player_type: player.get_field_type(),
+            original_party_id: player.get_original_party_id(),
+            badge_level: player.get_badge_level(),
+            rank: player.get_rank(),
+            last_connect_time: player.get_last_connect_time(),
+        }
+    }
+}
+
+/// A match lobby.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lobby {
+    pub lobby_id: u64,
+    pub match_id: u64,
+    pub server_id: u64,
+    /// The address to connect to, e.g. `169.254.0.1:27015`. Empty until a server is assigned.
+    pub connect: String,
+    pub map_name: String,
+    /// The MvM mission, if this is an MvM match.
+    pub mission_name: Option<String>,
+    pub match_group: Option<ETFMatchGroup>,
+    pub state: CSOTFGameServerLobby_State,
+    pub game_state: TF_GC_GameState,
+    /// Unix timestamp of when the lobby was formed.
+    pub formed_time: u32,
+    pub late_join_eligible: bool,
+    pub members: Vec<LobbyPlayer>,
+    /// Players invited to the lobby who have not accepted yet.
+    pub pending_members: Vec<LobbyPlayer>,
+}
+
+impl Lobby {
+    
+    pub fn player(&self, steamid: u64) -> Option<&LobbyPlayer> {
+        self.members.iter().find(|player| player.steamid == steamid)
+    }
+    
+    pub fn team(&self, team: TF_GC_TEAM) -> impl Iterator<Item = &LobbyPlayer> {
+        self.members.iter().filter(move |player| player.team == team)
+    }
+    
+    /// Whether `steamid` has been invited to the lobby and not yet accepted.
+    pub fn is_pending(&self, steamid: u64) -> bool {
+        self.pending_members.iter().any(|player| player.steamid == steamid)
+    }
+}
+
+impl From<&CSOTFGameServerLobby> for Lobby {
+    fn from(lobby: &CSOTFGameServerLobby) -> Self {
+        Self {
+            lobby_id: lobby.get_lobby_id(),
+            match_id: lobby.get_match_id(),
+            server_id: lobby.get_server_id(),
+            connect: lobby.get_connect().to_string(),
+            map_name: lobby.get_map_name().to_string(),
+            mission_name: Some(lobby.get_mission_name())
+                .filter(|mission_name| !mission_name.is_empty())
+                .map(String::from),
+            match_group: ETFMatchGroup::from_i32(lobby.get_match_group() as i32),
+            state: lobby.get_state(),
+            game_state: lobby.get_game_state(),
+            formed_time: lobby.get_formed_time(),
+            late_join_eligible: lobby.get_late_join_eligible(),
+            members: lobby.get_members()
+                .iter()
+                .map(LobbyPlayer::from)
+                .collect(),
+            pending_members: lobby.get_pending_members()
+                .iter()
+                .map(LobbyPlayer::from)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn player(steamid: u64, team: TF_GC_TEAM) -> CTFLobbyPlayerProto {
+        let mut player = CTFLobbyPlayerProto::new();
+        
+        player.set_id(steamid);
+        player.set_team(team);
+        player.set_name(format!("player {}", steamid));
+        player
+    }
+    
+    #[test]
+    fn reads_lobby() {
+        let mut lobby = CSOTFGameServerLobby::new();
+        
+        lobby.set_lobby_id(1);
+        lobby.set_connect("169.254.0.1:27015".into());
+        lobby.set_map_name("cp_badlands".into());
+        lobby.set_match_group(ETFMatchGroup::k_eTFMatchGroup_Casual_Default as u32);
+        lobby.mut_members().push(player(10, TF_GC_TEAM::TF_GC_TEAM_DEFENDERS));
+        lobby.mut_members().push(player(11, TF_GC_TEAM::TF_GC_TEAM_INVADERS));
+        lobby.mut_members().push(player(12, TF_GC_TEAM::TF_GC_TEAM_DEFENDERS));
+        lobby.mut_pending_members().push(player(13, TF_GC_TEAM::TF_GC_TEAM_INVADERS));
+        
+        let lobby = Lobby::from(&lobby);
+        let defenders = lobby.team(TF_GC_TEAM::TF_GC_TEAM_DEFENDERS)
+            .map(|player| player.steamid)
+            .collect::<Vec<_>>();
+        
+        assert_eq!(lobby.match_group, Some(ETFMatchGroup::k_eTFMatchGroup_Casual_Default));
+        assert_eq!(lobby.mission_name, None);
+        assert_eq!(defenders, [10, 12]);
+        assert_eq!(lobby.player(11).map(|player| player.name.as_str()), Some("player 11"));
+        assert!(lobby.player(13).is_none());
+        assert!(lobby.is_pending(13));
+        assert!(!lobby.is_pending(10));
+    }
+    
+    #[test]
+    fn reads_mvm_mission() {
+        let mut lobby = CSOTFGameServerLobby::new();
+        
+        lobby.set_mission_name("mvm_coaltown_advanced".into());
+        lobby.set_match_group(ETFMatchGroup::k_eTFMatchGroup_MvM_MannUp as u32);
+        
+        let lobby = Lobby::from(&lobby);
+        
+        assert_eq!(lobby.mission_name.as_deref(), Some("mvm_coaltown_advanced"));
+        assert_eq!(lobby.match_group, Some(ETFMatchGroup::k_eTFMatchGroup_MvM_MannUp));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,109 @@
+//! Localization tokens from a `resource/tf_<language>.txt` file.
+
+use std::collections::HashMap;
+use std::path::Path;
+use crate::schema::SchemaError;
+use crate::vdf::KeyValues;
+
+/// Localized strings by token.
+#[derive(Debug, Default, Clone)]
+pub struct Localization {
+    tokens: HashMap<String, String>,
+}
+
+impl Localization {
+    
+    /// Reads a localization file. These are usually UTF-16, which is detected by the BOM.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, SchemaError> {
+        let bytes = std::fs::read(path)?;
+        let text = match bytes.as_slice() {
+            [0xFF, 0xFE, rest @ ..] => {
+                let units = rest
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>();
+                
+                String::from_utf16_lossy(&units)
+            },
+            _ => String::from_utf8_lossy(&bytes).into_owned(),
+        };
+        
+        Self::parse(&text)
+    }
+    
+    pub fn parse(text: &str) -> Result<Self, SchemaError> {
+        let root = KeyValues::parse(text)?;
+        let tokens = root.get_object("lang")
+            .and_then(|lang| lang.get_object("Tokens"))
+            .ok_or(SchemaError::MissingSection("Tokens"))?;
+        let mut localization = Self::default();
+        
+        for (token, value) in tokens.iter() {
+            // "[english]" entries hold the English source of a translated token
+            if token.starts_with('[') {
+                continue;
+            }
+            
+            if let Some(value) = value.as_str() {
+                localization.insert(token, value);
+            }
+        }
+        
+        Ok(localization)
+    }
+    
+    pub fn insert(&mut self, token: &str, value: &str) {
+        self.tokens.insert(token.to_lowercase(), value.to_string());
+    }
+    
+    /// Looks up a token, ignoring case and a leading `#`.
+    pub fn get(&self, token: &str) -> Option<&str> {
+        let token = token.strip_prefix('#').unwrap_or(token);
+        
+        self.tokens.get(&token.to_lowercase()).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const TOKENS: &str = r#"
+        "lang"
+        {
+            "Language" "german"
+            "Tokens"
+            {
+                "TF_TeamCaptain" "Teamkapitän"
+                "[english]TF_TeamCaptain" "Team Captain"
+            }
+        }
+    "#;
+    
+    #[test]
+    fn parses_tokens() {
+        let localization = Localization::parse(TOKENS).unwrap();
+        
+        assert_eq!(localization.get("#TF_TeamCaptain"), Some("Teamkapitän"));
+        assert_eq!(localization.get("tf_teamcaptain"), Some("Teamkapitän"));
+        assert_eq!(localization.get("[english]TF_TeamCaptain"), None);
+        assert!(matches!(
+            Localization::parse(r#""lang" {}"#),
+            Err(SchemaError::MissingSection("Tokens")),
+        ));
+    }
+    
+    #[test]
+    fn reads_utf16_files() {
+        let path = std::env::temp_dir().join("tf2_localization_test.txt");
+        let mut bytes = vec![0xFF, 0xFE];
+        
+        bytes.extend(TOKENS.encode_utf16().flat_map(u16::to_le_bytes));
+        std::fs::write(&path, bytes).unwrap();
+        
+        let localization = Localization::from_file(&path);
+        
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(localization.unwrap().get("TF_TeamCaptain"), Some("Teamkapitän"));
+    }
+}
\ No newline at end of file
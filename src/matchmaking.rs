@@ -0,0 +1,198 @@
+//! Matchmaking queue progress, population and datacenter pings.
+
+use std::collections::HashMap;
+use protobuf::ProtobufEnum;
+use tf2_protobuf::tf_gcmessages::{
+    CMsgMatchmakingProgress,
+    CMsgGCMatchMakerStatsResponse,
+    CMsgGCDataCenterPing_Update_PingEntry,
+    CMsgGCDataCenterPing_Update_Status,
+    ETFMatchGroup,
+};
+
+/// Player and server counts for the queues the party is in. Counts are split between those
+/// matching the party's criteria and all, worldwide and near the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchmakingProgress {
+    /// How urgently players are needed, in percent.
+    pub urgency_pct: u32,
+    /// Average wait in seconds for a new match.
+    pub avg_wait_time_new: u32,
+    /// Average wait in seconds for a match in progress.
+    pub avg_wait_time_join_late: u32,
+    /// Seconds the party has been waiting.
+    pub your_wait_time: u32,
+    pub matching_worldwide_searching_players: u32,
+    pub matching_near_you_searching_players: u32,
+    pub total_worldwide_searching_players: u32,
+    pub total_near_you_searching_players: u32,
+    pub matching_worldwide_active_players: u32,
+    pub matching_near_you_active_players: u32,
+    pub total_worldwide_active_players: u32,
+    pub total_near_you_active_players: u32,
+    pub matching_worldwide_empty_gameservers: u32,
+    pub matching_near_you_empty_gameservers: u32,
+    pub total_worldwide_empty_gameservers: u32,
+    pub total_near_you_empty_gameservers: u32,
+}
+
+impl From<&CMsgMatchmakingProgress> for MatchmakingProgress {
+    fn from(message: &CMsgMatchmakingProgress) -> Self {
+        Self {
+            urgency_pct: message.get_urgency_pct(),
+            avg_wait_time_new: message.get_avg_wait_time_new(),
+            avg_wait_time_join_late: message.get_avg_wait_time_join_late(),
+            your_wait_time: message.get_your_wait_time(),
+            matching_worldwide_searching_players: message.get_matching_worldwide_searching_players(),
+            matching_near_you_searching_players: message.get_matching_near_you_searching_players(),
+            total_worldwide_searching_players: message.get_total_worldwide_searching_players(),
+            total_near_you_searching_players: message.get_total_near_you_searching_players(),
+            matching_worldwide_active_players: message.get_matching_worldwide_active_players(),
+            matching_near_you_active_players: message.get_matching_near_you_active_players(),
+            total_worldwide_active_players: message.get_total_worldwide_active_players(),
+            total_near_you_active_players: message.get_total_near_you_active_players(),
+            matching_worldwide_empty_gameservers: message.get_matching_worldwide_empty_gameservers(),
+            matching_near_you_empty_gameservers: message.get_matching_near_you_empty_gameservers(),
+            total_worldwide_empty_gameservers: message.get_total_worldwide_empty_gameservers(),
+            total_near_you_empty_gameservers: message.get_total_near_you_empty_gameservers(),
+        }
+    }
+}
+
+/// How busy a datacenter is for a match group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataCenterPopulation {
+    /// The datacenter code, e.g. `fra`.
+    pub name: String,
+    /// Relative population, where `1.0` is healthy.
+    pub health_ratio: f32,
+}
+
+/// Matchmaking population reported by the GC.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MatchmakerStats {
+    /// Datacenter populations for each match group.
+    pub data_centers: HashMap<ETFMatchGroup, Vec<DataCenterPopulation>>,
+    /// Players on each casual map, indexed by the map's position in the casual map list.
+    pub map_counts: Vec<u32>,
+}
+
+impl MatchmakerStats {
+    
+    pub fn data_centers(&self, match_group: ETFMatchGroup) -> &[DataCenterPopulation] {
+        self.data_centers
+            .get(&match_group)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+impl From<&CMsgGCMatchMakerStatsResponse> for MatchmakerStats {
+    fn from(message: &CMsgGCMatchMakerStatsResponse) -> Self {
+        Self {
+            // Populations are indexed by match group.
+            data_centers: message.get_matchgroup_data_center_population()
+                .iter()
+                .enumerate()
+                .filter_map(|(index, population)| {
+                    let match_group = ETFMatchGroup::from_i32(index as i32)?;
+                    let data_centers = population.get_data_center_population()
+                        .iter()
+                        .map(|data_center| DataCenterPopulation {
+                            name: data_center.get_name().to_string(),
+                            health_ratio: data_center.get_health_ratio(),
+                        })
+                        .collect();
+                    
+                    Some((match_group, data_centers))
+                })
+                .collect(),
+            map_counts: message.get_map_count().to_vec(),
+        }
+    }
+}
+
+/// The client's ping to a datacenter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataCenterPing {
+    /// The datacenter code, e.g. `fra`.
+    pub name: String,
+    /// Ping in milliseconds.
+    pub ping: u32,
+    pub status: CMsgGCDataCenterPing_Update_Status,
+}
+
+impl From<&DataCenterPing> for CMsgGCDataCenterPing_Update_PingEntry {
+    fn from(entry: &DataCenterPing) -> Self {
+        let mut message = CMsgGCDataCenterPing_Update_PingEntry::new();
+        
+        message.set_name(entry.name.clone());
+        message.set_ping(entry.ping);
+        message.set_ping_status(entry.status);
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tf2_protobuf::tf_gcmessages::{
+        CMsgGCDataCenterPopulation,
+        CMsgGCMatchGroupDataCenterPopulation,
+    };
+    
+    fn population(data_centers: &[(&str, f32)]) -> CMsgGCMatchGroupDataCenterPopulation {
+        let mut population = CMsgGCMatchGroupDataCenterPopulation::new();
+        
+        for (name, health_ratio) in data_centers {
+            let mut data_center = CMsgGCDataCenterPopulation::new();
+            
+            data_center.set_name(name.to_string());
+            data_center.set_health_ratio(*health_ratio);
+            population.mut_data_center_population().push(data_center);
+        }
+        
+        population
+    }
+    
+    #[test]
+    fn reads_populations_by_match_group() {
+        let mut message = CMsgGCMatchMakerStatsResponse::new();
+        
+        message.set_map_count(vec![10, 0, 4]);
+        message.mut_matchgroup_data_center_population().push(population(&[("fra", 1.0)]));
+        
+        for _ in 1..ETFMatchGroup::k_eTFMatchGroup_Casual_Default as usize {
+            message.mut_matchgroup_data_center_population().push(population(&[]));
+        }
+        
+        message.mut_matchgroup_data_center_population().push(population(&[
+            ("fra", 0.5),
+            ("iad", 1.5),
+        ]));
+        
+        let stats = MatchmakerStats::from(&message);
+        let casual = stats.data_centers(ETFMatchGroup::k_eTFMatchGroup_Casual_Default);
+        
+        assert_eq!(stats.map_counts, [10, 0, 4]);
+        assert_eq!(stats.data_centers(ETFMatchGroup::k_eTFMatchGroup_MvM_Default)[0].name, "fra");
+        assert!(stats.data_centers(ETFMatchGroup::k_eTFMatchGroup_Ladder_Default).is_empty());
+        assert_eq!(casual.len(), 2);
+        assert_eq!(casual[1].name, "iad");
+        assert_eq!(casual[1].health_ratio, 1.5);
+        assert!(stats.data_centers(ETFMatchGroup::k_eTFMatchGroup_Event_Default).is_empty());
+    }
+    
+    #[test]
+    fn writes_ping_entries() {
+        let entry = CMsgGCDataCenterPing_Update_PingEntry::from(&DataCenterPing {
+            name: "fra".into(),
+            ping: 25,
+            status: CMsgGCDataCenterPing_Update_Status::Unreachable,
+        });
+        
+        assert_eq!(entry.get_name(), "fra");
+        assert_eq!(entry.get_ping(), 25);
+        assert_eq!(entry.get_ping_status(), CMsgGCDataCenterPing_Update_Status::Unreachable);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,404 @@
+use std::collections::VecDeque;
+use crate::recipe::{Recipe, RecipeInput, SCRAP_METAL, RECLAIMED_METAL, REFINED_METAL};
+use crate::request::CraftItem;
+use crate::response::CraftResponse;
+use crate::so_cache::SOCache;
+use crate::item::FLAG_CANNOT_BE_USED_IN_CRAFTING;
+use crate::error::Error;
+
+/// Amounts of each metal type.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MetalCounts {
+    pub scrap: u32,
+    pub reclaimed: u32,
+    pub refined: u32,
+}
+
+impl MetalCounts {
+    
+    pub fn new(refined: u32, reclaimed: u32, scrap: u32) -> Self {
+        Self {
+            scrap,
+            reclaimed,
+            refined,
+        }
+    }
+    
+    /// The total value in scrap metal.
+    pub fn value(&self) -> u32 {
+        self.scrap + self.reclaimed * 3 + self.refined * 9
+    }
+}
+
+/// Works out the fewest smelt and combine crafts needed to hold at least `target` of each metal
+/// type. Returns `None` if `have` is not worth enough.
+pub fn plan(have: MetalCounts, target: MetalCounts) -> Option<Vec<Recipe>> {
+    if have.value() < target.value() {
+        return None;
+    }
+    
+    // Scrap needed is only ever made by smelting reclaimed, and surplus scrap can be combined.
+    let (smelt_reclaimed, scrap_surplus) = if have.scrap < target.scrap {
+        ((target.scrap - have.scrap).div_ceil(3), 0)
+    } else {
+        (0, (have.scrap - target.scrap) / 3)
+    };
+    let (smelt_refined, combine_scrap, combine_reclaimed) = if have.refined < target.refined {
+        let combine_reclaimed = target.refined - have.refined;
+        let reclaimed_needed = target.reclaimed + combine_reclaimed * 3 + smelt_reclaimed;
+        let combine_scrap = reclaimed_needed.saturating_sub(have.reclaimed);
+        
+        if combine_scrap > scrap_surplus {
+            return None;
+        }
+        
+        (0, combine_scrap, combine_reclaimed)
+    } else {
+        let refined_surplus = have.refined - target.refined;
+        let reclaimed_needed = (target.reclaimed + smelt_reclaimed)
+            .saturating_sub(have.reclaimed);
+        // Each smelted refined gives three reclaimed, each combine of scrap only one.
+        let (smelt_refined, combine_scrap) = (0..=refined_surplus.min(reclaimed_needed.div_ceil(3)))
+            .map(|smelt_refined| {
+                (smelt_refined, reclaimed_needed.saturating_sub(smelt_refined * 3))
+            })
+            .filter(|(_, combine_scrap)| *combine_scrap <= scrap_surplus)
+            .min_by_key(|(smelt_refined, combine_scrap)| smelt_refined + combine_scrap)?;
+        
+        (smelt_refined, combine_scrap, 0)
+    };
+    let mut crafts = Vec::new();
+    
+    // Ordered so that every craft's inputs exist by the time it runs.
+    crafts.extend((0..smelt_refined).map(|_| Recipe::SmeltRefined));
+    crafts.extend((0..combine_scrap).map(|_| Recipe::CombineScrap));
+    crafts.extend((0..smelt_reclaimed).map(|_| Recipe::SmeltReclaimed));
+    crafts.extend((0..combine_reclaimed).map(|_| Recipe::CombineReclaimed));
+    
+    Some(crafts)
+}
+
+/// Item ids of each metal type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MetalInventory {
+    pub scrap: Vec<u64>,
+    pub reclaimed: Vec<u64>,
+    pub refined: Vec<u64>,
+}
+
+impl MetalInventory {
+    
+    /// Collects the metal from `items`, ignoring anything else.
+    pub fn from_items<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = CraftItem>,
+    {
+        let mut inventory = Self::default();
+        
+        for item in items {
+            if let Some(ids) = inventory.ids_mut(item.def_index) {
+                ids.push(item.item_id);
+            }
+        }
+        
+        inventory
+    }
+    
+    /// Collects the metal from the cached items. Metal which cannot be used in crafting is
+    /// left out.
+    pub fn from_so_cache(so_cache: &SOCache) -> Self {
+        Self::from_items(so_cache
+            .items()
+            .filter(|item| item.get_flags() & FLAG_CANNOT_BE_USED_IN_CRAFTING == 0)
+            .map(CraftItem::from))
+    }
+    
+    pub fn counts(&self) -> MetalCounts {
+        MetalCounts {
+            scrap: self.scrap.len() as u32,
+            reclaimed: self.reclaimed.len() as u32,
+            refined: self.refined.len() as u32,
+        }
+    }
+    
+    fn ids(&self, def_index: u32) -> &[u64] {
+        match def_index {
+            SCRAP_METAL => &self.scrap,
+            RECLAIMED_METAL => &self.reclaimed,
+            REFINED_METAL => &self.refined,
+            _ => &[],
+        }
+    }
+    
+    fn ids_mut(&mut self, def_index: u32) -> Option<&mut Vec<u64>> {
+        match def_index {
+            SCRAP_METAL => Some(&mut self.scrap),
+            RECLAIMED_METAL => Some(&mut self.reclaimed),
+            REFINED_METAL => Some(&mut self.refined),
+            _ => None,
+        }
+    }
+}
+
+/// What a [`MetalCrafter`] should do next.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetalCraftStep {
+    /// Craft `recipe` from `item_ids`.
+    Craft {
+        recipe: Recipe,
+        item_ids: Vec<u64>,
+    },
+    /// The previous craft's response has not been given to [`MetalCrafter::handle_response`].
+    Waiting,
+    Done,
+}
+
+/// The result of [`crate::TeamFortress2::craft_metal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetalCraftStatus {
+    /// The next craft was sent with this job id.
+    Sent(u64),
+    /// The previous craft's response has not been given to [`MetalCrafter::handle_response`].
+    Waiting,
+    Done,
+}
+
+/// Runs a metal plan one craft at a time, tracking the item ids created by each craft.
+#[derive(Debug)]
+pub struct MetalCrafter {
+    inventory: MetalInventory,
+    crafts: VecDeque<Recipe>,
+    pending: Option<Recipe>,
+}
+
+impl MetalCrafter {
+    
+    /// Plans the crafts needed to reach `target` from `inventory`.
+    pub fn new(
+        inventory: MetalInventory,
+        target: MetalCounts,
+    ) -> Result<Self, Error> {
+        let have = inventory.counts();
+        let crafts = plan(have, target)
+            .ok_or(Error::InsufficientMetal {
+                have: have.value(),
+                target: target.value(),
+            })?;
+        
+        Ok(Self {
+            inventory,
+            crafts: crafts.into(),
+            pending: None,
+        })
+    }
+    
+    /// The metal currently held, including metal created by completed crafts.
+    pub fn inventory(&self) -> &MetalInventory {
+        &self.inventory
+    }
+    
+    /// The crafts which have not been started yet.
+    pub fn remaining(&self) -> impl Iterator<Item = &Recipe> {
+        self.crafts.iter()
+    }
+    
+    pub fn is_done(&self) -> bool {
+        self.pending.is_none() && self.crafts.is_empty()
+    }
+    
+    /// The next craft and the item ids it consumes. Nothing changes until the craft is marked
+    /// as sent with [`MetalCrafter::start_craft`].
+    pub fn next_craft(&self) -> MetalCraftStep {
+        if self.pending.is_some() {
+            return MetalCraftStep::Waiting;
+        }
+        
+        let recipe = match self.crafts.front() {
+            Some(recipe) => *recipe,
+            None => return MetalCraftStep::Done,
+        };
+        let (def_index, count) = metal_input(recipe);
+        let ids = self.inventory.ids(def_index);
+        
+        MetalCraftStep::Craft {
+            recipe,
+            item_ids: ids[ids.len().saturating_sub(count)..].to_vec(),
+        }
+    }
+    
+    /// Marks the craft returned by [`MetalCrafter::next_craft`] as sent, removing the items it
+    /// consumes. Call this only once the craft was sent.
+    pub fn start_craft(&mut self) {
+        if self.pending.is_some() {
+            return;
+        }
+        
+        let recipe = match self.crafts.pop_front() {
+            Some(recipe) => recipe,
+            None => return,
+        };
+        let (def_index, count) = metal_input(recipe);
+        
+        if let Some(ids) = self.inventory.ids_mut(def_index) {
+            ids.truncate(ids.len().saturating_sub(count));
+        }
+        
+        self.pending = Some(recipe);
+    }
+    
+    /// Checks the response to the pending craft and adds the created items to the inventory.
+    pub fn handle_response(&mut self, response: &CraftResponse) -> Result<(), Error> {
+        let recipe = self.pending.take()
+            .ok_or(Error::UnexpectedCraftResponse(response.blueprint))?;
+        let (def_index, count) = match recipe {
+            Recipe::SmeltRefined => (RECLAIMED_METAL, 3),
+            Recipe::SmeltReclaimed => (SCRAP_METAL, 3),
+            Recipe::CombineScrap => (RECLAIMED_METAL, 1),
+            _ => (REFINED_METAL, 1),
+        };
+        
+        if response.blueprint != recipe.id() || response.assetids.len() != count {
+            return Err(Error::UnexpectedCraftResponse(response.blueprint));
+        }
+        
+        if let Some(ids) = self.inventory.ids_mut(def_index) {
+            ids.extend(response.assetids.iter().copied());
+        }
+        
+        Ok(())
+    }
+}
+
+/// The metal type and count consumed by a craft in a metal plan.
+fn metal_input(recipe: Recipe) -> (u32, usize) {
+    match recipe.inputs() {
+        [RecipeInput::DefIndex { def_index, count }] => (*def_index, *count),
+        // plans only contain smelts and combines
+        _ => unreachable!("{:?} is not a metal craft", recipe),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protobuf::Message;
+    use tf2_protobuf::base_gcmessages::CSOEconItem;
+    use tf2_protobuf::gcsdk_gcmessages::CMsgSOSingleObject;
+    use tf2_protobuf::gcsystemmsgs::ESOMsg;
+    use crate::so_cache::SO_TYPE_ITEM;
+    
+    fn inventory(refined: u64, reclaimed: u64, scrap: u64) -> MetalInventory {
+        MetalInventory {
+            refined: (0..refined).map(|i| 3000 + i).collect(),
+            reclaimed: (0..reclaimed).map(|i| 2000 + i).collect(),
+            scrap: (0..scrap).map(|i| 1000 + i).collect(),
+        }
+    }
+    
+    #[test]
+    fn plans_smelts_for_change() {
+        let crafts = plan(MetalCounts::new(1, 0, 0), MetalCounts::new(0, 2, 3)).unwrap();
+        
+        assert_eq!(crafts, vec![Recipe::SmeltRefined, Recipe::SmeltReclaimed]);
+    }
+    
+    #[test]
+    fn plans_combines_from_surplus() {
+        let crafts = plan(MetalCounts::new(0, 2, 3), MetalCounts::new(1, 0, 0)).unwrap();
+        
+        assert_eq!(crafts, vec![Recipe::CombineScrap, Recipe::CombineReclaimed]);
+    }
+    
+    #[test]
+    fn plans_nothing_when_target_is_held() {
+        assert_eq!(plan(MetalCounts::new(1, 1, 1), MetalCounts::new(1, 1, 1)), Some(Vec::new()));
+    }
+    
+    #[test]
+    fn rejects_insufficient_metal() {
+        assert_eq!(plan(MetalCounts::new(0, 1, 2), MetalCounts::new(1, 0, 0)), None);
+        assert!(matches!(
+            MetalCrafter::new(inventory(0, 1, 2), MetalCounts::new(1, 0, 0)),
+            Err(Error::InsufficientMetal { have: 5, target: 9 }),
+        ));
+    }
+    
+    #[test]
+    fn crafter_keeps_state_until_craft_is_started() {
+        let mut crafter = MetalCrafter::new(inventory(1, 0, 0), MetalCounts::new(0, 3, 0)).unwrap();
+        let step = MetalCraftStep::Craft {
+            recipe: Recipe::SmeltRefined,
+            item_ids: vec![3000],
+        };
+        
+        // a failed send does not start the craft
+        assert_eq!(crafter.next_craft(), step);
+        assert_eq!(crafter.next_craft(), step);
+        assert_eq!(crafter.inventory().refined, vec![3000]);
+        
+        crafter.start_craft();
+        
+        assert_eq!(crafter.next_craft(), MetalCraftStep::Waiting);
+        assert!(crafter.inventory().refined.is_empty());
+        assert!(!crafter.is_done());
+        
+        crafter.handle_response(&CraftResponse {
+            blueprint: Recipe::SmeltRefined.id(),
+            assetids: vec![10, 11, 12],
+        }).unwrap();
+        
+        assert_eq!(crafter.next_craft(), MetalCraftStep::Done);
+        assert!(crafter.is_done());
+        assert_eq!(crafter.inventory().reclaimed, vec![10, 11, 12]);
+    }
+    
+    #[test]
+    fn crafter_rejects_unexpected_responses() {
+        let mut crafter = MetalCrafter::new(inventory(1, 0, 0), MetalCounts::new(0, 3, 0)).unwrap();
+        let response = CraftResponse {
+            blueprint: Recipe::SmeltRefined.id(),
+            assetids: vec![10, 11, 12],
+        };
+        
+        assert!(crafter.handle_response(&response).is_err());
+        
+        crafter.start_craft();
+        
+        assert!(crafter.handle_response(&CraftResponse {
+            blueprint: Recipe::SmeltReclaimed.id(),
+            assetids: vec![10, 11, 12],
+        }).is_err());
+    }
+    
+    #[test]
+    fn collects_craftable_metal_from_so_cache() {
+        let mut so_cache = SOCache::new();
+        let items = [
+            (1, SCRAP_METAL, 0),
+            (2, REFINED_METAL, 0),
+            (3, REFINED_METAL, FLAG_CANNOT_BE_USED_IN_CRAFTING),
+            (4, 5021, 0),
+        ];
+        
+        for (id, def_index, flags) in items {
+            let mut item = CSOEconItem::new();
+            let mut object = CMsgSOSingleObject::new();
+            
+            item.set_id(id);
+            item.set_def_index(def_index);
+            item.set_flags(flags);
+            object.set_type_id(SO_TYPE_ITEM);
+            object.set_object_data(item.write_to_bytes().unwrap());
+            so_cache.handle_message(
+                ESOMsg::k_ESOMsg_Create as i32,
+                &object.write_to_bytes().unwrap(),
+            ).unwrap();
+        }
+        
+        let inventory = MetalInventory::from_so_cache(&so_cache);
+        
+        assert_eq!(inventory.scrap, [1]);
+        assert_eq!(inventory.reclaimed, Vec::<u64>::new());
+        assert_eq!(inventory.refined, [2]);
+    }
+}
\ No newline at end of file
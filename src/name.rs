@@ -0,0 +1,216 @@
+//! Display names for items, as shown on markets.
+
+use crate::item::{Item, Quality};
+use crate::localization::Localization;
+use crate::schema::Schema;
+
+impl Item {
+    
+    /// The full name of the item, e.g. "Strange Professional Killstreak Australium Rocket
+    /// Launcher". Returns `None` if the item is not in `schema`.
+    pub fn market_name(&self, schema: &Schema, localization: &Localization) -> Option<String> {
+        let definition = schema.item(self.def_index)?;
+        let localize = |token: &Option<String>| {
+            token.as_deref().and_then(|token| localization.get(token))
+        };
+        let mut base_name = localize(&definition.item_name).unwrap_or(&definition.name);
+        let mut parts = Vec::new();
+        
+        if !self.craftable {
+            parts.push("Non-Craftable");
+        }
+        
+        if self.quality != Quality::Strange && self.strange_counter.is_some() {
+            parts.push("Strange");
+        }
+        
+        if !matches!(self.quality, Quality::Unique | Quality::DecoratedWeapon) {
+            parts.extend(self.quality.name());
+        }
+        
+        if let Some(effect) = self.particle_effect {
+            let particle_name = schema.particle(effect)
+                .and_then(|particle| particle.name.as_deref())
+                .or_else(|| localization.get(&format!("Attrib_Particle{}", effect)));
+            
+            parts.extend(particle_name);
+        }
+        
+        if self.festivized {
+            parts.push("Festivized");
+        }
+        
+        if let Some(tier) = self.killstreak_tier {
+            // killstreak kits are named e.g. "Professional Killstreak Rocket Launcher Kit"
+            if let Some(rest) = base_name.strip_prefix(tier.name()) {
+                base_name = rest.trim_start();
+            }
+            
+            parts.push(tier.name());
+        }
+        
+        if self.australium {
+            parts.push("Australium");
+        }
+        
+        if let Some(paint_kit) = self.paint_kit.and_then(|id| schema.paint_kit(id)) {
+            parts.extend(localize(&paint_kit.item_name));
+        }
+        
+        // strangifiers, unusualifiers and killstreak kits name the item they apply to
+        if let Some(target) = self.target_def_index.and_then(|def_index| schema.item(def_index)) {
+            parts.push(localize(&target.item_name).unwrap_or(&target.name));
+        }
+        
+        let mut name = if parts.is_empty() && self.quality == Quality::Unique && definition.propername {
+            format!("The {}", base_name)
+        } else {
+            parts.push(base_name);
+            parts.join(" ")
+        };
+        
+        if let Some(wear) = self.wear {
+            name.push_str(&format!(" ({})", wear.name()));
+        }
+        
+        let crate_series = self.crate_series.or_else(|| {
+            definition.attributes
+                .get("set supply crate series")
+                .and_then(|value| value.parse::<f64>().ok())
+                .map(|series| series as u32)
+        });
+        
+        if let Some(series) = crate_series {
+            name.push_str(&format!(" Series #{}", series));
+        }
+        
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tf2_protobuf::base_gcmessages::{CSOEconItem, CSOEconItemAttribute};
+    use crate::item::{
+        ATTRIBUTE_AUSTRALIUM, ATTRIBUTE_CRATE_SERIES, ATTRIBUTE_KILLSTREAK_TIER,
+        ATTRIBUTE_PARTICLE_EFFECT, FLAG_CANNOT_BE_USED_IN_CRAFTING,
+    };
+    
+    const ITEMS_GAME: &str = r##"
+        "items_game"
+        {
+            "items"
+            {
+                "205"
+                {
+                    "name" "TF_WEAPON_ROCKETLAUNCHER"
+                    "item_name" "#TF_Weapon_RocketLauncher"
+                    "propername" "1"
+                }
+                "378"
+                {
+                    "name" "Team Captain"
+                    "item_name" "#TF_TeamCaptain"
+                    "propername" "1"
+                }
+                "725"
+                {
+                    "name" "Tour of Duty Ticket"
+                    "item_name" "#TF_Tool_MvM_Ticket"
+                    "propername" "0"
+                }
+                "5022"
+                {
+                    "name" "Supply Crate 1"
+                    "item_name" "#CrateName"
+                    "propername" "0"
+                }
+            }
+            "attribute_controlled_attached_particles"
+            {
+                "cosmetic_unusual_effects"
+                {
+                    "13"
+                    {
+                        "system" "burningplayer_flyingbits"
+                        "name" "Burning Flames"
+                    }
+                }
+            }
+        }
+    "##;
+    const TOKENS: &str = r#"
+        "lang"
+        {
+            "Tokens"
+            {
+                "TF_Weapon_RocketLauncher"  "Rocket Launcher"
+                "TF_TeamCaptain"            "Team Captain"
+                "TF_Tool_MvM_Ticket"        "Tour of Duty Ticket"
+                "CrateName"                 "Mann Co. Supply Crate"
+            }
+        }
+    "#;
+    
+    fn market_name(def_index: u32, quality: u32, flags: u32, attributes: &[(u32, f32)]) -> String {
+        let schema = Schema::parse(ITEMS_GAME).unwrap();
+        let localization = Localization::parse(TOKENS).unwrap();
+        let mut item = CSOEconItem::new();
+        
+        item.set_def_index(def_index);
+        item.set_quality(quality);
+        item.set_flags(flags);
+        
+        for (def_index, value) in attributes {
+            let mut attribute = CSOEconItemAttribute::new();
+            
+            attribute.set_def_index(*def_index);
+            attribute.set_value(value.to_bits());
+            item.mut_attribute().push(attribute);
+        }
+        
+        Item::from(&item).market_name(&schema, &localization).unwrap()
+    }
+    
+    #[test]
+    fn names_strange_killstreak_australium() {
+        assert_eq!(
+            market_name(205, 11, 0, &[
+                (ATTRIBUTE_KILLSTREAK_TIER, 3.0),
+                (ATTRIBUTE_AUSTRALIUM, 1.0),
+            ]),
+            "Strange Professional Killstreak Australium Rocket Launcher",
+        );
+    }
+    
+    #[test]
+    fn names_unusual_effect() {
+        assert_eq!(
+            market_name(378, 5, 0, &[(ATTRIBUTE_PARTICLE_EFFECT, 13.0)]),
+            "Unusual Burning Flames Team Captain",
+        );
+    }
+    
+    #[test]
+    fn names_non_craftable() {
+        assert_eq!(
+            market_name(725, 6, FLAG_CANNOT_BE_USED_IN_CRAFTING, &[]),
+            "Non-Craftable Tour of Duty Ticket",
+        );
+    }
+    
+    #[test]
+    fn names_crate_series() {
+        assert_eq!(
+            market_name(5022, 6, 0, &[(ATTRIBUTE_CRATE_SERIES, 85.0)]),
+            "Mann Co. Supply Crate Series #85",
+        );
+    }
+    
+    #[test]
+    fn adds_article_to_unique_proper_names() {
+        assert_eq!(market_name(378, 6, 0, &[]), "The Team Captain");
+        assert_eq!(market_name(378, 3, 0, &[]), "Vintage Team Captain");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+//! Notifications queued by the GC, such as support messages and ban notices.
+
+use tf2_protobuf::tf_gcmessages::{CMsgGCNotification, CMsgGCNotification_NotificationType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotificationType {
+    ReportedPlayerBanned,
+    CustomString,
+    /// You were banned from matchmaking due to excessive reports.
+    MatchmakingBanDueToExcessiveReports,
+    ReportedPlayerWasBanned,
+    SupportMessage,
+}
+
+impl From<CMsgGCNotification_NotificationType> for NotificationType {
+    fn from(notification_type: CMsgGCNotification_NotificationType) -> Self {
+        use CMsgGCNotification_NotificationType::*;
+        
+        match notification_type {
+            NOTIFICATION_REPORTED_PLAYER_BANNED => Self::ReportedPlayerBanned,
+            NOTIFICATION_MM_BAN_DUE_TO_EXCESSIVE_REPORTS => Self::MatchmakingBanDueToExcessiveReports,
+            NOTIFICATION_REPORTED_PLAYER_WAS_BANNED => Self::ReportedPlayerWasBanned,
+            NOTIFICATION_SUPPORT_MESSAGE => Self::SupportMessage,
+            NOTIFICATION_CUSTOM_STRING |
+            NOTIFICATION_NUM_TYPES => Self::CustomString,
+        }
+    }
+}
+
+/// A pending notification. It is shown until acknowledged with
+/// [`crate::TeamFortress2::acknowledge_notification`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub id: u64,
+    pub account_id: u32,
+    /// Unix timestamp after which the notification is dropped.
+    pub expiration_time: u32,
+    pub notification_type: NotificationType,
+    pub message: String,
+}
+
+impl From<&CMsgGCNotification> for Notification {
+    fn from(notification: &CMsgGCNotification) -> Self {
+        Self {
+            id: notification.get_notification_id(),
+            account_id: notification.get_account_id(),
+            expiration_time: notification.get_expiration_time(),
+            notification_type: notification.get_field_type().into(),
+            message: notification.get_notification_string().to_string(),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,517 @@
+//! Paint kits (war paints) resolved for an item and wear into the texture operations the
+//! compositor runs to paint the item.
+
+use protobuf::{Message, SingularPtrField};
+use tf2_protobuf::tf_proto_def_messages::{
+    CMsgPaintKit_Definition,
+    CMsgPaintKit_Definition_Item,
+    CMsgPaintKit_OperationNode,
+    CMsgPaintKit_OperationNode_oneof_node,
+    CMsgPaintKit_OperationStage,
+    CMsgPaintKit_OperationStage_oneof_stage,
+    CMsgPaintKit_Operation_CombineStage,
+    CMsgProtoDefID,
+    CMsgVarField,
+    EVarFieldType,
+    ProtoDefTypes,
+};
+use crate::item::Wear;
+use crate::protodef::{resolve_var_field, ProtoDefError, ProtoDefId, ProtoDefs, Variables};
+
+const ITEM_TYPE_NAME: &str = ".CMsgPaintKit_Definition.Item";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CombineMode {
+    Add,
+    Lerp,
+    Multiply,
+}
+
+/// Levels adjustment of a stage's output.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Adjustment {
+    pub black: Option<String>,
+    pub offset: Option<String>,
+    pub gamma: Option<String>,
+}
+
+/// Transform of a stage's texture coordinates.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UvTransform {
+    pub rotation: Option<String>,
+    pub translate_u: Option<String>,
+    pub translate_v: Option<String>,
+    pub scale_uv: Option<String>,
+    pub flip_u: Option<String>,
+    pub flip_v: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextureLookup {
+    pub texture: Option<String>,
+    /// Used in place of `texture` on RED if the paint kit has team textures.
+    pub texture_red: Option<String>,
+    /// Used in place of `texture` on BLU if the paint kit has team textures.
+    pub texture_blue: Option<String>,
+    pub adjustment: Adjustment,
+    pub transform: UvTransform,
+}
+
+/// A sticker which may be chosen by an [`PaintKitOperation::ApplySticker`] operation.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Sticker {
+    pub base: Option<String>,
+    /// How likely this sticker is to be chosen.
+    pub weight: Option<String>,
+    pub spec: Option<String>,
+}
+
+/// An operation of the compositor. Values are kept as the strings read by the game, some of
+/// which are ranges such as `"0.5 1"` which the game picks from using the item's seed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaintKitOperation {
+    TextureLookup(Box<TextureLookup>),
+    /// Combines the results of its inputs.
+    Combine {
+        mode: CombineMode,
+        inputs: usize,
+        adjustment: Adjustment,
+        transform: UvTransform,
+    },
+    /// Selects regions of the item by their group.
+    Select {
+        groups: Option<String>,
+        select: Vec<String>,
+    },
+    /// Places one of the stickers over the result of its inputs.
+    ApplySticker {
+        inputs: usize,
+        stickers: Vec<Sticker>,
+        dest_tl: Option<String>,
+        dest_tr: Option<String>,
+        dest_bl: Option<String>,
+        adjustment: Adjustment,
+    },
+}
+
+/// A paint kit resolved for an item and wear.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaintKit {
+    pub def_index: u32,
+    pub name: String,
+    pub loc_desctoken: String,
+    pub item_def_index: u32,
+    pub wear: Wear,
+    pub has_team_textures: bool,
+    pub material_override: Option<String>,
+    /// The variables the operations were resolved with.
+    pub variables: Variables,
+    /// The operations in the order they are evaluated. Each operation produces one result, and
+    /// operations with `inputs` take the results of that many operations before them which are
+    /// not already an input of another operation.
+    pub operations: Vec<PaintKitOperation>,
+}
+
+impl PaintKit {
+    
+    /// Resolves the paint kit `def_index` for `item_def_index` at `wear`. Returns `None` if the
+    /// paint kit cannot be applied to the item.
+    ///
+    /// Variables are taken from, in increasing precedence, the item definition's variable
+    /// template, the item definition, its definition for the wear, the paint kit and the paint
+    /// kit's data for the item. Operation templates use their own variables as defaults.
+    pub fn resolve(
+        defs: &ProtoDefs,
+        def_index: u32,
+        item_def_index: u32,
+        wear: Wear,
+    ) -> Result<Option<Self>, ProtoDefError> {
+        let id = ProtoDefId::new(ProtoDefTypes::DEF_TYPE_PAINTKIT_DEFINITION, def_index);
+        let paint_kit = defs.paint_kit_definition(def_index)
+            .ok_or(ProtoDefError::MissingDefinition(id))?;
+        let mut found = None;
+        
+        for item in items(paint_kit) {
+            let template_id = ProtoDefId::from(item.get_item_definition_template());
+            // Items whose template is missing cannot be matched and are skipped.
+            let item_definition = match defs.paint_kit_item_definition(template_id.def_index) {
+                Some(item_definition) => item_definition,
+                None => continue,
+            };
+            
+            if item_definition.get_item_definition_index() == item_def_index {
+                found = Some((item, template_id, item_definition));
+                break;
+            }
+        }
+        
+        let (item, item_definition_id, item_definition) = match found {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        let data = item.get_data();
+        
+        if !data.get_can_apply_paintkit() {
+            return Ok(None);
+        }
+        
+        let mut variables = Variables::new();
+        
+        if item_definition.has_variable_template() {
+            let template_id = ProtoDefId::from(item_definition.get_variable_template());
+            
+            if defs.get(template_id).is_none() {
+                return Err(ProtoDefError::MissingDefinition(template_id));
+            }
+            
+            variables.extend(defs.variables(template_id));
+        }
+        
+        variables.extend(defs.variables(item_definition_id));
+        
+        // Item definitions have a definition for each wear.
+        let definition = item_definition.get_definition().get(wear as usize - 1);
+        
+        if let Some(definition) = definition {
+            define_variables(&mut variables, definition.get_variable())?;
+        }
+        
+        variables.extend(defs.variables(id));
+        define_variables(&mut variables, data.get_variable())?;
+        
+        let operation_template = match definition {
+            Some(definition) if definition.has_operation_template() => {
+                Some(definition.get_operation_template())
+            },
+            _ if paint_kit.has_operation_template() => Some(paint_kit.get_operation_template()),
+            _ => None,
+        };
+        let mut flattener = Flattener {
+            defs,
+            visiting: Vec::new(),
+            operations: Vec::new(),
+        };
+        
+        if let Some(operation_template) = operation_template {
+            flattener.template(operation_template, &variables)?;
+        }
+        
+        Ok(Some(Self {
+            def_index,
+            name: paint_kit.get_header().get_name().to_string(),
+            loc_desctoken: paint_kit.get_loc_desctoken().to_string(),
+            item_def_index,
+            wear,
+            has_team_textures: paint_kit.get_has_team_textures(),
+            material_override: if data.has_material_override() {
+                Some(data.get_material_override().to_string())
+            } else {
+                None
+            },
+            variables,
+            operations: flattener.operations,
+        }))
+    }
+}
+
+/// The items listed by a paint kit, both in `item` and in the fields named after weapons.
+fn items(paint_kit: &CMsgPaintKit_Definition) -> Vec<&CMsgPaintKit_Definition_Item> {
+    let mut items = paint_kit.get_item().iter().collect::<Vec<_>>();
+    
+    for field in paint_kit.descriptor().fields() {
+        if field.is_repeated() || field.proto().get_type_name() != ITEM_TYPE_NAME {
+            continue;
+        }
+        
+        if !field.has_field(paint_kit) {
+            continue;
+        }
+        
+        if let Some(item) = field.get_message(paint_kit).as_any().downcast_ref() {
+            items.push(item);
+        }
+    }
+    
+    items
+}
+
+/// Defines the variables named by `fields` with their values.
+fn define_variables(
+    variables: &mut Variables,
+    fields: &[CMsgVarField],
+) -> Result<(), ProtoDefError> {
+    for field in fields {
+        let mut value = field.clone();
+        
+        value.clear_variable();
+        
+        let value = resolve_var_field(&value, EVarFieldType::VAR_TYPE_STRING, variables)?;
+        
+        variables.insert(field.get_variable().to_string(), value.to_string());
+    }
+    
+    Ok(())
+}
+
+fn resolve_string(
+    field: &CMsgVarField,
+    variables: &Variables,
+) -> Result<String, ProtoDefError> {
+    Ok(resolve_var_field(field, EVarFieldType::VAR_TYPE_STRING, variables)?.to_string())
+}
+
+fn resolve_optional(
+    field: &SingularPtrField<CMsgVarField>,
+    variables: &Variables,
+) -> Result<Option<String>, ProtoDefError> {
+    field.as_ref()
+        .map(|field| resolve_string(field, variables))
+        .transpose()
+}
+
+fn adjustment(
+    black: &SingularPtrField<CMsgVarField>,
+    offset: &SingularPtrField<CMsgVarField>,
+    gamma: &SingularPtrField<CMsgVarField>,
+    variables: &Variables,
+) -> Result<Adjustment, ProtoDefError> {
+    Ok(Adjustment {
+        black: resolve_optional(black, variables)?,
+        offset: resolve_optional(offset, variables)?,
+        gamma: resolve_optional(gamma, variables)?,
+    })
+}
+
+/// Flattens operation trees into the order they are evaluated.
+struct Flattener<'a> {
+    defs: &'a ProtoDefs,
+    /// The operation templates being flattened, to catch templates which include themselves.
+    visiting: Vec<ProtoDefId>,
+    operations: Vec<PaintKitOperation>,
+}
+
+impl<'a> Flattener<'a> {
+    
+    /// Adds the operations of a template, returning the number of results it produces.
+    fn template(
+        &mut self,
+        template: &CMsgProtoDefID,
+        variables: &Variables,
+    ) -> Result<usize, ProtoDefError> {
+        let id = ProtoDefId::from(template);
+        let operation = self.defs.paint_kit_operation(id.def_index)
+            .ok_or(ProtoDefError::MissingDefinition(id))?;
+        
+        if self.visiting.contains(&id) {
+            return Err(ProtoDefError::OperationCycle(id));
+        }
+        
+        let mut template_variables = self.defs.variables(id);
+        
+        template_variables.extend(variables.iter().map(|(name, value)| {
+            (name.clone(), value.clone())
+        }));
+        self.visiting.push(id);
+        
+        let results = self.nodes(operation.get_operation_node(), &template_variables)?;
+        
+        self.visiting.pop();
+        Ok(results)
+    }
+    
+    fn nodes(
+        &mut self,
+        nodes: &[CMsgPaintKit_OperationNode],
+        variables: &Variables,
+    ) -> Result<usize, ProtoDefError> {
+        let mut results = 0;
+        
+        for node in nodes {
+            results += match &node.node {
+                Some(CMsgPaintKit_OperationNode_oneof_node::stage(stage)) => {
+                    self.stage(stage, variables)?
+                },
+                Some(CMsgPaintKit_OperationNode_oneof_node::operation_template(template)) => {
+                    self.template(template, variables)?
+                },
+                None => 0,
+            };
+        }
+        
+        Ok(results)
+    }
+    
+    fn stage(
+        &mut self,
+        stage: &CMsgPaintKit_OperationStage,
+        variables: &Variables,
+    ) -> Result<usize, ProtoDefError> {
+        use CMsgPaintKit_OperationStage_oneof_stage::*;
+        
+        let operation = match &stage.stage {
+            Some(texture_lookup(stage)) => PaintKitOperation::TextureLookup(Box::new(TextureLookup {
+                texture: resolve_optional(&stage.texture, variables)?,
+                texture_red: resolve_optional(&stage.texture_red, variables)?,
+                texture_blue: resolve_optional(&stage.texture_blue, variables)?,
+                adjustment: adjustment(
+                    &stage.adjust_black,
+                    &stage.adjust_offset,
+                    &stage.adjust_gamma,
+                    variables,
+                )?,
+                transform: UvTransform {
+                    rotation: resolve_optional(&stage.rotation, variables)?,
+                    translate_u: resolve_optional(&stage.translate_u, variables)?,
+                    translate_v: resolve_optional(&stage.translate_v, variables)?,
+                    scale_uv: resolve_optional(&stage.scale_uv, variables)?,
+                    flip_u: resolve_optional(&stage.flip_u, variables)?,
+                    flip_v: resolve_optional(&stage.flip_v, variables)?,
+                },
+            })),
+            Some(combine_add(stage)) => self.combine(CombineMode::Add, stage, variables)?,
+            Some(combine_lerp(stage)) => self.combine(CombineMode::Lerp, stage, variables)?,
+            Some(combine_multiply(stage)) => self.combine(CombineMode::Multiply, stage, variables)?,
+            Some(select(stage)) => PaintKitOperation::Select {
+                groups: resolve_optional(&stage.groups, variables)?,
+                select: stage.get_select()
+                    .iter()
+                    .map(|field| resolve_string(field, variables))
+                    .collect::<Result<_, _>>()?,
+            },
+            Some(apply_sticker(stage)) => {
+                let inputs = self.nodes(stage.get_operation_node(), variables)?;
+                let stickers = stage.get_sticker()
+                    .iter()
+                    .map(|sticker| Ok(Sticker {
+                        base: resolve_optional(&sticker.base, variables)?,
+                        weight: resolve_optional(&sticker.weight, variables)?,
+                        spec: resolve_optional(&sticker.spec, variables)?,
+                    }))
+                    .collect::<Result<_, ProtoDefError>>()?;
+                
+                PaintKitOperation::ApplySticker {
+                    inputs,
+                    stickers,
+                    dest_tl: resolve_optional(&stage.dest_tl, variables)?,
+                    dest_tr: resolve_optional(&stage.dest_tr, variables)?,
+                    dest_bl: resolve_optional(&stage.dest_bl, variables)?,
+                    adjustment: adjustment(
+                        &stage.adjust_black,
+                        &stage.adjust_offset,
+                        &stage.adjust_gamma,
+                        variables,
+                    )?,
+                }
+            },
+            None => return Ok(0),
+        };
+        
+        self.operations.push(operation);
+        Ok(1)
+    }
+    
+    /// Adds the inputs of a combine stage followed by the stage.
+    fn combine(
+        &mut self,
+        mode: CombineMode,
+        stage: &CMsgPaintKit_Operation_CombineStage,
+        variables: &Variables,
+    ) -> Result<PaintKitOperation, ProtoDefError> {
+        let inputs = self.nodes(stage.get_operation_node(), variables)?;
+        
+        Ok(PaintKitOperation::Combine {
+            mode,
+            inputs,
+            adjustment: adjustment(
+                &stage.adjust_black,
+                &stage.adjust_offset,
+                &stage.adjust_gamma,
+                variables,
+            )?,
+            transform: UvTransform {
+                rotation: resolve_optional(&stage.rotation, variables)?,
+                translate_u: resolve_optional(&stage.translate_u, variables)?,
+                translate_v: resolve_optional(&stage.translate_v, variables)?,
+                scale_uv: resolve_optional(&stage.scale_uv, variables)?,
+                flip_u: resolve_optional(&stage.flip_u, variables)?,
+                flip_v: resolve_optional(&stage.flip_v, variables)?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const FIXTURE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/paint_kits.txt",
+    ));
+    
+    fn texture_lookup(
+        texture: &str,
+        texture_blue: Option<&str>,
+        scale_uv: Option<&str>,
+    ) -> PaintKitOperation {
+        PaintKitOperation::TextureLookup(Box::new(TextureLookup {
+            texture: Some(texture.to_string()),
+            texture_blue: texture_blue.map(String::from),
+            transform: UvTransform {
+                scale_uv: scale_uv.map(String::from),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+    }
+    
+    #[test]
+    fn flattens_operations_in_evaluation_order() {
+        let defs = ProtoDefs::parse(FIXTURE).unwrap();
+        let paint_kit = PaintKit::resolve(&defs, 30, 200, Wear::FactoryNew).unwrap().unwrap();
+        
+        assert_eq!(paint_kit.name, "paint_kit");
+        assert_eq!(paint_kit.operations, [
+            texture_lookup("paint_kit", None, Some("2")),
+            texture_lookup("item_sticker", Some("template_blue"), None),
+            PaintKitOperation::Combine {
+                mode: CombineMode::Multiply,
+                inputs: 2,
+                adjustment: Adjustment::default(),
+                transform: UvTransform::default(),
+            },
+            PaintKitOperation::Select {
+                groups: Some("groups".to_string()),
+                select: vec!["factory_new".to_string()],
+            },
+        ]);
+    }
+    
+    #[test]
+    fn variables_follow_precedence() {
+        let defs = ProtoDefs::parse(FIXTURE).unwrap();
+        let paint_kit = PaintKit::resolve(&defs, 30, 200, Wear::MinimalWear).unwrap().unwrap();
+        let variable = |name: &str| paint_kit.variables.get(name).map(String::as_str);
+        
+        // The item definition overrides its variable template.
+        assert_eq!(variable("scale"), Some("2"));
+        // The paint kit overrides the variable template.
+        assert_eq!(variable("color"), Some("paint_kit"));
+        // The item definition's definition for the wear.
+        assert_eq!(variable("wear"), Some("minimal_wear"));
+        // The paint kit's data for the item overrides the paint kit.
+        assert_eq!(variable("sticker"), Some("item_sticker"));
+        assert_eq!(variable("blue"), None);
+    }
+    
+    #[test]
+    fn items_with_missing_templates_are_skipped() {
+        let defs = ProtoDefs::parse(FIXTURE).unwrap();
+        
+        assert!(PaintKit::resolve(&defs, 30, 300, Wear::FactoryNew).unwrap().is_none());
+        assert!(matches!(
+            PaintKit::resolve(&defs, 31, 200, Wear::FactoryNew),
+            Err(ProtoDefError::MissingDefinition(_)),
+        ));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,256 @@
+//! Views of the party shared objects and party chat.
+
+use steam_vent::{connection::Connection, net::NetworkError};
+use tf2_protobuf::tf_gcmessages::{
+    CSOTFParty,
+    CSOTFPartyInvite,
+    CSOTFPartyInvite_Type,
+    CMsgPartyChatMsg,
+    CTFPartyOptions,
+    ETFMatchGroup,
+    ETFPartyChatType,
+    TFPendingPartyMember_EType,
+};
+use crate::{Error, TeamFortress2};
+
+/// A player who was invited to or asked to join the party.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingMember {
+    pub steamid: u64,
+    /// `true` if the player asked to join, `false` if they were invited.
+    pub requested_to_join: bool,
+    pub inviter: u64,
+}
+
+/// A matchmaking queue the party is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueEntry {
+    pub match_group: ETFMatchGroup,
+    /// Unix timestamp of when the party joined the queue.
+    pub queued_time: u32,
+}
+
+/// The party the client is in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Party {
+    pub party_id: u64,
+    pub leader_id: u64,
+    pub member_ids: Vec<u64>,
+    pub pending_members: Vec<PendingMember>,
+    pub queues: Vec<QueueEntry>,
+    /// The lobby the party is playing in, if any.
+    pub associated_lobby_id: Option<u64>,
+}
+
+impl Party {
+    
+    pub fn is_leader(&self, steamid: u64) -> bool {
+        self.leader_id == steamid
+    }
+    
+    pub fn is_member(&self, steamid: u64) -> bool {
+        self.member_ids.contains(&steamid)
+    }
+    
+    pub fn is_queued(&self, match_group: ETFMatchGroup) -> bool {
+        self.queues.iter().any(|queue| queue.match_group == match_group)
+    }
+}
+
+impl From<&CSOTFParty> for Party {
+    fn from(party: &CSOTFParty) -> Self {
+        Self {
+            party_id: party.get_party_id(),
+            leader_id: party.get_leader_id(),
+            member_ids: party.get_member_ids().to_vec(),
+            pending_members: party.get_pending_members()
+                .iter()
+                .map(|member| PendingMember {
+                    steamid: member.get_steamid(),
+                    requested_to_join: member.get_field_type() == TFPendingPartyMember_EType::RequestedToJoin,
+                    inviter: member.get_inviter(),
+                })
+                .collect(),
+            queues: party.get_matchmaking_queues()
+                .iter()
+                .map(|queue| QueueEntry {
+                    match_group: queue.get_match_group(),
+                    queued_time: queue.get_queued_time(),
+                })
+                .collect(),
+            associated_lobby_id: Some(party.get_associated_lobby_id())
+                .filter(|lobby_id| *lobby_id != 0),
+        }
+    }
+}
+
+/// An invite to a party, or a request to join one of ours.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartyInvite {
+    pub party_id: u64,
+    pub inviter: u64,
+    /// `true` if this is a request to join rather than an invite.
+    pub is_join_request: bool,
+    pub member_ids: Vec<u64>,
+}
+
+impl From<&CSOTFPartyInvite> for PartyInvite {
+    fn from(invite: &CSOTFPartyInvite) -> Self {
+        Self {
+            party_id: invite.get_group_id(),
+            inviter: invite.get_inviter(),
+            is_join_request: invite.get_field_type() == CSOTFPartyInvite_Type::PENDING_JOIN_REQUEST,
+            member_ids: invite.get_members()
+                .iter()
+                .map(|member| member.get_steamid())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartyChatType {
+    MemberChat,
+    MemberJoin,
+    MemberLeave,
+    /// A message sent by us could not be delivered.
+    SendFailed,
+    MemberOnline,
+    MemberOffline,
+    Invalid,
+}
+
+impl From<ETFPartyChatType> for PartyChatType {
+    fn from(chat_type: ETFPartyChatType) -> Self {
+        match chat_type {
+            ETFPartyChatType::k_eTFPartyChatType_MemberChat => Self::MemberChat,
+            ETFPartyChatType::k_eTFPartyChatType_Synthetic_MemberJoin => Self::MemberJoin,
+            ETFPartyChatType::k_eTFPartyChatType_Synthetic_MemberLeave => Self::MemberLeave,
+            ETFPartyChatType::k_eTFPartyChatType_Synthetic_SendFailed => Self::SendFailed,
+            ETFPartyChatType::k_eTFPartyChatType_Synthetic_MemberOnline => Self::MemberOnline,
+            ETFPartyChatType::k_eTFPartyChatType_Synthetic_MemberOffline => Self::MemberOffline,
+            ETFPartyChatType::k_eTFPartyChatType_Invalid => Self::Invalid,
+        }
+    }
+}
+
+/// A party chat message or membership change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartyChatMessage {
+    pub chat_type: PartyChatType,
+    pub actor_id: u64,
+    pub message: String,
+}
+
+impl From<&CMsgPartyChatMsg> for PartyChatMessage {
+    fn from(message: &CMsgPartyChatMsg) -> Self {
+        Self {
+            chat_type: message.get_field_type().into(),
+            actor_id: message.get_actor_id(),
+            message: message.get_msg().to_string(),
+        }
+    }
+}
+
+/// Matchmaking options for the party.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PartyOptions {
+    /// Replace all options rather than only the ones given.
+    pub overwrite_existing: bool,
+    pub late_join_ok: Option<bool>,
+    pub custom_ping_tolerance: Option<u32>,
+    /// The casual maps selected, as a bitset over the casual map list.
+    pub casual_selected_maps_bits: Option<Vec<u32>>,
+    pub squad_surplus: Option<bool>,
+}
+
+impl From<&PartyOptions> for CTFPartyOptions {
+    fn from(options: &PartyOptions) -> Self {
+        let mut message = CTFPartyOptions::new();
+        
+        message.set_overwrite_existing(options.overwrite_existing);
+        
+        if let Some(late_join_ok) = options.late_join_ok {
+            message.mut_group_criteria().set_late_join_ok(late_join_ok);
+        }
+        
+        if let Some(ping_tolerance) = options.custom_ping_tolerance {
+            message.mut_group_criteria().set_custom_ping_tolerance(ping_tolerance);
+        }
+        
+        if let Some(maps_bits) = &options.casual_selected_maps_bits {
+            message.mut_group_criteria()
+                .mut_casual_criteria()
+                .set_selected_maps_bits(maps_bits.clone());
+        }
+        
+        if let Some(squad_surplus) = options.squad_surplus {
+            message.mut_player_criteria().set_mvm_squad_surplus(squad_surplus);
+        }
+        
+        message
+    }
+}
+
+/// Acts on the client's party, from [`TeamFortress2::party_handle`]. The GC does not reply to
+/// invites, join requests, kicks or promotions directly; their results arrive as the party
+/// events of [`crate::event::GCEvent`], e.g. [`crate::event::GCEvent::PartyMemberLeft`].
+pub struct PartyHandle<'a> {
+    tf2: &'a mut TeamFortress2,
+    connection: &'a mut Connection,
+}
+
+impl<'a> PartyHandle<'a> {
+    
+    pub(crate) fn new(tf2: &'a mut TeamFortress2, connection: &'a mut Connection) -> Self {
+        Self {
+            tf2,
+            connection,
+        }
+    }
+    
+    /// The current state of the party from the cache.
+    pub fn party(&self) -> Option<Party> {
+        self.tf2.party()
+    }
+    
+    /// Invites a player. Results in [`crate::event::GCEvent::PartyPendingMemberAdded`].
+    pub async fn invite(&mut self, steamid: u64) -> Result<u64, NetworkError> {
+        self.tf2.invite_to_party(self.connection, steamid, false).await
+    }
+    
+    /// Accepts a request to join from a player.
+    pub async fn accept_join_request(&mut self, steamid: u64) -> Result<u64, NetworkError> {
+        self.tf2.invite_to_party(self.connection, steamid, true).await
+    }
+    
+    /// Asks to join the party of a player, accepting their invite if `expecting_invite` is set.
+    pub async fn request_join(
+        &mut self,
+        steamid: u64,
+        party_id: u64,
+        expecting_invite: bool,
+    ) -> Result<u64, NetworkError> {
+        self.tf2.request_join_party(self.connection, steamid, party_id, expecting_invite).await
+    }
+    
+    /// Kicks a member. Results in [`crate::event::GCEvent::PartyMemberLeft`].
+    pub async fn kick(&mut self, steamid: u64) -> Result<u64, Error> {
+        self.tf2.kick_party_member(self.connection, steamid).await
+    }
+    
+    /// Makes a member the leader. Results in [`crate::event::GCEvent::PartyLeaderChanged`].
+    pub async fn promote(&mut self, steamid: u64) -> Result<u64, Error> {
+        self.tf2.promote_to_party_leader(self.connection, steamid).await
+    }
+    
+    /// Sends a chat message. Results in [`crate::event::GCEvent::PartyChat`].
+    pub async fn send_chat(&mut self, text: &str) -> Result<u64, Error> {
+        self.tf2.send_party_chat(self.connection, text).await
+    }
+    
+    /// Sets the party's options. Results in [`crate::event::GCEvent::PartyOptionsSet`].
+    pub async fn set_options(&mut self, options: &PartyOptions) -> Result<u64, Error> {
+        self.tf2.set_party_options(self.connection, options).await
+    }
+}
\ No newline at end of file
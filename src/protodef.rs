@@ -0,0 +1,1054 @@
+//! A database of the definitions in TF2's proto def files (`tf_proto_def_messages`), such as
+//! quests, the quest map and paint kits.
+//!
+//! Definitions are keyed by their type and defindex, as referenced by [`CMsgProtoDefID`], and
+//! have the definitions listed as prefabs in their header applied.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use protobuf::{CodedInputStream, Message, ProtobufEnum, ProtobufError};
+use protobuf::descriptor::{DescriptorProto, FieldDescriptorProto, FieldDescriptorProto_Type};
+use protobuf::wire_format::WireType;
+use tf2_protobuf::tf_proto_def_messages::{
+    self,
+    CMsgFieldID,
+    CMsgFieldID_CMsgField,
+    CMsgHeaderOnly,
+    CMsgPaintKit_Definition,
+    CMsgPaintKit_ItemDefinition,
+    CMsgPaintKit_Operation,
+    CMsgPaintKit_Variables,
+    CMsgProtoDefHeader,
+    CMsgProtoDefID,
+    CMsgQuestDef,
+    CMsgQuestMapNodeDef,
+    CMsgQuestMapRegionDef,
+    CMsgQuestMapStarType,
+    CMsgQuestMapStoreItem,
+    CMsgQuestObjectiveDef,
+    CMsgQuestTheme,
+    CMsgVarField,
+    CMsgVarField_oneof_value,
+    CMsgVariableDefinition,
+    EQuestPoints,
+    EValueDefinitionSource,
+    EVarFieldType,
+    ProtoDefTypes,
+};
+use crate::text_format::{self, Descriptors, TextMessage, TextValue};
+
+const PROTO_DEF_ID_TYPE_NAME: &str = ".CMsgProtoDefID";
+const HEADER_TYPE_NAME: &str = ".CMsgProtoDefHeader";
+const VAR_FIELD_TYPE_NAME: &str = ".CMsgVarField";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProtoDefError {
+    #[error("{}", .0)]
+    IO(#[from] std::io::Error),
+    #[error("{}", .0)]
+    Proto(#[from] ProtobufError),
+    #[error("{}", .0)]
+    Parse(#[from] text_format::ParseError),
+    #[error("Unknown definition type {}", .0)]
+    UnknownType(i32),
+    #[error("Unknown definition type \"{}\"", .0)]
+    UnknownTypeName(String),
+    #[error("Definition {} is not loaded", .0)]
+    MissingDefinition(ProtoDefId),
+    #[error("Definition {} inherits from itself", .0)]
+    PrefabCycle(ProtoDefId),
+    #[error("Operation {} includes itself", .0)]
+    OperationCycle(ProtoDefId),
+    #[error("Variable \"{}\" is not defined", .0)]
+    UndefinedVariable(String),
+    #[error("Variable \"{}\" has invalid value \"{}\"", .name, .value)]
+    InvalidVariable {
+        name: String,
+        value: String,
+    },
+}
+
+/// The key of a definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProtoDefId {
+    pub def_type: ProtoDefTypes,
+    pub def_index: u32,
+}
+
+impl ProtoDefId {
+    
+    pub fn new(def_type: ProtoDefTypes, def_index: u32) -> Self {
+        Self {
+            def_type,
+            def_index,
+        }
+    }
+}
+
+impl From<&CMsgProtoDefID> for ProtoDefId {
+    fn from(id: &CMsgProtoDefID) -> Self {
+        Self::new(id.get_field_type(), id.get_defindex())
+    }
+}
+
+impl fmt::Display for ProtoDefId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {}", self.def_type, self.def_index)
+    }
+}
+
+/// A definition of any type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtoDef {
+    QuestMapNode(CMsgQuestMapNodeDef),
+    QuestTheme(CMsgQuestTheme),
+    QuestMapRegion(CMsgQuestMapRegionDef),
+    Quest(CMsgQuestDef),
+    QuestObjective(CMsgQuestObjectiveDef),
+    PaintKitVariables(CMsgPaintKit_Variables),
+    PaintKitOperation(CMsgPaintKit_Operation),
+    PaintKitItemDefinition(CMsgPaintKit_ItemDefinition),
+    PaintKitDefinition(Box<CMsgPaintKit_Definition>),
+    HeaderOnly(CMsgHeaderOnly),
+    QuestMapStoreItem(CMsgQuestMapStoreItem),
+    QuestMapStarType(CMsgQuestMapStarType),
+}
+
+impl ProtoDef {
+    
+    /// Decodes a definition of `def_type` from the binary format.
+    pub fn parse(def_type: ProtoDefTypes, bytes: &[u8]) -> Result<Self, ProtobufError> {
+        use ProtoDefTypes::*;
+        
+        Ok(match def_type {
+            DEF_TYPE_QUEST_MAP_NODE => Self::QuestMapNode(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_QUEST_THEME => Self::QuestTheme(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_QUEST_MAP_REGION => Self::QuestMapRegion(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_QUEST => Self::Quest(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_QUEST_OBJECTIVE => Self::QuestObjective(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_PAINTKIT_VARIABLES => Self::PaintKitVariables(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_PAINTKIT_OPERATION => Self::PaintKitOperation(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_PAINTKIT_ITEM_DEFINITION => Self::PaintKitItemDefinition(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_PAINTKIT_DEFINITION => Self::PaintKitDefinition(Box::new(Message::parse_from_bytes(bytes)?)),
+            DEF_TYPE_HEADER_ONLY => Self::HeaderOnly(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_QUEST_MAP_STORE_ITEM => Self::QuestMapStoreItem(Message::parse_from_bytes(bytes)?),
+            DEF_TYPE_QUEST_MAP_STAR_TYPE => Self::QuestMapStarType(Message::parse_from_bytes(bytes)?),
+        })
+    }
+    
+    pub fn def_type(&self) -> ProtoDefTypes {
+        use ProtoDefTypes::*;
+        
+        match self {
+            Self::QuestMapNode(_) => DEF_TYPE_QUEST_MAP_NODE,
+            Self::QuestTheme(_) => DEF_TYPE_QUEST_THEME,
+            Self::QuestMapRegion(_) => DEF_TYPE_QUEST_MAP_REGION,
+            Self::Quest(_) => DEF_TYPE_QUEST,
+            Self::QuestObjective(_) => DEF_TYPE_QUEST_OBJECTIVE,
+            Self::PaintKitVariables(_) => DEF_TYPE_PAINTKIT_VARIABLES,
+            Self::PaintKitOperation(_) => DEF_TYPE_PAINTKIT_OPERATION,
+            Self::PaintKitItemDefinition(_) => DEF_TYPE_PAINTKIT_ITEM_DEFINITION,
+            Self::PaintKitDefinition(_) => DEF_TYPE_PAINTKIT_DEFINITION,
+            Self::HeaderOnly(_) => DEF_TYPE_HEADER_ONLY,
+            Self::QuestMapStoreItem(_) => DEF_TYPE_QUEST_MAP_STORE_ITEM,
+            Self::QuestMapStarType(_) => DEF_TYPE_QUEST_MAP_STAR_TYPE,
+        }
+    }
+    
+    pub fn id(&self) -> ProtoDefId {
+        ProtoDefId::new(self.def_type(), self.header().get_defindex())
+    }
+    
+    /// The definition as a message.
+    pub fn message(&self) -> &dyn Message {
+        match self {
+            Self::QuestMapNode(def) => def,
+            Self::QuestTheme(def) => def,
+            Self::QuestMapRegion(def) => def,
+            Self::Quest(def) => def,
+            Self::QuestObjective(def) => def,
+            Self::PaintKitVariables(def) => def,
+            Self::PaintKitOperation(def) => def,
+            Self::PaintKitItemDefinition(def) => def,
+            Self::PaintKitDefinition(def) => def.as_ref(),
+            Self::HeaderOnly(def) => def,
+            Self::QuestMapStoreItem(def) => def,
+            Self::QuestMapStarType(def) => def,
+        }
+    }
+    
+    pub fn header(&self) -> &CMsgProtoDefHeader {
+        match self {
+            Self::QuestMapNode(def) => def.get_header(),
+            Self::QuestTheme(def) => def.get_header(),
+            Self::QuestMapRegion(def) => def.get_header(),
+            Self::Quest(def) => def.get_header(),
+            Self::QuestObjective(def) => def.get_header(),
+            Self::PaintKitVariables(def) => def.get_header(),
+            Self::PaintKitOperation(def) => def.get_header(),
+            Self::PaintKitItemDefinition(def) => def.get_header(),
+            Self::PaintKitDefinition(def) => def.get_header(),
+            Self::HeaderOnly(def) => def.get_header(),
+            Self::QuestMapStoreItem(def) => def.get_header(),
+            Self::QuestMapStarType(def) => def.get_header(),
+        }
+    }
+    
+    fn set_header(&mut self, header: CMsgProtoDefHeader) {
+        match self {
+            Self::QuestMapNode(def) => def.set_header(header),
+            Self::QuestTheme(def) => def.set_header(header),
+            Self::QuestMapRegion(def) => def.set_header(header),
+            Self::Quest(def) => def.set_header(header),
+            Self::QuestObjective(def) => def.set_header(header),
+            Self::PaintKitVariables(def) => def.set_header(header),
+            Self::PaintKitOperation(def) => def.set_header(header),
+            Self::PaintKitItemDefinition(def) => def.set_header(header),
+            Self::PaintKitDefinition(def) => def.set_header(header),
+            Self::HeaderOnly(def) => def.set_header(header),
+            Self::QuestMapStoreItem(def) => def.set_header(header),
+            Self::QuestMapStarType(def) => def.set_header(header),
+        }
+    }
+    
+    /// The fully qualified name of the message type, e.g. `.CMsgQuestDef`.
+    fn type_name(&self) -> String {
+        format!(".{}", self.message().descriptor().full_name())
+    }
+}
+
+/// The value of a [`CMsgVarField`] or other scalar field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VarValue {
+    Float(f32),
+    Double(f64),
+    UInt32(u32),
+    UInt64(u64),
+    SInt32(i32),
+    SInt64(i64),
+    Bool(bool),
+    String(String),
+}
+
+impl VarValue {
+    
+    /// Parses the value of the variable `name` as `var_type`. Invalid types are kept as strings.
+    pub fn parse(var_type: EVarFieldType, name: &str, value: &str) -> Result<Self, ProtoDefError> {
+        let invalid = || ProtoDefError::InvalidVariable {
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+        let trimmed = value.trim();
+        
+        Ok(match var_type {
+            EVarFieldType::VAR_TYPE_FLOAT => Self::Float(trimmed.parse().map_err(|_| invalid())?),
+            EVarFieldType::VAR_TYPE_DOUBLE => Self::Double(trimmed.parse().map_err(|_| invalid())?),
+            EVarFieldType::VAR_TYPE_UINT32 => Self::UInt32(trimmed.parse().map_err(|_| invalid())?),
+            EVarFieldType::VAR_TYPE_UINT64 => Self::UInt64(trimmed.parse().map_err(|_| invalid())?),
+            EVarFieldType::VAR_TYPE_SINT32 => Self::SInt32(trimmed.parse().map_err(|_| invalid())?),
+            EVarFieldType::VAR_TYPE_SINT64 => Self::SInt64(trimmed.parse().map_err(|_| invalid())?),
+            EVarFieldType::VAR_TYPE_BOOL => match trimmed {
+                "1" | "true" => Self::Bool(true),
+                "0" | "false" => Self::Bool(false),
+                _ => return Err(invalid()),
+            },
+            EVarFieldType::VAR_TYPE_STRING |
+            EVarFieldType::VAR_TYPE_INVALID => Self::String(value.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for VarValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Float(value) => write!(f, "{}", value),
+            Self::Double(value) => write!(f, "{}", value),
+            Self::UInt32(value) => write!(f, "{}", value),
+            Self::UInt64(value) => write!(f, "{}", value),
+            Self::SInt32(value) => write!(f, "{}", value),
+            Self::SInt64(value) => write!(f, "{}", value),
+            Self::Bool(value) => write!(f, "{}", *value as u8),
+            Self::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Variable values by name.
+pub type Variables = HashMap<String, String>;
+
+/// Resolves a [`CMsgVarField`], looking its variable up in `variables` if it names one.
+pub fn resolve_var_field(
+    field: &CMsgVarField,
+    var_type: EVarFieldType,
+    variables: &Variables,
+) -> Result<VarValue, ProtoDefError> {
+    if field.has_variable() {
+        let name = field.get_variable();
+        let value = variables.get(name)
+            .ok_or_else(|| ProtoDefError::UndefinedVariable(name.to_string()))?;
+        
+        return VarValue::parse(var_type, name, value);
+    }
+    
+    Ok(match &field.value {
+        Some(CMsgVarField_oneof_value::float(value)) => VarValue::Float(*value),
+        Some(CMsgVarField_oneof_value::double(value)) => VarValue::Double(*value),
+        Some(CMsgVarField_oneof_value::uint32(value)) => VarValue::UInt32(*value),
+        Some(CMsgVarField_oneof_value::uint64(value)) => VarValue::UInt64(*value),
+        Some(CMsgVarField_oneof_value::sint32(value)) => VarValue::SInt32(*value),
+        Some(CMsgVarField_oneof_value::sint64(value)) => VarValue::SInt64(*value),
+        Some(CMsgVarField_oneof_value::bool(value)) => VarValue::Bool(*value),
+        Some(CMsgVarField_oneof_value::string(value)) => VarValue::parse(var_type, "", value)?,
+        None => VarValue::parse(var_type, "", "")?,
+    })
+}
+
+/// An objective of a quest with its point value resolved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuestObjective<'a> {
+    pub objective: &'a CMsgQuestObjectiveDef,
+    pub point_type: EQuestPoints,
+    pub points: u32,
+}
+
+/// Definitions loaded from proto def files, with prefabs applied.
+#[derive(Debug, Clone)]
+pub struct ProtoDefs {
+    /// The definitions as loaded.
+    raw: HashMap<ProtoDefId, ProtoDef>,
+    defs: HashMap<ProtoDefId, ProtoDef>,
+    /// The descriptors of the proto def messages, used to walk definitions by field.
+    descriptors: Descriptors<'static>,
+}
+
+impl Default for ProtoDefs {
+    fn default() -> Self {
+        Self {
+            raw: HashMap::new(),
+            defs: HashMap::new(),
+            descriptors: Descriptors::new(&[tf_proto_def_messages::file_descriptor_proto()]),
+        }
+    }
+}
+
+impl ProtoDefs {
+    
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Loads a proto def file. Files ending in `.txt` are read as text, other files as binary.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ProtoDefError> {
+        let path = path.as_ref();
+        
+        if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("txt")) {
+            Self::parse(&std::fs::read_to_string(path)?)
+        } else {
+            Self::from_bytes(&std::fs::read(path)?)
+        }
+    }
+    
+    /// Loads definitions from the binary format. The data is a series of blocks, each a
+    /// little-endian `u32` [`ProtoDefTypes`] value and definition count followed by the
+    /// definitions, each prefixed with its little-endian `u32` length. This layout is assumed
+    /// and has not been confirmed against the game's files.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProtoDefError> {
+        let mut defs = Self::new();
+        let mut is = CodedInputStream::from_bytes(bytes);
+        
+        while !is.eof()? {
+            let def_type = is.read_raw_little_endian32()? as i32;
+            let def_type = ProtoDefTypes::from_i32(def_type)
+                .ok_or(ProtoDefError::UnknownType(def_type))?;
+            let count = is.read_raw_little_endian32()?;
+            
+            for _ in 0..count {
+                let size = is.read_raw_little_endian32()?;
+                let data = is.read_raw_bytes(size)?;
+                let def = ProtoDef::parse(def_type, &data)?;
+                
+                defs.raw.insert(def.id(), def);
+            }
+        }
+        
+        defs.apply_prefabs()?;
+        Ok(defs)
+    }
+    
+    /// Loads definitions from the protobuf text format. Each definition is a block named after
+    /// its type, e.g. `DEF_TYPE_QUEST { header { defindex: 1 } max_points_0: 100 }`.
+    pub fn parse(text: &str) -> Result<Self, ProtoDefError> {
+        let mut defs = Self::new();
+        let message = TextMessage::parse(text)?;
+        
+        for field in message.fields() {
+            let def_type = ProtoDefTypes::values()
+                .iter()
+                .find(|def_type| def_type.descriptor().name() == field.name)
+                .copied()
+                .ok_or_else(|| ProtoDefError::UnknownTypeName(field.name.clone()))?;
+            let def = match &field.value {
+                TextValue::Message(message) => {
+                    let type_name = message_type_name(def_type);
+                    let bytes = message.encode(&defs.descriptors, type_name)?;
+                    
+                    ProtoDef::parse(def_type, &bytes)?
+                },
+                _ => return Err(ProtoDefError::UnknownTypeName(field.name.clone())),
+            };
+            
+            defs.raw.insert(def.id(), def);
+        }
+        
+        defs.apply_prefabs()?;
+        Ok(defs)
+    }
+    
+    /// Builds each definition from its prefabs, in order, and then its own values. Singular
+    /// fields override those of the prefabs and repeated fields are appended to them. Header
+    /// variables are merged by name and the defindex, name and prefabs are never inherited.
+    fn apply_prefabs(&mut self) -> Result<(), ProtoDefError> {
+        let mut defs = HashMap::new();
+        
+        for id in self.raw.keys() {
+            self.apply_prefabs_to(*id, &mut defs, &mut Vec::new())?;
+        }
+        
+        self.defs = defs;
+        Ok(())
+    }
+    
+    fn apply_prefabs_to(
+        &self,
+        id: ProtoDefId,
+        defs: &mut HashMap<ProtoDefId, ProtoDef>,
+        visiting: &mut Vec<ProtoDefId>,
+    ) -> Result<(), ProtoDefError> {
+        if defs.contains_key(&id) {
+            return Ok(());
+        }
+        
+        if visiting.contains(&id) {
+            return Err(ProtoDefError::PrefabCycle(id));
+        }
+        
+        let raw = self.raw.get(&id)
+            .ok_or(ProtoDefError::MissingDefinition(id))?;
+        let mut bytes = Vec::new();
+        let mut tags: Vec<String> = Vec::new();
+        let mut variables: Vec<CMsgVariableDefinition> = Vec::new();
+        
+        visiting.push(id);
+        
+        for prefab_id in self.prefab_ids(raw) {
+            self.apply_prefabs_to(prefab_id, defs, visiting)?;
+            
+            let prefab = &defs[&prefab_id];
+            
+            bytes.extend(prefab.message().write_to_bytes()?);
+            tags.extend(prefab.header().get_tags().iter().cloned());
+            merge_variables(
+                &mut variables,
+                prefab.header().get_variables().iter().filter(|variable| variable.get_inherit()),
+            );
+        }
+        
+        visiting.pop();
+        bytes.extend(raw.message().write_to_bytes()?);
+        
+        let mut def = ProtoDef::parse(id.def_type, &bytes)?;
+        let mut header = raw.header().clone();
+        
+        for tag in raw.header().get_tags() {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        
+        merge_variables(&mut variables, raw.header().get_variables().iter());
+        header.set_tags(tags.into());
+        header.set_variables(variables.into());
+        def.set_header(header);
+        defs.insert(id, def);
+        Ok(())
+    }
+    
+    /// Prefabs are looked up among definitions of the same type.
+    fn prefab_ids(&self, def: &ProtoDef) -> Vec<ProtoDefId> {
+        def.header().get_prefabs()
+            .iter()
+            .map(|prefab| ProtoDefId::new(def.def_type(), prefab.get_defindex()))
+            .collect()
+    }
+    
+    /// A definition with its prefabs applied.
+    pub fn get(&self, id: ProtoDefId) -> Option<&ProtoDef> {
+        self.defs.get(&id)
+    }
+    
+    /// A definition as it was loaded, without its prefabs applied.
+    pub fn get_raw(&self, id: ProtoDefId) -> Option<&ProtoDef> {
+        self.raw.get(&id)
+    }
+    
+    /// Looks up the definition a [`CMsgProtoDefID`] refers to.
+    pub fn resolve(&self, id: &CMsgProtoDefID) -> Option<&ProtoDef> {
+        self.get(ProtoDefId::from(id))
+    }
+    
+    /// All definitions, excluding those which are only prefabs.
+    pub fn definitions(&self) -> impl Iterator<Item = &ProtoDef> {
+        self.defs.values().filter(|def| !def.header().get_prefab_only())
+    }
+    
+    /// All definitions of `def_type`, excluding those which are only prefabs.
+    pub fn of_type(&self, def_type: ProtoDefTypes) -> impl Iterator<Item = &ProtoDef> {
+        self.definitions().filter(move |def| def.def_type() == def_type)
+    }
+    
+    pub fn quest(&self, def_index: u32) -> Option<&CMsgQuestDef> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST, def_index))? {
+            ProtoDef::Quest(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn quest_objective(&self, def_index: u32) -> Option<&CMsgQuestObjectiveDef> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_OBJECTIVE, def_index))? {
+            ProtoDef::QuestObjective(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn quest_theme(&self, def_index: u32) -> Option<&CMsgQuestTheme> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_THEME, def_index))? {
+            ProtoDef::QuestTheme(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn quest_map_node(&self, def_index: u32) -> Option<&CMsgQuestMapNodeDef> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_MAP_NODE, def_index))? {
+            ProtoDef::QuestMapNode(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn quest_map_region(&self, def_index: u32) -> Option<&CMsgQuestMapRegionDef> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_MAP_REGION, def_index))? {
+            ProtoDef::QuestMapRegion(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn quest_map_store_item(&self, def_index: u32) -> Option<&CMsgQuestMapStoreItem> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_MAP_STORE_ITEM, def_index))? {
+            ProtoDef::QuestMapStoreItem(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn quest_map_star_type(&self, def_index: u32) -> Option<&CMsgQuestMapStarType> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_MAP_STAR_TYPE, def_index))? {
+            ProtoDef::QuestMapStarType(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn paint_kit_definition(&self, def_index: u32) -> Option<&CMsgPaintKit_Definition> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_PAINTKIT_DEFINITION, def_index))? {
+            ProtoDef::PaintKitDefinition(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn paint_kit_item_definition(&self, def_index: u32) -> Option<&CMsgPaintKit_ItemDefinition> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_PAINTKIT_ITEM_DEFINITION, def_index))? {
+            ProtoDef::PaintKitItemDefinition(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn paint_kit_operation(&self, def_index: u32) -> Option<&CMsgPaintKit_Operation> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_PAINTKIT_OPERATION, def_index))? {
+            ProtoDef::PaintKitOperation(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    pub fn paint_kit_variables(&self, def_index: u32) -> Option<&CMsgPaintKit_Variables> {
+        match self.get(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_PAINTKIT_VARIABLES, def_index))? {
+            ProtoDef::PaintKitVariables(def) => Some(def),
+            _ => None,
+        }
+    }
+    
+    /// The objectives of a quest, skipping those which are not loaded.
+    pub fn quest_objectives<'a>(&'a self, quest: &'a CMsgQuestDef) -> Vec<QuestObjective<'a>> {
+        quest.get_objectives()
+            .iter()
+            .filter_map(|instance| {
+                let objective = match self.resolve(instance.get_objective())? {
+                    ProtoDef::QuestObjective(objective) => objective,
+                    _ => return None,
+                };
+                let points = if instance.has_point_value() {
+                    instance.get_point_value()
+                } else {
+                    objective.get_points()
+                };
+                
+                Some(QuestObjective {
+                    objective,
+                    point_type: instance.get_point_type(),
+                    points,
+                })
+            })
+            .collect()
+    }
+    
+    /// The variables defined by a definition and inherited from its prefabs.
+    pub fn variables(&self, id: ProtoDefId) -> Variables {
+        self.get(id)
+            .map(|def| header_variables(def.header()))
+            .unwrap_or_default()
+    }
+    
+    /// The definitions referenced by a definition, excluding its prefabs.
+    pub fn references(&self, id: ProtoDefId) -> Result<Vec<ProtoDefId>, ProtoDefError> {
+        let def = self.get(id).ok_or(ProtoDefError::MissingDefinition(id))?;
+        let descriptors = &self.descriptors;
+        let descriptor = message_descriptor(descriptors, def)?;
+        let mut references = Vec::new();
+        
+        collect_references(
+            &def.message().write_to_bytes()?,
+            descriptor,
+            descriptors,
+            &mut references,
+        )?;
+        
+        Ok(references)
+    }
+    
+    /// References to definitions which are not loaded, as the referencing definition and the
+    /// missing definition.
+    pub fn dangling_references(&self) -> Result<Vec<(ProtoDefId, ProtoDefId)>, ProtoDefError> {
+        let mut dangling = Vec::new();
+        
+        for id in self.defs.keys() {
+            for reference in self.references(*id)? {
+                if !self.defs.contains_key(&reference) {
+                    dangling.push((*id, reference));
+                }
+            }
+        }
+        
+        Ok(dangling)
+    }
+    
+    /// The value of the scalar field at `field_id` in a definition with its prefabs applied.
+    /// [`CMsgVarField`] values are resolved with the definition's variables. Returns `None` if
+    /// the field is not set or is a message.
+    pub fn field_value(
+        &self,
+        id: ProtoDefId,
+        field_id: &CMsgFieldID,
+    ) -> Result<Option<VarValue>, ProtoDefError> {
+        let def = self.get(id).ok_or(ProtoDefError::MissingDefinition(id))?;
+        let descriptors = &self.descriptors;
+        let descriptor = message_descriptor(descriptors, def)?;
+        let bytes = def.message().write_to_bytes()?;
+        let (field, value) = match find_field(&bytes, descriptor, descriptors, field_id.get_field())? {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        
+        decode_field(field, value, &header_variables(def.header()))
+    }
+    
+    /// Where the value of the field at `field_id` comes from: the definition itself, a variable
+    /// it defines, one of its prefabs, or nowhere.
+    pub fn field_source(
+        &self,
+        id: ProtoDefId,
+        field_id: &CMsgFieldID,
+    ) -> Result<EValueDefinitionSource, ProtoDefError> {
+        let raw = self.get_raw(id).ok_or(ProtoDefError::MissingDefinition(id))?;
+        let descriptors = &self.descriptors;
+        let descriptor = message_descriptor(descriptors, raw)?;
+        let bytes = raw.message().write_to_bytes()?;
+        
+        if let Some((field, value)) = find_field(&bytes, descriptor, descriptors, field_id.get_field())? {
+            if let (VAR_FIELD_TYPE_NAME, WireValue::Bytes(bytes)) = (field.get_type_name(), &value) {
+                let var_field: CMsgVarField = Message::parse_from_bytes(bytes)?;
+                
+                if var_field.has_variable() {
+                    return Ok(EValueDefinitionSource::VARIABLE_DEFINES);
+                }
+            }
+            
+            return Ok(EValueDefinitionSource::THIS_DEFINES);
+        }
+        
+        for prefab_id in self.prefab_ids(raw) {
+            if self.field_source(prefab_id, field_id)? != EValueDefinitionSource::NOT_DEFINED {
+                return Ok(EValueDefinitionSource::PARENT_DEFINES);
+            }
+        }
+        
+        Ok(EValueDefinitionSource::NOT_DEFINED)
+    }
+}
+
+fn message_type_name(def_type: ProtoDefTypes) -> &'static str {
+    use ProtoDefTypes::*;
+    
+    match def_type {
+        DEF_TYPE_QUEST_MAP_NODE => ".CMsgQuestMapNodeDef",
+        DEF_TYPE_QUEST_THEME => ".CMsgQuestTheme",
+        DEF_TYPE_QUEST_MAP_REGION => ".CMsgQuestMapRegionDef",
+        DEF_TYPE_QUEST => ".CMsgQuestDef",
+        DEF_TYPE_QUEST_OBJECTIVE => ".CMsgQuestObjectiveDef",
+        DEF_TYPE_PAINTKIT_VARIABLES => ".CMsgPaintKit_Variables",
+        DEF_TYPE_PAINTKIT_OPERATION => ".CMsgPaintKit_Operation",
+        DEF_TYPE_PAINTKIT_ITEM_DEFINITION => ".CMsgPaintKit_ItemDefinition",
+        DEF_TYPE_PAINTKIT_DEFINITION => ".CMsgPaintKit_Definition",
+        DEF_TYPE_HEADER_ONLY => ".CMsgHeaderOnly",
+        DEF_TYPE_QUEST_MAP_STORE_ITEM => ".CMsgQuestMapStoreItem",
+        DEF_TYPE_QUEST_MAP_STAR_TYPE => ".CMsgQuestMapStarType",
+    }
+}
+
+fn message_descriptor<'a>(
+    descriptors: &Descriptors<'a>,
+    def: &ProtoDef,
+) -> Result<&'a DescriptorProto, ProtoDefError> {
+    descriptors.message(&def.type_name())
+        .ok_or_else(|| ProtoDefError::UnknownTypeName(def.type_name()))
+}
+
+fn header_variables(header: &CMsgProtoDefHeader) -> Variables {
+    header.get_variables()
+        .iter()
+        .map(|variable| (variable.get_name().to_string(), variable.get_value().to_string()))
+        .collect()
+}
+
+/// Adds `variables`, replacing existing variables with the same name.
+fn merge_variables<'a>(
+    merged: &mut Vec<CMsgVariableDefinition>,
+    variables: impl Iterator<Item = &'a CMsgVariableDefinition>,
+) {
+    for variable in variables {
+        match merged.iter_mut().find(|merged| merged.get_name() == variable.get_name()) {
+            Some(merged) => *merged = variable.clone(),
+            None => merged.push(variable.clone()),
+        }
+    }
+}
+
+/// A field value in the binary format.
+enum WireValue {
+    Varint(u64),
+    Fixed64(u64),
+    Fixed32(u32),
+    Bytes(Vec<u8>),
+}
+
+fn read_wire_value(
+    is: &mut CodedInputStream,
+    wire_type: WireType,
+) -> Result<Option<WireValue>, ProtobufError> {
+    Ok(match wire_type {
+        WireType::WireTypeVarint => Some(WireValue::Varint(is.read_raw_varint64()?)),
+        WireType::WireTypeFixed64 => Some(WireValue::Fixed64(is.read_raw_little_endian64()?)),
+        WireType::WireTypeFixed32 => Some(WireValue::Fixed32(is.read_raw_little_endian32()?)),
+        WireType::WireTypeLengthDelimited => Some(WireValue::Bytes(is.read_bytes()?)),
+        wire_type => {
+            is.skip_field(wire_type)?;
+            None
+        },
+    })
+}
+
+/// Finds the field at `path` in an encoded message, where each step is a field number and the
+/// index of the value for repeated fields.
+fn find_field<'a>(
+    bytes: &[u8],
+    descriptor: &'a DescriptorProto,
+    descriptors: &Descriptors<'a>,
+    path: &[CMsgFieldID_CMsgField],
+) -> Result<Option<(&'a FieldDescriptorProto, WireValue)>, ProtobufError> {
+    let (step, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return Ok(None),
+    };
+    let field = match descriptor.get_field()
+        .iter()
+        .find(|field| field.get_number() as u32 == step.get_field_number()) {
+        Some(field) => field,
+        None => return Ok(None),
+    };
+    let mut is = CodedInputStream::from_bytes(bytes);
+    let mut index = 0;
+    
+    while !is.eof()? {
+        let (number, wire_type) = is.read_tag_unpack()?;
+        
+        if number != step.get_field_number() {
+            is.skip_field(wire_type)?;
+            continue;
+        }
+        
+        let value = match read_wire_value(&mut is, wire_type)? {
+            Some(value) => value,
+            None => continue,
+        };
+        
+        if index < step.get_repeated_index() {
+            index += 1;
+            continue;
+        }
+        
+        if rest.is_empty() {
+            return Ok(Some((field, value)));
+        }
+        
+        return match (value, descriptors.message(field.get_type_name())) {
+            (WireValue::Bytes(nested_bytes), Some(nested)) => {
+                find_field(&nested_bytes, nested, descriptors, rest)
+            },
+            _ => Ok(None),
+        };
+    }
+    
+    Ok(None)
+}
+
+fn decode_field(
+    field: &FieldDescriptorProto,
+    value: WireValue,
+    variables: &Variables,
+) -> Result<Option<VarValue>, ProtoDefError> {
+    use FieldDescriptorProto_Type::*;
+    
+    Ok(Some(match (field.get_field_type(), value) {
+        (TYPE_MESSAGE, WireValue::Bytes(bytes)) if field.get_type_name() == VAR_FIELD_TYPE_NAME => {
+            let var_field: CMsgVarField = Message::parse_from_bytes(&bytes)?;
+            let var_type = match tf_proto_def_messages::exts::var_field_type.get(field.get_options()) {
+                Some(EVarFieldType::VAR_TYPE_INVALID) | None => EVarFieldType::VAR_TYPE_STRING,
+                Some(var_type) => var_type,
+            };
+            
+            resolve_var_field(&var_field, var_type, variables)?
+        },
+        (TYPE_INT32 | TYPE_ENUM, WireValue::Varint(value)) => VarValue::SInt32(value as i32),
+        (TYPE_INT64, WireValue::Varint(value)) => VarValue::SInt64(value as i64),
+        (TYPE_UINT32, WireValue::Varint(value)) => VarValue::UInt32(value as u32),
+        (TYPE_UINT64, WireValue::Varint(value)) => VarValue::UInt64(value),
+        (TYPE_SINT32, WireValue::Varint(value)) => {
+            VarValue::SInt32(((value >> 1) as i32) ^ -((value & 1) as i32))
+        },
+        (TYPE_SINT64, WireValue::Varint(value)) => {
+            VarValue::SInt64(((value >> 1) as i64) ^ -((value & 1) as i64))
+        },
+        (TYPE_BOOL, WireValue::Varint(value)) => VarValue::Bool(value != 0),
+        (TYPE_FIXED32, WireValue::Fixed32(value)) => VarValue::UInt32(value),
+        (TYPE_SFIXED32, WireValue::Fixed32(value)) => VarValue::SInt32(value as i32),
+        (TYPE_FLOAT, WireValue::Fixed32(value)) => VarValue::Float(f32::from_bits(value)),
+        (TYPE_FIXED64, WireValue::Fixed64(value)) => VarValue::UInt64(value),
+        (TYPE_SFIXED64, WireValue::Fixed64(value)) => VarValue::SInt64(value as i64),
+        (TYPE_DOUBLE, WireValue::Fixed64(value)) => VarValue::Double(f64::from_bits(value)),
+        (TYPE_STRING | TYPE_BYTES, WireValue::Bytes(bytes)) => {
+            VarValue::String(String::from_utf8_lossy(&bytes).into_owned())
+        },
+        _ => return Ok(None),
+    }))
+}
+
+fn collect_references(
+    bytes: &[u8],
+    descriptor: &DescriptorProto,
+    descriptors: &Descriptors,
+    references: &mut Vec<ProtoDefId>,
+) -> Result<(), ProtobufError> {
+    let mut is = CodedInputStream::from_bytes(bytes);
+    
+    while !is.eof()? {
+        let (number, wire_type) = is.read_tag_unpack()?;
+        let field = descriptor.get_field()
+            .iter()
+            .find(|field| {
+                field.get_number() as u32 == number &&
+                field.get_field_type() == FieldDescriptorProto_Type::TYPE_MESSAGE
+            });
+        
+        let value = read_wire_value(&mut is, wire_type)?;
+        
+        if let (Some(field), Some(WireValue::Bytes(nested_bytes))) = (field, value) {
+            match field.get_type_name() {
+                PROTO_DEF_ID_TYPE_NAME => {
+                    let id: CMsgProtoDefID = Message::parse_from_bytes(&nested_bytes)?;
+                    
+                    references.push(ProtoDefId::from(&id));
+                },
+                // Prefabs in the header are not references.
+                HEADER_TYPE_NAME => {},
+                type_name => if let Some(nested) = descriptors.message(type_name) {
+                    collect_references(&nested_bytes, nested, descriptors, references)?;
+                },
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const FIXTURE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/proto_defs.txt",
+    ));
+    
+    fn field_id(numbers: &[u32]) -> CMsgFieldID {
+        let mut field_id = CMsgFieldID::new();
+        
+        for number in numbers {
+            let mut field = CMsgFieldID_CMsgField::new();
+            
+            field.set_field_number(*number);
+            field_id.mut_field().push(field);
+        }
+        
+        field_id
+    }
+    
+    fn quest(defindex: u32, prefab: Option<u32>) -> CMsgQuestDef {
+        let mut quest = CMsgQuestDef::new();
+        
+        quest.mut_header().set_defindex(defindex);
+        
+        if let Some(prefab) = prefab {
+            let mut id = CMsgProtoDefID::new();
+            
+            id.set_defindex(prefab);
+            id.set_field_type(ProtoDefTypes::DEF_TYPE_QUEST);
+            quest.mut_header().mut_prefabs().push(id);
+        }
+        
+        quest
+    }
+    
+    #[test]
+    fn parses_text() {
+        let defs = ProtoDefs::parse(FIXTURE).unwrap();
+        let theme = defs.quest_theme(1).unwrap();
+        let objective = defs.quest_objective(10).unwrap();
+        
+        assert_eq!(defs.definitions().count(), 5);
+        assert_eq!(theme.get_header().get_name(), "theme");
+        assert_eq!(objective.get_points(), 5);
+        assert_eq!(defs.of_type(ProtoDefTypes::DEF_TYPE_QUEST).count(), 1);
+        assert!(defs.quest(100).unwrap().get_header().get_prefab_only());
+        assert_eq!(defs.quest_map_store_item(200).unwrap().get_item_name(), "Hat");
+    }
+    
+    #[test]
+    fn parses_bytes() {
+        let mut base = quest(1, None);
+        let mut derived = quest(2, Some(1));
+        let mut bytes = Vec::new();
+        
+        base.set_max_points_0(10);
+        base.set_max_points_1(20);
+        derived.set_max_points_0(30);
+        bytes.extend((ProtoDefTypes::DEF_TYPE_QUEST as u32).to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+        
+        for quest in [&base, &derived] {
+            let data = quest.write_to_bytes().unwrap();
+            
+            bytes.extend((data.len() as u32).to_le_bytes());
+            bytes.extend(data);
+        }
+        
+        let defs = ProtoDefs::from_bytes(&bytes).unwrap();
+        let quest = defs.quest(2).unwrap();
+        
+        assert_eq!(quest.get_max_points_0(), 30);
+        assert_eq!(quest.get_max_points_1(), 20);
+    }
+    
+    #[test]
+    fn prefabs_override_singular_fields_and_append_repeated_fields() {
+        let defs = ProtoDefs::parse(FIXTURE).unwrap();
+        let quest = defs.quest(101).unwrap();
+        let raw = defs.get_raw(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST, 101)).unwrap();
+        
+        assert_eq!(quest.get_max_points_0(), 150);
+        assert_eq!(quest.get_max_points_1(), 200);
+        assert_eq!(quest.get_loaner_names(), ["TF_WEAPON_SHOTGUN", "TF_WEAPON_ROCKETLAUNCHER"]);
+        assert_eq!(quest.get_theme().get_defindex(), 1);
+        assert_eq!(quest.get_header().get_name(), "quest");
+        assert_eq!(quest.get_header().get_tags(), ["base", "easy"]);
+        assert_eq!(
+            defs.variables(ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST, 101)).get("reward"),
+            Some(&"Hat".to_string()),
+        );
+        assert_eq!(raw.header().get_tags(), ["easy"]);
+    }
+    
+    #[test]
+    fn prefab_cycle_is_an_error() {
+        let text = r#"
+            DEF_TYPE_QUEST {
+                header { defindex: 1 prefabs { defindex: 2 type: DEF_TYPE_QUEST } }
+            }
+            DEF_TYPE_QUEST {
+                header { defindex: 2 prefabs { defindex: 1 type: DEF_TYPE_QUEST } }
+            }
+        "#;
+        
+        assert!(matches!(ProtoDefs::parse(text), Err(ProtoDefError::PrefabCycle(_))));
+    }
+    
+    #[test]
+    fn field_value_substitutes_variables() {
+        let defs = ProtoDefs::parse(FIXTURE).unwrap();
+        let variable = ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_MAP_STORE_ITEM, 200);
+        let literal = ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_MAP_STORE_ITEM, 201);
+        let quest = ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST, 101);
+        
+        assert_eq!(defs.field_value(variable, &field_id(&[3])).unwrap(), Some(VarValue::SInt32(25)));
+        assert_eq!(defs.field_value(literal, &field_id(&[3])).unwrap(), Some(VarValue::SInt32(-3)));
+        assert_eq!(defs.field_value(literal, &field_id(&[2])).unwrap(), None);
+        assert_eq!(defs.field_value(quest, &field_id(&[3])).unwrap(), Some(VarValue::UInt32(200)));
+        assert_eq!(
+            defs.field_source(variable, &field_id(&[3])).unwrap(),
+            EValueDefinitionSource::VARIABLE_DEFINES,
+        );
+        assert_eq!(
+            defs.field_source(quest, &field_id(&[3])).unwrap(),
+            EValueDefinitionSource::PARENT_DEFINES,
+        );
+    }
+    
+    #[test]
+    fn finds_dangling_references() {
+        let defs = ProtoDefs::parse(FIXTURE).unwrap();
+        let quest = ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST, 101);
+        let missing = ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_OBJECTIVE, 11);
+        
+        assert_eq!(defs.references(quest).unwrap(), [
+            ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_THEME, 1),
+            ProtoDefId::new(ProtoDefTypes::DEF_TYPE_QUEST_OBJECTIVE, 10),
+            missing,
+        ]);
+        assert_eq!(defs.dangling_references().unwrap(), [(quest, missing)]);
+    }
+}
\ No newline at end of file
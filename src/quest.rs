@@ -0,0 +1,179 @@
+//! Views of the quest (contract) shared objects and replies to quest map actions.
+
+use tf2_protobuf::tf_gcmessages::{
+    CSOQuest,
+    CSOQuestMapNode,
+    CSOQuestMapRewardPurchase,
+    CMsgGCQuestResponse,
+};
+
+/// A contract the client owns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quest {
+    pub quest_id: u64,
+    /// The `CMsgQuestDef` defindex.
+    pub def_index: u32,
+    pub active: bool,
+    /// Points earned towards each of the three stars.
+    pub points: [u32; 3],
+    /// The map node the contract was selected from.
+    pub node_def_index: Option<u32>,
+    pub map_cycle: u32,
+}
+
+impl From<&CSOQuest> for Quest {
+    fn from(quest: &CSOQuest) -> Self {
+        Self {
+            quest_id: quest.get_quest_id(),
+            def_index: quest.get_defindex(),
+            active: quest.get_active(),
+            points: [
+                quest.get_points_0(),
+                quest.get_points_1(),
+                quest.get_points_2(),
+            ],
+            node_def_index: Some(quest.get_quest_map_node_source_id())
+                .filter(|def_index| *def_index != 0),
+            map_cycle: quest.get_map_cycle(),
+        }
+    }
+}
+
+/// An unlocked node on the quest map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuestMapNode {
+    /// The `CMsgQuestMapNodeDef` defindex.
+    pub def_index: u32,
+    pub node_id: u32,
+    pub stars_earned: [bool; 3],
+    pub loot_claimed: bool,
+    /// The contract chosen on this node, if any.
+    pub selected_quest_def_index: Option<u32>,
+    pub map_cycle: u32,
+}
+
+impl QuestMapNode {
+    
+    pub fn star_count(&self) -> usize {
+        self.stars_earned.iter().filter(|earned| **earned).count()
+    }
+}
+
+impl From<&CSOQuestMapNode> for QuestMapNode {
+    fn from(node: &CSOQuestMapNode) -> Self {
+        Self {
+            def_index: node.get_defindex(),
+            node_id: node.get_node_id(),
+            stars_earned: [
+                node.get_star_0_earned(),
+                node.get_star_1_earned(),
+                node.get_star_2_earned(),
+            ],
+            loot_claimed: node.get_loot_claimed(),
+            selected_quest_def_index: Some(node.get_selected_quest_def())
+                .filter(|def_index| *def_index != 0),
+            map_cycle: node.get_map_cycle(),
+        }
+    }
+}
+
+/// A reward bought from the quest map store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuestRewardPurchase {
+    /// The store item defindex.
+    pub def_index: u32,
+    pub count: u32,
+    pub map_cycle: u32,
+    pub purchase_id: u32,
+}
+
+impl From<&CSOQuestMapRewardPurchase> for QuestRewardPurchase {
+    fn from(purchase: &CSOQuestMapRewardPurchase) -> Self {
+        Self {
+            def_index: purchase.get_defindex(),
+            count: purchase.get_count(),
+            map_cycle: purchase.get_map_cycle(),
+            purchase_id: purchase.get_purchase_id(),
+        }
+    }
+}
+
+/// A quest map action the GC replied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuestAction {
+    UnlockNode,
+    PurchaseReward,
+    TurnIn,
+}
+
+/// The GC's reply to a quest map action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuestResponse {
+    pub action: QuestAction,
+    pub success: bool,
+}
+
+impl QuestResponse {
+    
+    pub fn new(action: QuestAction, response: &CMsgGCQuestResponse) -> Self {
+        Self {
+            action,
+            success: response.get_success(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn reads_quest() {
+        let mut quest = CSOQuest::new();
+        
+        quest.set_quest_id(1);
+        quest.set_defindex(20);
+        quest.set_points_0(100);
+        quest.set_points_2(5);
+        
+        let read = Quest::from(&quest);
+        
+        assert_eq!(read.points, [100, 0, 5]);
+        assert_eq!(read.node_def_index, None);
+        
+        quest.set_quest_map_node_source_id(3);
+        
+        assert_eq!(Quest::from(&quest).node_def_index, Some(3));
+    }
+    
+    #[test]
+    fn reads_map_node() {
+        let mut node = CSOQuestMapNode::new();
+        
+        node.set_defindex(3);
+        node.set_star_0_earned(true);
+        node.set_star_2_earned(true);
+        
+        let read = QuestMapNode::from(&node);
+        
+        assert_eq!(read.stars_earned, [true, false, true]);
+        assert_eq!(read.star_count(), 2);
+        assert_eq!(read.selected_quest_def_index, None);
+        
+        node.set_selected_quest_def(20);
+        
+        assert_eq!(QuestMapNode::from(&node).selected_quest_def_index, Some(20));
+    }
+    
+    #[test]
+    fn reads_response() {
+        let mut response = CMsgGCQuestResponse::new();
+        
+        response.set_success(true);
+        
+        assert_eq!(QuestResponse::new(QuestAction::TurnIn, &response), QuestResponse {
+            action: QuestAction::TurnIn,
+            success: true,
+        });
+    }
+}
\ No newline at end of file
@@ -0,0 +1,218 @@
+use crate::request::CraftItem;
+use crate::error::Error;
+
+pub const SCRAP_METAL: u32 = 5000;
+pub const RECLAIMED_METAL: u32 = 5001;
+pub const REFINED_METAL: u32 = 5002;
+
+/// The blueprint index used for custom recipes. The GC matches the inputs against every known 
+/// blueprint.
+pub const RECIPE_CUSTOM: i16 = -2;
+
+/// An input slot of a recipe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecipeInput {
+    /// `count` items with the given definition index.
+    DefIndex {
+        def_index: u32,
+        count: usize,
+    },
+    /// `count` items that cannot be checked without the item schema, e.g. weapons of the same 
+    /// class.
+    Any {
+        count: usize,
+    },
+}
+
+impl RecipeInput {
+    
+    pub fn count(&self) -> usize {
+        match self {
+            Self::DefIndex { count, .. } => *count,
+            Self::Any { count } => *count,
+        }
+    }
+}
+
+/// Common TF2 crafting blueprints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Recipe {
+    /// Any combination of items, matched by the GC.
+    Custom,
+    /// Two weapons of the same class into one scrap metal.
+    SmeltClassWeapons,
+    /// Three scrap metal into one reclaimed metal.
+    CombineScrap,
+    /// Three reclaimed metal into one refined metal.
+    CombineReclaimed,
+    /// One reclaimed metal into three scrap metal.
+    SmeltReclaimed,
+    /// One refined metal into three reclaimed metal.
+    SmeltRefined,
+    /// Three weapons of the same class into a class token.
+    FabricateClassToken,
+    /// Three weapons of the same slot into a slot token.
+    FabricateSlotToken,
+    /// Three refined metal into a random hat.
+    FabricateHeadgear,
+    /// A class token, a slot token and one scrap metal into a random weapon.
+    RebuildClassWeapons,
+}
+
+impl Recipe {
+    
+    /// The blueprint index sent to the GC. Blueprints without a dedicated index are sent as 
+    /// [`RECIPE_CUSTOM`].
+    pub fn id(&self) -> i16 {
+        match self {
+            Self::SmeltClassWeapons => 3,
+            Self::CombineScrap => 4,
+            Self::CombineReclaimed => 5,
+            Self::SmeltReclaimed => 22,
+            Self::SmeltRefined => 23,
+            Self::Custom |
+            Self::FabricateClassToken |
+            Self::FabricateSlotToken |
+            Self::FabricateHeadgear |
+            Self::RebuildClassWeapons => RECIPE_CUSTOM,
+        }
+    }
+    
+    /// The inputs consumed by this recipe. Empty for [`Recipe::Custom`].
+    pub fn inputs(&self) -> &'static [RecipeInput] {
+        match self {
+            Self::Custom => &[],
+            Self::SmeltClassWeapons => &[
+                RecipeInput::Any { count: 2 },
+            ],
+            Self::CombineScrap => &[
+                RecipeInput::DefIndex { def_index: SCRAP_METAL, count: 3 },
+            ],
+            Self::CombineReclaimed => &[
+                RecipeInput::DefIndex { def_index: RECLAIMED_METAL, count: 3 },
+            ],
+            Self::SmeltReclaimed => &[
+                RecipeInput::DefIndex { def_index: RECLAIMED_METAL, count: 1 },
+            ],
+            Self::SmeltRefined => &[
+                RecipeInput::DefIndex { def_index: REFINED_METAL, count: 1 },
+            ],
+            Self::FabricateClassToken |
+            Self::FabricateSlotToken => &[
+                RecipeInput::Any { count: 3 },
+            ],
+            Self::FabricateHeadgear => &[
+                RecipeInput::DefIndex { def_index: REFINED_METAL, count: 3 },
+            ],
+            Self::RebuildClassWeapons => &[
+                RecipeInput::DefIndex { def_index: SCRAP_METAL, count: 1 },
+                RecipeInput::Any { count: 2 },
+            ],
+        }
+    }
+    
+    /// Checks the given items against the inputs of this recipe. Inputs which need the item 
+    /// schema are only checked by count.
+    pub fn validate(&self, items: &[CraftItem]) -> Result<(), Error> {
+        let inputs = self.inputs();
+        
+        if inputs.is_empty() {
+            return Ok(());
+        }
+        
+        let expected = inputs
+            .iter()
+            .map(|input| input.count())
+            .sum::<usize>();
+        
+        if items.len() != expected {
+            return Err(Error::InvalidItemCount {
+                expected,
+                got: items.len(),
+            });
+        }
+        
+        for (i, item) in items.iter().enumerate() {
+            if items[..i].iter().any(|other| other.item_id == item.item_id) {
+                return Err(Error::DuplicateItem(item.item_id));
+            }
+        }
+        
+        for input in inputs {
+            if let RecipeInput::DefIndex { def_index, count } = input {
+                let got = items
+                    .iter()
+                    .filter(|item| item.def_index == *def_index)
+                    .count();
+                
+                if got < *count {
+                    return Err(Error::InvalidRecipeInput {
+                        def_index: *def_index,
+                        expected: *count,
+                        got,
+                    });
+                }
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn items(def_indexes: &[u32]) -> Vec<CraftItem> {
+        def_indexes
+            .iter()
+            .enumerate()
+            .map(|(i, def_index)| CraftItem {
+                item_id: i as u64 + 1,
+                def_index: *def_index,
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn accepts_matching_inputs() {
+        assert!(Recipe::CombineScrap.validate(&items(&[SCRAP_METAL; 3])).is_ok());
+        assert!(Recipe::SmeltClassWeapons.validate(&items(&[13, 200])).is_ok());
+        assert!(Recipe::RebuildClassWeapons.validate(&items(&[5003, SCRAP_METAL, 5010])).is_ok());
+        assert!(Recipe::Custom.validate(&items(&[1, 2, 3, 4])).is_ok());
+    }
+    
+    #[test]
+    fn rejects_wrong_count() {
+        assert!(matches!(
+            Recipe::CombineReclaimed.validate(&items(&[RECLAIMED_METAL; 2])),
+            Err(Error::InvalidItemCount { expected: 3, got: 2 }),
+        ));
+    }
+    
+    #[test]
+    fn rejects_duplicate_items() {
+        let mut items = items(&[SCRAP_METAL; 3]);
+        
+        items[2].item_id = items[0].item_id;
+        
+        assert!(matches!(
+            Recipe::CombineScrap.validate(&items),
+            Err(Error::DuplicateItem(1)),
+        ));
+    }
+    
+    #[test]
+    fn rejects_wrong_def_index() {
+        assert!(matches!(
+            Recipe::FabricateHeadgear.validate(&items(&[REFINED_METAL, REFINED_METAL, SCRAP_METAL])),
+            Err(Error::InvalidRecipeInput { def_index: REFINED_METAL, expected: 3, got: 2 }),
+        ));
+    }
+    
+    #[test]
+    fn dedicated_blueprints_have_ids() {
+        assert_eq!(Recipe::SmeltRefined.id(), 23);
+        assert_eq!(Recipe::FabricateClassToken.id(), RECIPE_CUSTOM);
+    }
+}
@@ -1,4 +1,6 @@
 
+use tf2_protobuf::base_gcmessages::CSOEconItem;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ItemCustomization {
     GiftedBy,
@@ -9,6 +11,16 @@ pub enum ItemCustomization {
     Festivizer,
 }
 
+/// How the GC should sort the backpack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortType {
+    Rarity = 1,
+    Type = 2,
+    Class = 3,
+    Slot = 4,
+    Date = 5,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct RecipeComponent {
     pub subject_item_id: u64,
@@ -19,4 +31,20 @@ pub struct RecipeComponent {
 pub struct SetItemPosition {
     pub item_id: u64,
     pub position: u32,
+}
+
+/// An item given as input to a craft.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CraftItem {
+    pub item_id: u64,
+    pub def_index: u32,
+}
+
+impl From<&CSOEconItem> for CraftItem {
+    fn from(item: &CSOEconItem) -> Self {
+        Self {
+            item_id: item.get_id(),
+            def_index: item.get_def_index(),
+        }
+    }
 }
\ No newline at end of file
@@ -1,7 +1,9 @@
 use bytes::BytesMut;
 use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::Cursor;
+use protobuf::Message;
+use std::io::{Cursor, ErrorKind};
 use std::fmt::Debug;
+use tf2_protobuf::econ_gcmessages::CMsgCraftingResponse;
 
 pub type GCBytesMessageError = std::io::Error;
 
@@ -34,4 +36,33 @@ impl GCResponseMessage for CraftResponse {
             assetids,
         })
     }
+}
+
+/// Parses a protobuf message body, mapping decode failures to [`GCBytesMessageError`].
+pub(crate) fn parse_proto<Msg: Message>(payload: &[u8]) -> Result<Msg, GCBytesMessageError> {
+    Msg::parse_from_bytes(payload)
+        .map_err(|error| GCBytesMessageError::new(ErrorKind::InvalidData, error))
+}
+
+/// Response to collection upgrades, Halloween offerings and stat clock crafts.
+#[derive(Debug)]
+pub struct CraftingResponse {
+    pub assetids: Vec<u64>,
+}
+
+impl GCResponseMessage for CraftingResponse {
+    
+    fn from_payload(payload: BytesMut) -> Result<Self, GCBytesMessageError> {
+        let message: CMsgCraftingResponse = parse_proto(&payload)?;
+        
+        Ok(Self::from(&message))
+    }
+}
+
+impl From<&CMsgCraftingResponse> for CraftingResponse {
+    fn from(message: &CMsgCraftingResponse) -> Self {
+        Self {
+            assetids: message.get_item_ids().to_vec(),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,564 @@
+//! The item schema, loaded from `items_game.txt`.
+
+use std::collections::HashMap;
+use std::path::Path;
+use crate::vdf::{self, KeyValues};
+use crate::loadout::{TFClass, LoadoutSlot};
+
+#[derive(thiserror::Error, Debug)]
+pub enum SchemaError {
+    #[error("{}", .0)]
+    IO(#[from] std::io::Error),
+    #[error("{}", .0)]
+    Parse(#[from] vdf::ParseError),
+    #[error("Missing \"{}\" section", .0)]
+    MissingSection(&'static str),
+    #[error("Too many nested includes at \"{}\"", .0)]
+    IncludeDepth(String),
+}
+
+/// An item definition from the `items` section, with its prefabs applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemDefinition {
+    pub def_index: u32,
+    pub name: String,
+    pub item_class: Option<String>,
+    /// The localization token of the item's display name, e.g. `#TF_Unique_Prepare_Cap`.
+    pub item_name: Option<String>,
+    pub item_type_name: Option<String>,
+    pub item_slot: Option<String>,
+    /// The quality the item is created with.
+    pub item_quality: Option<u32>,
+    /// Whether "The" is placed before the item's name.
+    pub propername: bool,
+    pub craft_class: Option<String>,
+    pub craft_material_type: Option<String>,
+    pub image_inventory: Option<String>,
+    pub min_ilevel: Option<u32>,
+    pub max_ilevel: Option<u32>,
+    pub used_by_classes: Vec<TFClass>,
+    pub item_set: Option<String>,
+    /// Attributes applied to the item, by attribute name.
+    pub attributes: HashMap<String, String>,
+    /// The full definition including keys not covered by the fields above.
+    pub kv: KeyValues,
+}
+
+impl ItemDefinition {
+    
+    fn from_kv(def_index: u32, kv: KeyValues, qualities: &HashMap<String, u32>) -> Self {
+        let get_string = |key: &str| kv.get_str(key).map(String::from);
+        let mut attributes = HashMap::new();
+        
+        if let Some(static_attrs) = kv.get_object("static_attrs") {
+            for (name, value) in static_attrs.iter() {
+                if let Some(value) = value.as_str() {
+                    attributes.insert(name.to_string(), value.to_string());
+                }
+            }
+        }
+        
+        if let Some(item_attributes) = kv.get_object("attributes") {
+            for (name, attribute) in item_attributes.objects() {
+                if let Some(value) = attribute.get_str("value") {
+                    attributes.insert(name.to_string(), value.to_string());
+                }
+            }
+        }
+        
+        let used_by_classes = kv.get_object("used_by_classes")
+            .map(|classes| {
+                classes
+                    .iter()
+                    .filter_map(|(name, _value)| TFClass::from_name(name))
+                    .collect()
+            })
+            .unwrap_or_default();
+        
+        Self {
+            def_index,
+            name: get_string("name").unwrap_or_default(),
+            item_class: get_string("item_class"),
+            item_name: get_string("item_name"),
+            item_type_name: get_string("item_type_name"),
+            item_slot: get_string("item_slot"),
+            item_quality: kv.get_str("item_quality")
+                .and_then(|name| qualities.get(&name.to_lowercase()).copied()),
+            propername: kv.get_bool("propername"),
+            craft_class: get_string("craft_class"),
+            craft_material_type: get_string("craft_material_type"),
+            image_inventory: get_string("image_inventory"),
+            min_ilevel: kv.get_parsed("min_ilevel"),
+            max_ilevel: kv.get_parsed("max_ilevel"),
+            used_by_classes,
+            item_set: get_string("item_set"),
+            attributes,
+            kv,
+        }
+    }
+    
+    /// The loadout slot the item is equipped in, if it is equippable.
+    pub fn loadout_slot(&self) -> Option<LoadoutSlot> {
+        LoadoutSlot::from_name(self.item_slot.as_deref()?)
+    }
+}
+
+/// An attribute definition from the `attributes` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeDefinition {
+    pub def_index: u32,
+    pub name: String,
+    pub attribute_class: Option<String>,
+    pub description_string: Option<String>,
+    pub description_format: Option<String>,
+    pub effect_type: Option<String>,
+    pub attribute_type: Option<String>,
+    pub hidden: bool,
+    /// Whether the value is an integer rather than a float bit-pattern.
+    pub stored_as_integer: bool,
+}
+
+impl AttributeDefinition {
+    
+    fn from_kv(def_index: u32, kv: &KeyValues) -> Self {
+        let get_string = |key: &str| kv.get_str(key).map(String::from);
+        
+        Self {
+            def_index,
+            name: get_string("name").unwrap_or_default(),
+            attribute_class: get_string("attribute_class"),
+            description_string: get_string("description_string"),
+            description_format: get_string("description_format"),
+            effect_type: get_string("effect_type"),
+            attribute_type: get_string("attribute_type"),
+            hidden: kv.get_bool("hidden"),
+            stored_as_integer: kv.get_bool("stored_as_integer"),
+        }
+    }
+}
+
+/// A particle effect from the `attribute_controlled_attached_particles` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Particle {
+    pub id: u32,
+    pub system: String,
+    /// The English name, if the schema includes one.
+    pub name: Option<String>,
+    /// The group the particle is listed under, e.g. `cosmetic_unusual_effects`.
+    pub group: Option<String>,
+}
+
+/// A war paint, found through the `paintkit_proto_def_index` attribute of its war paint item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaintKit {
+    pub id: u32,
+    /// The definition index of the war paint item which applies this paint kit.
+    pub item_def_index: u32,
+    pub item_name: Option<String>,
+}
+
+/// A crafting blueprint from the `recipes` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeDefinition {
+    pub id: u32,
+    pub name: Option<String>,
+    pub desc_inputs: Option<String>,
+    pub desc_outputs: Option<String>,
+    pub premium_only: bool,
+    pub disabled: bool,
+}
+
+/// An item set from the `item_sets` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemSet {
+    pub key: String,
+    pub name: Option<String>,
+    /// The names of the items in the set.
+    pub items: Vec<String>,
+    /// The bonus attributes applied when wearing the whole set, by attribute name.
+    pub attributes: HashMap<String, String>,
+}
+
+/// An indexed `items_game.txt`.
+#[derive(Debug, Default, Clone)]
+pub struct Schema {
+    pub items: HashMap<u32, ItemDefinition>,
+    pub attributes: HashMap<u32, AttributeDefinition>,
+    /// Quality names by value, e.g. `6` is `unique`.
+    pub qualities: HashMap<u32, String>,
+    pub particles: HashMap<u32, Particle>,
+    pub paint_kits: HashMap<u32, PaintKit>,
+    pub recipes: HashMap<u32, RecipeDefinition>,
+    pub item_sets: HashMap<String, ItemSet>,
+    items_by_name: HashMap<String, u32>,
+    attributes_by_name: HashMap<String, u32>,
+}
+
+impl Schema {
+    
+    /// Reads `items_game.txt`, resolving `#base` and `#include` directives relative to its
+    /// directory.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, SchemaError> {
+        let root = read_kv(path.as_ref(), 0)?;
+        let items_game = root.get_object("items_game")
+            .ok_or(SchemaError::MissingSection("items_game"))?;
+        
+        Ok(Self::from_kv(items_game))
+    }
+    
+    /// Parses the contents of `items_game.txt`.
+    pub fn parse(text: &str) -> Result<Self, SchemaError> {
+        let root = KeyValues::parse(text)?;
+        let items_game = root.get_object("items_game")
+            .ok_or(SchemaError::MissingSection("items_game"))?;
+        
+        Ok(Self::from_kv(items_game))
+    }
+    
+    /// Builds the schema from the parsed `items_game` object.
+    pub fn from_kv(items_game: &KeyValues) -> Self {
+        let mut schema = Self::default();
+        let mut quality_values = HashMap::new();
+        
+        if let Some(qualities) = items_game.get_object("qualities") {
+            for (name, quality) in qualities.objects() {
+                if let Some(value) = quality.get_parsed::<u32>("value") {
+                    quality_values.insert(name.to_lowercase(), value);
+                    schema.qualities.insert(value, name.to_string());
+                }
+            }
+        }
+        
+        let empty = KeyValues::new();
+        let prefabs = items_game.get_object("prefabs").unwrap_or(&empty);
+        
+        if let Some(items) = items_game.get_object("items") {
+            for (key, kv) in items.objects() {
+                let def_index = match key.parse::<u32>() {
+                    Ok(def_index) => def_index,
+                    // skips "default"
+                    Err(_) => continue,
+                };
+                let mut kv = kv.clone();
+                
+                apply_prefabs(&mut kv, prefabs, 0);
+                
+                let item = ItemDefinition::from_kv(def_index, kv, &quality_values);
+                
+                schema.items_by_name.insert(item.name.to_lowercase(), def_index);
+                schema.items.insert(def_index, item);
+            }
+        }
+        
+        for item in schema.items.values() {
+            if let Some(id) = item.attributes
+                .get("paintkit_proto_def_index")
+                .and_then(|value| value.parse::<f64>().ok()) {
+                schema.paint_kits.insert(id as u32, PaintKit {
+                    id: id as u32,
+                    item_def_index: item.def_index,
+                    item_name: item.item_name.clone(),
+                });
+            }
+        }
+        
+        if let Some(attributes) = items_game.get_object("attributes") {
+            for (key, kv) in attributes.objects() {
+                if let Ok(def_index) = key.parse::<u32>() {
+                    let attribute = AttributeDefinition::from_kv(def_index, kv);
+                    
+                    schema.attributes_by_name.insert(attribute.name.to_lowercase(), def_index);
+                    schema.attributes.insert(def_index, attribute);
+                }
+            }
+        }
+        
+        if let Some(particles) = items_game.get_object("attribute_controlled_attached_particles") {
+            for (group, particles) in particles.objects() {
+                for (key, kv) in particles.objects() {
+                    let id = match kv.get_parsed::<u32>("id").or_else(|| key.parse().ok()) {
+                        Some(id) => id,
+                        None => continue,
+                    };
+                    
+                    schema.particles.insert(id, Particle {
+                        id,
+                        system: kv.get_str("system").unwrap_or_default().to_string(),
+                        name: kv.get_str("name").map(String::from),
+                        group: Some(group.to_string()),
+                    });
+                }
+            }
+        }
+        
+        if let Some(recipes) = items_game.get_object("recipes") {
+            for (key, kv) in recipes.objects() {
+                if let Ok(id) = key.parse::<u32>() {
+                    schema.recipes.insert(id, RecipeDefinition {
+                        id,
+                        name: kv.get_str("name").map(String::from),
+                        desc_inputs: kv.get_str("desc_inputs").map(String::from),
+                        desc_outputs: kv.get_str("desc_outputs").map(String::from),
+                        premium_only: kv.get_bool("premium_only"),
+                        disabled: kv.get_bool("disabled"),
+                    });
+                }
+            }
+        }
+        
+        if let Some(item_sets) = items_game.get_object("item_sets") {
+            for (key, kv) in item_sets.objects() {
+                let items = kv.get_object("items")
+                    .map(|items| {
+                        items
+                            .iter()
+                            .map(|(name, _value)| name.to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let attributes = kv.get_object("attributes")
+                    .map(|attributes| {
+                        attributes
+                            .objects()
+                            .filter_map(|(name, attribute)| {
+                                attribute
+                                    .get_str("value")
+                                    .map(|value| (name.to_string(), value.to_string()))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                
+                schema.item_sets.insert(key.to_string(), ItemSet {
+                    key: key.to_string(),
+                    name: kv.get_str("name").map(String::from),
+                    items,
+                    attributes,
+                });
+            }
+        }
+        
+        schema
+    }
+    
+    pub fn item(&self, def_index: u32) -> Option<&ItemDefinition> {
+        self.items.get(&def_index)
+    }
+    
+    /// Looks up an item by its `name` key, ignoring case.
+    pub fn item_by_name(&self, name: &str) -> Option<&ItemDefinition> {
+        self.items_by_name
+            .get(&name.to_lowercase())
+            .and_then(|def_index| self.items.get(def_index))
+    }
+    
+    pub fn attribute(&self, def_index: u32) -> Option<&AttributeDefinition> {
+        self.attributes.get(&def_index)
+    }
+    
+    /// Looks up an attribute by its `name` key, ignoring case.
+    pub fn attribute_by_name(&self, name: &str) -> Option<&AttributeDefinition> {
+        self.attributes_by_name
+            .get(&name.to_lowercase())
+            .and_then(|def_index| self.attributes.get(def_index))
+    }
+    
+    pub fn quality_name(&self, quality: u32) -> Option<&str> {
+        self.qualities.get(&quality).map(String::as_str)
+    }
+    
+    pub fn particle(&self, id: u32) -> Option<&Particle> {
+        self.particles.get(&id)
+    }
+    
+    pub fn paint_kit(&self, id: u32) -> Option<&PaintKit> {
+        self.paint_kits.get(&id)
+    }
+}
+
+/// Included files can themselves include files; this guards against cycles.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Reads a KeyValues file along with the files named by its `#base` and `#include` directives.
+fn read_kv(path: &Path, depth: usize) -> Result<KeyValues, SchemaError> {
+    let mut kv = KeyValues::parse(&std::fs::read_to_string(path)?)?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    
+    kv.resolve_includes(|name| {
+        if depth >= MAX_INCLUDE_DEPTH {
+            return Err(SchemaError::IncludeDepth(name.to_string()));
+        }
+        
+        read_kv(&directory.join(name), depth + 1)
+    })?;
+    
+    Ok(kv)
+}
+
+/// Prefabs can themselves use prefabs; this guards against cycles.
+const MAX_PREFAB_DEPTH: usize = 16;
+
+/// Fills in missing keys from the prefabs named by the `prefab` key, which may list several
+/// prefabs separated by spaces.
+fn apply_prefabs(kv: &mut KeyValues, prefabs: &KeyValues, depth: usize) {
+    if depth >= MAX_PREFAB_DEPTH {
+        return;
+    }
+    
+    let names = match kv.get_str("prefab") {
+        Some(names) => names.to_string(),
+        None => return,
+    };
+    
+    for name in names.split_whitespace() {
+        if let Some(prefab) = prefabs.get_object(name) {
+            let mut prefab = prefab.clone();
+            
+            apply_prefabs(&mut prefab, prefabs, depth + 1);
+            kv.merge_missing(&prefab);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn schema() -> Schema {
+        Schema::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/items_game.txt"))
+            .unwrap()
+    }
+    
+    #[test]
+    fn resolves_base_file() {
+        let schema = schema();
+        
+        // the including file takes precedence over its base
+        assert_eq!(schema.quality_name(6), Some("unique"));
+        assert_eq!(schema.quality_name(99), None);
+        assert_eq!(schema.quality_name(3), Some("vintage"));
+    }
+    
+    #[test]
+    fn applies_prefabs() {
+        let schema = schema();
+        let hat = schema.item_by_name("bill's hat").unwrap();
+        
+        assert_eq!(hat.def_index, 126);
+        // keys on the item override its prefabs
+        assert_eq!(hat.item_slot.as_deref(), Some("misc"));
+        assert_eq!(hat.item_class.as_deref(), Some("tf_wearable"));
+        assert_eq!(hat.craft_class.as_deref(), Some("hat"));
+        assert_eq!(hat.item_quality, Some(6));
+        assert_eq!(hat.min_ilevel, Some(1));
+        assert_eq!(hat.max_ilevel, Some(100));
+        // from the second listed prefab
+        assert_eq!(hat.attributes.get("always tradable").map(String::as_str), Some("1"));
+        assert_eq!(hat.used_by_classes, vec![TFClass::Scout, TFClass::Heavy]);
+        assert!(!hat.propername);
+        assert_eq!(
+            hat.image_inventory.as_deref(),
+            Some("backpack/player/items/all_class/all_domination_b_medium"),
+        );
+    }
+    
+    #[test]
+    fn applies_nested_prefabs() {
+        let schema = schema();
+        let shotgun = schema.item(10).unwrap();
+        
+        assert_eq!(shotgun.item_class.as_deref(), Some("tf_weapon_shotgun"));
+        assert_eq!(shotgun.loadout_slot(), Some(LoadoutSlot::Secondary));
+        assert_eq!(shotgun.craft_class.as_deref(), Some("weapon"));
+        assert_eq!(shotgun.item_quality, Some(0));
+        assert_eq!(shotgun.used_by_classes, vec![TFClass::Soldier]);
+        assert_eq!(shotgun.item_name.as_deref(), Some("#TF_Weapon_Shotgun"));
+        // the item's own prefab key is kept rather than replaced by the nested one
+        assert_eq!(shotgun.kv.get_str("prefab"), Some("weapon_shotgun"));
+        // nothing is inherited from unrelated prefabs
+        assert_eq!(shotgun.min_ilevel, None);
+        assert!(shotgun.attributes.is_empty());
+    }
+    
+    #[test]
+    fn stops_at_prefab_cycles() {
+        let mut kv = KeyValues::parse(r#""prefab" "a""#).unwrap();
+        let prefabs = KeyValues::parse(r#""a" { "prefab" "b" "x" "1" } "b" { "prefab" "a" }"#)
+            .unwrap();
+        
+        apply_prefabs(&mut kv, &prefabs, 0);
+        
+        assert_eq!(kv.get_str("x"), Some("1"));
+    }
+    
+    #[test]
+    fn parses_attributes() {
+        let schema = schema();
+        let attribute = schema.attribute_by_name("Set Item Tint RGB").unwrap();
+        
+        assert_eq!(attribute.def_index, 142);
+        assert_eq!(attribute.attribute_class.as_deref(), Some("set_item_tint_rgb"));
+        assert!(!attribute.stored_as_integer);
+    }
+    
+    #[test]
+    fn indexes_particles() {
+        let schema = schema();
+        let flames = schema.particle(13).unwrap();
+        let fireworks = schema.particle(3001).unwrap();
+        
+        assert_eq!(schema.particles.len(), 2);
+        assert_eq!(flames.system, "superrare_burning2");
+        assert_eq!(flames.name.as_deref(), Some("Burning Flames"));
+        assert_eq!(flames.group.as_deref(), Some("cosmetic_unusual_effects"));
+        assert_eq!(fireworks.name, None);
+        assert_eq!(fireworks.group.as_deref(), Some("taunt_unusual_effects"));
+    }
+    
+    #[test]
+    fn indexes_paint_kits() {
+        let schema = schema();
+        let paint_kit = schema.paint_kit(102).unwrap();
+        
+        assert_eq!(schema.paint_kits.len(), 1);
+        assert_eq!(paint_kit.item_def_index, 16102);
+        assert_eq!(paint_kit.item_name.as_deref(), Some("#TF_Paintkit_Warhawk"));
+    }
+    
+    #[test]
+    fn indexes_recipes() {
+        let schema = schema();
+        let smelt = &schema.recipes[&3];
+        let rebuild = &schema.recipes[&22];
+        
+        assert_eq!(schema.recipes.len(), 2);
+        assert_eq!(smelt.name.as_deref(), Some("#RT_SmeltClassWeapons"));
+        assert_eq!(smelt.desc_inputs.as_deref(), Some("#RDI_AB"));
+        assert_eq!(smelt.desc_outputs.as_deref(), Some("#RDO_AB"));
+        assert!(!smelt.premium_only);
+        assert!(!smelt.disabled);
+        assert!(rebuild.disabled);
+    }
+    
+    #[test]
+    fn indexes_item_sets() {
+        let schema = schema();
+        let set = &schema.item_sets["polycount_soldier"];
+        
+        assert_eq!(set.key, "polycount_soldier");
+        assert_eq!(set.name.as_deref(), Some("#TF_Set_Polycount_Soldier"));
+        assert_eq!(set.items, vec!["The Black Box", "The Grenadier's Softcap"]);
+        assert_eq!(
+            set.attributes.get("dmg taken from crit reduced").map(String::as_str),
+            Some("0.95"),
+        );
+    }
+    
+    #[test]
+    fn requires_items_game() {
+        assert!(matches!(
+            Schema::parse(r#""other" {}"#),
+            Err(SchemaError::MissingSection("items_game")),
+        ));
+    }
+}
\ No newline at end of file
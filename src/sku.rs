@@ -0,0 +1,479 @@
+//! Conversion of items to and from SKU strings, e.g. `5021;6;uncraftable` or
+//! `15013;15;w3;pk102`.
+
+use std::fmt;
+use std::str::FromStr;
+use protobuf::Message;
+use tf2_protobuf::{
+    base_gcmessages::{CSOEconItem, CSOEconItemAttribute},
+    tf_gcmessages::CAttribute_DynamicRecipeComponent,
+};
+use crate::attribute::DYNAMIC_RECIPE_FLAG_IS_OUTPUT;
+use crate::item::{
+    Item,
+    KillstreakTier,
+    Quality,
+    Wear,
+    ATTRIBUTE_AUSTRALIUM,
+    ATTRIBUTE_CRATE_SERIES,
+    ATTRIBUTE_FESTIVIZED,
+    ATTRIBUTE_KILL_EATER,
+    ATTRIBUTE_KILL_EATER_SCORE_TYPE,
+    ATTRIBUTE_KILLSTREAK_TIER,
+    ATTRIBUTE_NEVER_CRAFTABLE,
+    ATTRIBUTE_PAINT_KIT,
+    ATTRIBUTE_PARTICLE_EFFECT,
+    ATTRIBUTE_TEXTURE_WEAR,
+    ATTRIBUTE_TOOL_TARGET_ITEM,
+    ATTRIBUTE_UNIQUE_CRAFT_INDEX,
+};
+
+/// The first `recipe component defined item` attribute, used for the output of a chemistry set.
+const ATTRIBUTE_RECIPE_COMPONENT: u32 = 2000;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ParseSkuError {
+    #[error("Invalid defindex \"{}\"", .0)]
+    InvalidDefIndex(String),
+    #[error("Invalid quality \"{}\"", .0)]
+    InvalidQuality(String),
+    #[error("Invalid attribute \"{}\"", .0)]
+    InvalidAttribute(String),
+}
+
+/// The attributes of an item which identify it for pricing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sku {
+    pub def_index: u32,
+    pub quality: Quality,
+    pub craftable: bool,
+    pub australium: bool,
+    pub festivized: bool,
+    pub particle_effect: Option<u32>,
+    pub killstreak_tier: Option<KillstreakTier>,
+    pub wear: Option<Wear>,
+    pub paint_kit: Option<u32>,
+    /// A strange counter on an item of another quality, e.g. a Strange Unusual.
+    pub elevated_quality: Option<Quality>,
+    pub target_def_index: Option<u32>,
+    pub craft_number: Option<u32>,
+    pub crate_series: Option<u32>,
+    pub output_def_index: Option<u32>,
+    pub output_quality: Option<Quality>,
+}
+
+impl Sku {
+    
+    /// A craftable item with no other attributes.
+    pub fn new(def_index: u32, quality: Quality) -> Self {
+        Self {
+            def_index,
+            quality,
+            craftable: true,
+            australium: false,
+            festivized: false,
+            particle_effect: None,
+            killstreak_tier: None,
+            wear: None,
+            paint_kit: None,
+            elevated_quality: None,
+            target_def_index: None,
+            craft_number: None,
+            crate_series: None,
+            output_def_index: None,
+            output_quality: None,
+        }
+    }
+}
+
+impl From<&Item> for Sku {
+    fn from(item: &Item) -> Self {
+        let elevated_quality = if item.quality != Quality::Strange && item.strange_counter.is_some() {
+            Some(Quality::Strange)
+        } else {
+            None
+        };
+        
+        Self {
+            def_index: item.def_index,
+            quality: item.quality,
+            craftable: item.craftable,
+            australium: item.australium,
+            festivized: item.festivized,
+            particle_effect: item.particle_effect,
+            killstreak_tier: item.killstreak_tier,
+            wear: item.wear,
+            paint_kit: item.paint_kit,
+            elevated_quality,
+            target_def_index: item.target_def_index,
+            craft_number: item.craft_number,
+            crate_series: item.crate_series,
+            output_def_index: item.output_def_index,
+            output_quality: item.output_quality,
+        }
+    }
+}
+
+/// Builds an item carrying the attributes described by the SKU, e.g. for display or for
+/// comparing against items from the backpack. Fields not covered by SKUs, such as the item id,
+/// are left unset.
+impl From<&Sku> for CSOEconItem {
+    fn from(sku: &Sku) -> Self {
+        let mut item = CSOEconItem::new();
+        let mut attributes = Vec::new();
+        let float = |value: f32| value.to_bits();
+        
+        item.set_def_index(sku.def_index);
+        item.set_quality(sku.quality.into());
+        
+        if let Some(effect) = sku.particle_effect {
+            attributes.push((ATTRIBUTE_PARTICLE_EFFECT, float(effect as f32)));
+        }
+        
+        if sku.australium {
+            attributes.push((ATTRIBUTE_AUSTRALIUM, float(1.0)));
+        }
+        
+        if !sku.craftable {
+            attributes.push((ATTRIBUTE_NEVER_CRAFTABLE, float(1.0)));
+        }
+        
+        if let Some(wear) = sku.wear {
+            attributes.push((ATTRIBUTE_TEXTURE_WEAR, float(wear.as_float())));
+        }
+        
+        if let Some(paint_kit) = sku.paint_kit {
+            attributes.push((ATTRIBUTE_PAINT_KIT, paint_kit));
+        }
+        
+        if sku.elevated_quality == Some(Quality::Strange) {
+            attributes.push((ATTRIBUTE_KILL_EATER, 0));
+            attributes.push((ATTRIBUTE_KILL_EATER_SCORE_TYPE, float(0.0)));
+        }
+        
+        if let Some(tier) = sku.killstreak_tier {
+            attributes.push((ATTRIBUTE_KILLSTREAK_TIER, float(tier as u32 as f32)));
+        }
+        
+        if let Some(target) = sku.target_def_index {
+            attributes.push((ATTRIBUTE_TOOL_TARGET_ITEM, float(target as f32)));
+        }
+        
+        if sku.festivized {
+            attributes.push((ATTRIBUTE_FESTIVIZED, float(1.0)));
+        }
+        
+        if let Some(craft_number) = sku.craft_number {
+            attributes.push((ATTRIBUTE_UNIQUE_CRAFT_INDEX, craft_number));
+        }
+        
+        if let Some(series) = sku.crate_series {
+            attributes.push((ATTRIBUTE_CRATE_SERIES, float(series as f32)));
+        }
+        
+        for (def_index, value) in attributes {
+            let mut attribute = CSOEconItemAttribute::new();
+            
+            attribute.set_def_index(def_index);
+            attribute.set_value(value);
+            item.mut_attribute().push(attribute);
+        }
+        
+        if sku.output_def_index.is_some() || sku.output_quality.is_some() {
+            let mut component = CAttribute_DynamicRecipeComponent::new();
+            let mut attribute = CSOEconItemAttribute::new();
+            
+            component.set_def_index(sku.output_def_index.unwrap_or_default());
+            component.set_item_quality(sku.output_quality.map(u32::from).unwrap_or_default());
+            component.set_component_flags(DYNAMIC_RECIPE_FLAG_IS_OUTPUT);
+            attribute.set_def_index(ATTRIBUTE_RECIPE_COMPONENT);
+            
+            if let Ok(bytes) = component.write_to_bytes() {
+                attribute.set_value_bytes(bytes);
+                item.mut_attribute().push(attribute);
+            }
+        }
+        
+        item
+    }
+}
+
+impl fmt::Display for Sku {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{};{}", self.def_index, u32::from(self.quality))?;
+        
+        if let Some(effect) = self.particle_effect {
+            write!(f, ";u{}", effect)?;
+        }
+        
+        if self.australium {
+            write!(f, ";australium")?;
+        }
+        
+        if !self.craftable {
+            write!(f, ";uncraftable")?;
+        }
+        
+        if let Some(wear) = self.wear {
+            write!(f, ";w{}", wear as u32)?;
+        }
+        
+        if let Some(paint_kit) = self.paint_kit {
+            write!(f, ";pk{}", paint_kit)?;
+        }
+        
+        if self.elevated_quality == Some(Quality::Strange) {
+            write!(f, ";strange")?;
+        }
+        
+        if let Some(tier) = self.killstreak_tier {
+            write!(f, ";kt-{}", tier as u32)?;
+        }
+        
+        if let Some(target) = self.target_def_index {
+            write!(f, ";td-{}", target)?;
+        }
+        
+        if self.festivized {
+            write!(f, ";festive")?;
+        }
+        
+        if let Some(craft_number) = self.craft_number {
+            write!(f, ";n{}", craft_number)?;
+        }
+        
+        if let Some(series) = self.crate_series {
+            write!(f, ";c{}", series)?;
+        }
+        
+        if let Some(output) = self.output_def_index {
+            write!(f, ";od-{}", output)?;
+        }
+        
+        if let Some(quality) = self.output_quality {
+            write!(f, ";oq-{}", u32::from(quality))?;
+        }
+        
+        Ok(())
+    }
+}
+
+impl FromStr for Sku {
+    type Err = ParseSkuError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(';');
+        let def_index = parts.next().unwrap_or_default();
+        let def_index = def_index.parse::<u32>()
+            .map_err(|_| ParseSkuError::InvalidDefIndex(def_index.to_string()))?;
+        let quality = parts.next().unwrap_or_default();
+        let quality = quality.parse::<u32>()
+            .map(Quality::from)
+            .map_err(|_| ParseSkuError::InvalidQuality(quality.to_string()))?;
+        let mut sku = Self::new(def_index, quality);
+        
+        for part in parts {
+            let invalid = || ParseSkuError::InvalidAttribute(part.to_string());
+            let number = |prefix: &str| -> Result<u32, ParseSkuError> {
+                part[prefix.len()..].parse().map_err(|_| invalid())
+            };
+            
+            match part {
+                "australium" => sku.australium = true,
+                "uncraftable" => sku.craftable = false,
+                "strange" => sku.elevated_quality = Some(Quality::Strange),
+                "festive" => sku.festivized = true,
+                _ if part.starts_with("kt-") => {
+                    sku.killstreak_tier = Some(KillstreakTier::try_from(number("kt-")?).map_err(|_| invalid())?);
+                },
+                _ if part.starts_with("td-") => sku.target_def_index = Some(number("td-")?),
+                _ if part.starts_with("od-") => sku.output_def_index = Some(number("od-")?),
+                _ if part.starts_with("oq-") => sku.output_quality = Some(Quality::from(number("oq-")?)),
+                _ if part.starts_with("pk") => sku.paint_kit = Some(number("pk")?),
+                _ if part.starts_with('u') => sku.particle_effect = Some(number("u")?),
+                _ if part.starts_with('w') => {
+                    sku.wear = Some(Wear::try_from(number("w")?).map_err(|_| invalid())?);
+                },
+                _ if part.starts_with('n') => sku.craft_number = Some(number("n")?),
+                _ if part.starts_with('c') => sku.crate_series = Some(number("c")?),
+                _ => return Err(invalid()),
+            }
+        }
+        
+        Ok(sku)
+    }
+}
+
+impl Item {
+    
+    /// The SKU of this item.
+    pub fn sku(&self) -> Sku {
+        Sku::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn round_trip(s: &str) -> Sku {
+        let sku = s.parse::<Sku>().unwrap();
+        
+        assert_eq!(sku.to_string(), s);
+        assert_eq!(sku.to_string().parse::<Sku>().unwrap(), sku);
+        sku
+    }
+    
+    #[test]
+    fn round_trips_quality_and_effect() {
+        let sku = round_trip("378;5;u13");
+        
+        assert_eq!(sku.quality, Quality::Unusual);
+        assert_eq!(sku.particle_effect, Some(13));
+        assert!(sku.craftable);
+        
+        let sku = round_trip("5021;6;uncraftable");
+        
+        assert_eq!(sku.quality, Quality::Unique);
+        assert!(!sku.craftable);
+    }
+    
+    #[test]
+    fn round_trips_australium_festive_and_killstreak() {
+        let sku = round_trip("205;11;australium;kt-3;festive");
+        
+        assert_eq!(sku.quality, Quality::Strange);
+        assert!(sku.australium);
+        assert!(sku.festivized);
+        assert_eq!(sku.killstreak_tier, Some(KillstreakTier::Professional));
+    }
+    
+    #[test]
+    fn round_trips_wear_and_paint_kit() {
+        let sku = round_trip("15013;15;w3;pk102");
+        
+        assert_eq!(sku.quality, Quality::DecoratedWeapon);
+        assert_eq!(sku.wear, Some(Wear::FieldTested));
+        assert_eq!(sku.paint_kit, Some(102));
+        
+        let sku = round_trip("15141;5;u703;w1;pk307;strange;kt-1");
+        
+        assert_eq!(sku.elevated_quality, Some(Quality::Strange));
+        assert_eq!(sku.killstreak_tier, Some(KillstreakTier::Killstreak));
+    }
+    
+    #[test]
+    fn round_trips_craft_number_and_tools() {
+        assert_eq!(round_trip("5020;6;n42").craft_number, Some(42));
+        assert_eq!(round_trip("5022;6;c83").crate_series, Some(83));
+        assert_eq!(round_trip("6522;6;td-200").target_def_index, Some(200));
+        
+        let sku = round_trip("20005;6;td-211;od-6526;oq-6");
+        
+        assert_eq!(sku.output_def_index, Some(6526));
+        assert_eq!(sku.output_quality, Some(Quality::Unique));
+    }
+    
+    #[test]
+    fn writes_attributes_in_canonical_order() {
+        let sku = "5021;6;festive;uncraftable;u13".parse::<Sku>().unwrap();
+        
+        assert_eq!(sku.to_string(), "5021;6;u13;uncraftable;festive");
+    }
+    
+    #[test]
+    fn rejects_malformed_skus() {
+        assert_eq!("".parse::<Sku>(), Err(ParseSkuError::InvalidDefIndex("".into())));
+        assert_eq!("abc;6".parse::<Sku>(), Err(ParseSkuError::InvalidDefIndex("abc".into())));
+        assert_eq!("5021".parse::<Sku>(), Err(ParseSkuError::InvalidQuality("".into())));
+        assert_eq!("5021;x".parse::<Sku>(), Err(ParseSkuError::InvalidQuality("x".into())));
+        
+        for attribute in ["bogus", "", "u", "kt-4", "w0", "w6", "pkx", "n-1", "td-"] {
+            assert_eq!(
+                format!("5021;6;{}", attribute).parse::<Sku>(),
+                Err(ParseSkuError::InvalidAttribute(attribute.into())),
+                "{}",
+                attribute,
+            );
+        }
+    }
+    
+    #[test]
+    fn converts_items() {
+        let mut item = CSOEconItem::new();
+        
+        item.set_def_index(205);
+        item.set_quality(5);
+        
+        for (def_index, value) in [
+            (ATTRIBUTE_PARTICLE_EFFECT, 13f32.to_bits()),
+            (ATTRIBUTE_AUSTRALIUM, 1f32.to_bits()),
+            (ATTRIBUTE_KILLSTREAK_TIER, 2f32.to_bits()),
+            (ATTRIBUTE_FESTIVIZED, 1f32.to_bits()),
+            (ATTRIBUTE_UNIQUE_CRAFT_INDEX, 7),
+        ] {
+            let mut attribute = CSOEconItemAttribute::new();
+            
+            attribute.set_def_index(def_index);
+            attribute.set_value(value);
+            item.mut_attribute().push(attribute);
+        }
+        
+        let sku = Item::from(&item).sku();
+        
+        assert_eq!(sku.to_string(), "205;5;u13;australium;kt-2;festive;n7");
+        assert_eq!(sku.to_string().parse::<Sku>().unwrap(), sku);
+    }
+    
+    /// Converts `sku` to an item and back, through its string form.
+    fn item_round_trip(s: &str) {
+        let sku = s.parse::<Sku>().unwrap();
+        let item = Item::from(&CSOEconItem::from(&sku));
+        let parsed = item.sku().to_string().parse::<Sku>().unwrap();
+        
+        assert_eq!(parsed, sku, "{}", s);
+        assert_eq!(parsed.def_index, item.def_index);
+        assert_eq!(parsed.quality, item.quality);
+        assert_eq!(parsed.craftable, item.craftable);
+        assert_eq!(parsed.particle_effect, item.particle_effect);
+        assert_eq!(parsed.killstreak_tier, item.killstreak_tier);
+        assert_eq!(parsed.wear, item.wear);
+        assert_eq!(parsed.paint_kit, item.paint_kit);
+    }
+    
+    #[test]
+    fn round_trips_through_items() {
+        for s in [
+            "5021;6",
+            "5021;6;uncraftable",
+            "378;5;u13",
+            "205;11;australium;kt-3;festive",
+            "15141;5;u703;w1;pk307;strange;kt-1",
+            "5020;6;n42",
+            "5022;6;c83",
+            "6522;6;td-200",
+            "20005;6;td-211;od-6526;oq-6",
+        ] {
+            item_round_trip(s);
+        }
+    }
+    
+    #[test]
+    fn builds_item_attributes() {
+        let sku = "15013;15;uncraftable;w3;pk102".parse::<Sku>().unwrap();
+        let item = CSOEconItem::from(&sku);
+        let attribute = |def_index: u32| {
+            item.get_attribute()
+                .iter()
+                .find(|attribute| attribute.get_def_index() == def_index)
+                .map(|attribute| attribute.get_value())
+        };
+        
+        assert_eq!(item.get_def_index(), 15013);
+        assert_eq!(item.get_quality(), 15);
+        assert_eq!(attribute(ATTRIBUTE_NEVER_CRAFTABLE), Some(1f32.to_bits()));
+        assert_eq!(attribute(ATTRIBUTE_TEXTURE_WEAR), Some(0.6f32.to_bits()));
+        assert_eq!(attribute(ATTRIBUTE_PAINT_KIT), Some(102));
+        assert_eq!(item.get_attribute().len(), 3);
+    }
+}
\ No newline at end of file
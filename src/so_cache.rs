@@ -0,0 +1,515 @@
+use std::collections::HashMap;
+use protobuf::ProtobufEnum;
+use tf2_protobuf::{
+    gcsystemmsgs::ESOMsg,
+    gcsdk_gcmessages::{
+        CMsgSOSingleObject,
+        CMsgSOMultipleObjects,
+        CMsgSOCacheSubscribed,
+        CMsgSOCacheUnsubscribed,
+    },
+    base_gcmessages::{
+        CSOEconItem,
+        CSOClassPresetClientData,
+        CSOEconItemPresetInstance,
+    },
+    tf_gcmessages::{
+        CMsgGCNotification,
+        CSOTFParty,
+        CSOTFPartyInvite,
+        CSOTFGameServerLobby,
+        CSOTFLadderPlayerStats,
+        CSOTFMatchResultPlayerStats,
+        CSOTFRatingData,
+        CMsgTFXPSource,
+        CSOQuest,
+        CSOQuestMapNode,
+        CSOQuestMapRewardPurchase,
+        CSOWarData,
+    },
+};
+use crate::response::{parse_proto, GCBytesMessageError};
+use crate::loadout::{self, ClassPresets, Loadout, TFClass};
+
+/// Shared object type id of [`CSOEconItem`].
+pub const SO_TYPE_ITEM: i32 = 1;
+/// Shared object type id of [`CSOClassPresetClientData`]. The value is assumed from the
+/// game's econ type list and has not been confirmed against the GC.
+pub const SO_TYPE_CLASS_PRESET_CLIENT_DATA: i32 = 28;
+/// Shared object type id of [`CSOEconItemPresetInstance`]. The value is assumed from the
+/// game's econ type list and has not been confirmed against the GC.
+pub const SO_TYPE_ITEM_PRESET_INSTANCE: i32 = 35;
+/// Shared object type id of [`CSOTFParty`].
+pub const SO_TYPE_PARTY: i32 = 2003;
+/// Shared object type id of [`CSOTFGameServerLobby`].
+pub const SO_TYPE_LOBBY: i32 = 2004;
+/// Shared object type id of [`CSOTFPartyInvite`].
+pub const SO_TYPE_PARTY_INVITE: i32 = 2006;
+/// Shared object type id of [`CSOTFLadderPlayerStats`].
+pub const SO_TYPE_LADDER_PLAYER_STATS: i32 = 2007;
+/// Shared object type id of [`CSOTFMatchResultPlayerStats`].
+pub const SO_TYPE_MATCH_RESULT_PLAYER_STATS: i32 = 2008;
+/// Shared object type id of [`CMsgTFXPSource`].
+pub const SO_TYPE_XP_SOURCE: i32 = 2009;
+/// Shared object type id of [`CMsgGCNotification`].
+pub const SO_TYPE_NOTIFICATION: i32 = 2010;
+/// Shared object type id of [`CSOTFRatingData`].
+pub const SO_TYPE_RATING_DATA: i32 = 2011;
+/// Shared object type id of [`CSOQuest`].
+pub const SO_TYPE_QUEST: i32 = 2012;
+/// Shared object type id of [`CSOQuestMapNode`].
+pub const SO_TYPE_QUEST_MAP_NODE: i32 = 2013;
+/// Shared object type id of [`CSOQuestMapRewardPurchase`].
+pub const SO_TYPE_QUEST_MAP_REWARD_PURCHASE: i32 = 2014;
+/// Shared object type id of [`CSOWarData`].
+pub const SO_TYPE_WAR_DATA: i32 = 2015;
+
+/// A change made to the cache by a shared object message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SOChange {
+    Created,
+    Updated,
+    Destroyed,
+}
+
+/// Local copy of the shared objects the GC has sent for the subscribed caches.
+#[derive(Debug, Default)]
+pub struct SOCache {
+    /// Type ids received for each cache owner, used to drop objects on unsubscribe.
+    owners: HashMap<u64, Vec<i32>>,
+    /// The owner of the client's own cache.
+    steamid: Option<u64>,
+    items: HashMap<u64, CSOEconItem>,
+    /// Keyed by class id.
+    class_presets: HashMap<u32, CSOClassPresetClientData>,
+    /// Keyed by class id, preset id and slot.
+    preset_instances: HashMap<(u32, u32, u32), CSOEconItemPresetInstance>,
+    notifications: HashMap<u64, CMsgGCNotification>,
+    party: Option<CSOTFParty>,
+    party_invites: HashMap<u64, CSOTFPartyInvite>,
+    lobbies: HashMap<u64, CSOTFGameServerLobby>,
+    /// Keyed by account id, match group and season.
+    ladder_stats: HashMap<(u32, i32, u32), CSOTFLadderPlayerStats>,
+    /// Keyed by match id and account id.
+    match_results: HashMap<(u64, u32), CSOTFMatchResultPlayerStats>,
+    /// Keyed by account id and rating type.
+    ratings: HashMap<(u32, i32), CSOTFRatingData>,
+    /// Keyed by source type, account id and match id.
+    xp_sources: HashMap<(i32, u32, u64), CMsgTFXPSource>,
+    quests: HashMap<u64, CSOQuest>,
+    /// Keyed by node defindex.
+    quest_map_nodes: HashMap<u32, CSOQuestMapNode>,
+    /// Keyed by store item defindex.
+    quest_reward_purchases: HashMap<u32, CSOQuestMapRewardPurchase>,
+    /// Keyed by account id and war id.
+    war_data: HashMap<(u32, u32), CSOWarData>,
+}
+
+impl SOCache {
+    
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// The SteamID of the client, from the owner of its cache. Other caches are owned by
+    /// lobbies and parties rather than individual accounts.
+    pub fn steamid(&self) -> Option<u64> {
+        self.steamid
+    }
+    
+    /// The account id of the client.
+    pub fn account_id(&self) -> Option<u32> {
+        self.steamid.map(|steamid| steamid as u32)
+    }
+    
+    pub fn items(&self) -> impl Iterator<Item = &CSOEconItem> {
+        self.items.values()
+    }
+    
+    pub fn item(&self, item_id: u64) -> Option<&CSOEconItem> {
+        self.items.get(&item_id)
+    }
+    
+    /// Pending notifications, which are removed by the GC once acknowledged.
+    pub fn notifications(&self) -> impl Iterator<Item = &CMsgGCNotification> {
+        self.notifications.values()
+    }
+    
+    pub fn notification(&self, notification_id: u64) -> Option<&CMsgGCNotification> {
+        self.notifications.get(&notification_id)
+    }
+    
+    /// Adds a notification received outside of a cache subscription.
+    pub(crate) fn insert_notification(&mut self, notification: CMsgGCNotification) {
+        self.notifications.insert(notification.get_notification_id(), notification);
+    }
+    
+    /// Removes an acknowledged notification.
+    pub(crate) fn remove_notification(&mut self, notification_id: u64) -> Option<CMsgGCNotification> {
+        self.notifications.remove(&notification_id)
+    }
+    
+    /// The party the client is in.
+    pub fn party(&self) -> Option<&CSOTFParty> {
+        self.party.as_ref()
+    }
+    
+    /// Invites to other parties and requests to join ours, keyed by party id.
+    pub fn party_invites(&self) -> impl Iterator<Item = &CSOTFPartyInvite> {
+        self.party_invites.values()
+    }
+    
+    /// The lobbies the client is in or invited to.
+    pub fn lobbies(&self) -> impl Iterator<Item = &CSOTFGameServerLobby> {
+        self.lobbies.values()
+    }
+    
+    pub fn lobby(&self, lobby_id: u64) -> Option<&CSOTFGameServerLobby> {
+        self.lobbies.get(&lobby_id)
+    }
+    
+    /// Per-season ladder stats for each match group.
+    pub fn ladder_stats(&self) -> impl Iterator<Item = &CSOTFLadderPlayerStats> {
+        self.ladder_stats.values()
+    }
+    
+    /// Results of past matches, loaded with [`crate::TeamFortress2::load_match_history`].
+    pub fn match_results(&self) -> impl Iterator<Item = &CSOTFMatchResultPlayerStats> {
+        self.match_results.values()
+    }
+    
+    pub fn ratings(&self) -> impl Iterator<Item = &CSOTFRatingData> {
+        self.ratings.values()
+    }
+    
+    /// Experience earned in matches which has not been acknowledged.
+    pub fn xp_sources(&self) -> impl Iterator<Item = &CMsgTFXPSource> {
+        self.xp_sources.values()
+    }
+    
+    pub fn quests(&self) -> impl Iterator<Item = &CSOQuest> {
+        self.quests.values()
+    }
+    
+    pub fn quest(&self, quest_id: u64) -> Option<&CSOQuest> {
+        self.quests.get(&quest_id)
+    }
+    
+    /// The unlocked quest map nodes.
+    pub fn quest_map_nodes(&self) -> impl Iterator<Item = &CSOQuestMapNode> {
+        self.quest_map_nodes.values()
+    }
+    
+    pub fn quest_map_node(&self, node_def_index: u32) -> Option<&CSOQuestMapNode> {
+        self.quest_map_nodes.get(&node_def_index)
+    }
+    
+    pub fn quest_reward_purchases(&self) -> impl Iterator<Item = &CSOQuestMapRewardPurchase> {
+        self.quest_reward_purchases.values()
+    }
+    
+    /// The sides joined in wars and the points scored for them.
+    pub fn war_data(&self) -> impl Iterator<Item = &CSOWarData> {
+        self.war_data.values()
+    }
+    
+    /// The loadout of each class according to the cached items.
+    pub fn loadouts(&self) -> HashMap<TFClass, Loadout> {
+        loadout::loadouts(self.items.values())
+    }
+    
+    pub fn loadout(&self, class: TFClass) -> Loadout {
+        self.loadouts().remove(&class).unwrap_or_default()
+    }
+    
+    /// The active preset of each class and the items in each preset.
+    pub fn presets(&self) -> HashMap<TFClass, ClassPresets> {
+        loadout::presets(self.class_presets.values(), self.preset_instances.values())
+    }
+    
+    pub fn class_presets(&self, class: TFClass) -> ClassPresets {
+        self.presets().remove(&class).unwrap_or_default()
+    }
+    
+    /// Applies a shared object message to the cache. Returns `false` if `msg_type` is not one
+    /// of [`ESOMsg`].
+    pub fn handle_message(
+        &mut self,
+        msg_type: i32,
+        payload: &[u8],
+    ) -> Result<bool, GCBytesMessageError> {
+        let msg = match ESOMsg::from_i32(msg_type) {
+            Some(msg) => msg,
+            None => return Ok(false),
+        };
+        
+        match msg {
+            ESOMsg::k_ESOMsg_Create |
+            ESOMsg::k_ESOMsg_Update |
+            ESOMsg::k_ESOMsg_Destroy => {
+                let message: CMsgSOSingleObject = parse_proto(payload)?;
+                let change = match msg {
+                    ESOMsg::k_ESOMsg_Create => SOChange::Created,
+                    ESOMsg::k_ESOMsg_Update => SOChange::Updated,
+                    _ => SOChange::Destroyed,
+                };
+                
+                self.apply(
+                    message.get_owner(),
+                    message.get_type_id(),
+                    message.get_object_data(),
+                    change,
+                )?;
+            },
+            ESOMsg::k_ESOMsg_UpdateMultiple => {
+                let message: CMsgSOMultipleObjects = parse_proto(payload)?;
+                
+                for object in message.get_objects() {
+                    self.apply(
+                        message.get_owner(),
+                        object.get_type_id(),
+                        object.get_object_data(),
+                        SOChange::Updated,
+                    )?;
+                }
+            },
+            ESOMsg::k_ESOMsg_CacheSubscribed => {
+                let message: CMsgSOCacheSubscribed = parse_proto(payload)?;
+                let owner = message.get_owner();
+                
+                self.clear_owner(owner);
+                
+                if self.steamid.is_none() && is_individual_account(owner) {
+                    self.steamid = Some(owner);
+                }
+                
+                for subscribed_type in message.get_objects() {
+                    for object_data in subscribed_type.get_object_data() {
+                        self.apply(
+                            owner,
+                            subscribed_type.get_type_id(),
+                            object_data,
+                            SOChange::Created,
+                        )?;
+                    }
+                }
+            },
+            ESOMsg::k_ESOMsg_CacheUnsubscribed => {
+                let message: CMsgSOCacheUnsubscribed = parse_proto(payload)?;
+                
+                self.clear_owner(message.get_owner());
+            },
+            _ => {},
+        }
+        
+        Ok(true)
+    }
+    
+    fn apply(
+        &mut self,
+        owner: u64,
+        type_id: i32,
+        object_data: &[u8],
+        change: SOChange,
+    ) -> Result<(), GCBytesMessageError> {
+        let type_ids = self.owners.entry(owner).or_default();
+        
+        if !type_ids.contains(&type_id) {
+            type_ids.push(type_id);
+        }
+        
+        match type_id {
+            SO_TYPE_ITEM => {
+                let item: CSOEconItem = parse_proto(object_data)?;
+                
+                if change == SOChange::Destroyed {
+                    self.items.remove(&item.get_id());
+                } else {
+                    self.items.insert(item.get_id(), item);
+                }
+            },
+            SO_TYPE_CLASS_PRESET_CLIENT_DATA => {
+                let data: CSOClassPresetClientData = parse_proto(object_data)?;
+                let class_id = data.get_class_id();
+                
+                if change == SOChange::Destroyed {
+                    self.class_presets.remove(&class_id);
+                } else {
+                    self.class_presets.insert(class_id, data);
+                }
+            },
+            SO_TYPE_ITEM_PRESET_INSTANCE => {
+                let instance: CSOEconItemPresetInstance = parse_proto(object_data)?;
+                let key = (instance.get_class_id(), instance.get_preset_id(), instance.get_slot_id());
+                
+                if change == SOChange::Destroyed {
+                    self.preset_instances.remove(&key);
+                } else {
+                    self.preset_instances.insert(key, instance);
+                }
+            },
+            SO_TYPE_NOTIFICATION => {
+                let notification: CMsgGCNotification = parse_proto(object_data)?;
+                let id = notification.get_notification_id();
+                
+                if change == SOChange::Destroyed {
+                    self.notifications.remove(&id);
+                } else {
+                    self.notifications.insert(id, notification);
+                }
+            },
+            SO_TYPE_PARTY => {
+                let party: CSOTFParty = parse_proto(object_data)?;
+                
+                if change == SOChange::Destroyed {
+                    self.party = None;
+                } else {
+                    self.party = Some(party);
+                }
+            },
+            SO_TYPE_PARTY_INVITE => {
+                let invite: CSOTFPartyInvite = parse_proto(object_data)?;
+                let id = invite.get_group_id();
+                
+                if change == SOChange::Destroyed {
+                    self.party_invites.remove(&id);
+                } else {
+                    self.party_invites.insert(id, invite);
+                }
+            },
+            SO_TYPE_LOBBY => {
+                let lobby: CSOTFGameServerLobby = parse_proto(object_data)?;
+                let id = lobby.get_lobby_id();
+                
+                if change == SOChange::Destroyed {
+                    self.lobbies.remove(&id);
+                } else {
+                    self.lobbies.insert(id, lobby);
+                }
+            },
+            SO_TYPE_LADDER_PLAYER_STATS => {
+                let stats: CSOTFLadderPlayerStats = parse_proto(object_data)?;
+                let key = (stats.get_account_id(), stats.get_match_group(), stats.get_season_id());
+                
+                if change == SOChange::Destroyed {
+                    self.ladder_stats.remove(&key);
+                } else {
+                    self.ladder_stats.insert(key, stats);
+                }
+            },
+            SO_TYPE_MATCH_RESULT_PLAYER_STATS => {
+                let stats: CSOTFMatchResultPlayerStats = parse_proto(object_data)?;
+                let key = (stats.get_match_id(), stats.get_account_id());
+                
+                if change == SOChange::Destroyed {
+                    self.match_results.remove(&key);
+                } else {
+                    self.match_results.insert(key, stats);
+                }
+            },
+            SO_TYPE_RATING_DATA => {
+                let rating: CSOTFRatingData = parse_proto(object_data)?;
+                let key = (rating.get_account_id(), rating.get_rating_type());
+                
+                if change == SOChange::Destroyed {
+                    self.ratings.remove(&key);
+                } else {
+                    self.ratings.insert(key, rating);
+                }
+            },
+            SO_TYPE_XP_SOURCE => {
+                let source: CMsgTFXPSource = parse_proto(object_data)?;
+                let key = (
+                    source.get_field_type().value(),
+                    source.get_account_id(),
+                    source.get_match_id(),
+                );
+                
+                if change == SOChange::Destroyed {
+                    self.xp_sources.remove(&key);
+                } else {
+                    self.xp_sources.insert(key, source);
+                }
+            },
+            SO_TYPE_QUEST => {
+                let quest: CSOQuest = parse_proto(object_data)?;
+                let id = quest.get_quest_id();
+                
+                if change == SOChange::Destroyed {
+                    self.quests.remove(&id);
+                } else {
+                    self.quests.insert(id, quest);
+                }
+            },
+            SO_TYPE_QUEST_MAP_NODE => {
+                let node: CSOQuestMapNode = parse_proto(object_data)?;
+                let id = node.get_defindex();
+                
+                if change == SOChange::Destroyed {
+                    self.quest_map_nodes.remove(&id);
+                } else {
+                    self.quest_map_nodes.insert(id, node);
+                }
+            },
+            SO_TYPE_QUEST_MAP_REWARD_PURCHASE => {
+                let purchase: CSOQuestMapRewardPurchase = parse_proto(object_data)?;
+                let id = purchase.get_defindex();
+                
+                if change == SOChange::Destroyed {
+                    self.quest_reward_purchases.remove(&id);
+                } else {
+                    self.quest_reward_purchases.insert(id, purchase);
+                }
+            },
+            SO_TYPE_WAR_DATA => {
+                let war_data: CSOWarData = parse_proto(object_data)?;
+                let key = (war_data.get_account_id(), war_data.get_war_id());
+                
+                if change == SOChange::Destroyed {
+                    self.war_data.remove(&key);
+                } else {
+                    self.war_data.insert(key, war_data);
+                }
+            },
+            _ => {},
+        }
+        
+        Ok(())
+    }
+    
+    fn clear_owner(&mut self, owner: u64) {
+        if self.steamid == Some(owner) {
+            self.steamid = None;
+        }
+        
+        for type_id in self.owners.remove(&owner).unwrap_or_default() {
+            self.clear_type(type_id);
+        }
+    }
+    
+    fn clear_type(&mut self, type_id: i32) {
+        match type_id {
+            SO_TYPE_ITEM => self.items.clear(),
+            SO_TYPE_CLASS_PRESET_CLIENT_DATA => self.class_presets.clear(),
+            SO_TYPE_ITEM_PRESET_INSTANCE => self.preset_instances.clear(),
+            SO_TYPE_NOTIFICATION => self.notifications.clear(),
+            SO_TYPE_PARTY => self.party = None,
+            SO_TYPE_PARTY_INVITE => self.party_invites.clear(),
+            SO_TYPE_LOBBY => self.lobbies.clear(),
+            SO_TYPE_LADDER_PLAYER_STATS => self.ladder_stats.clear(),
+            SO_TYPE_MATCH_RESULT_PLAYER_STATS => self.match_results.clear(),
+            SO_TYPE_RATING_DATA => self.ratings.clear(),
+            SO_TYPE_XP_SOURCE => self.xp_sources.clear(),
+            SO_TYPE_QUEST => self.quests.clear(),
+            SO_TYPE_QUEST_MAP_NODE => self.quest_map_nodes.clear(),
+            SO_TYPE_QUEST_MAP_REWARD_PURCHASE => self.quest_reward_purchases.clear(),
+            SO_TYPE_WAR_DATA => self.war_data.clear(),
+            _ => {},
+        }
+    }
+}
+
+/// Whether `steamid` is an individual user account rather than e.g. a lobby.
+fn is_individual_account(steamid: u64) -> bool {
+    (steamid >> 52) & 0xF == 1
+}
@@ -1,4 +1,4 @@
-use protobuf::{RepeatedField, Message};
+use protobuf::{RepeatedField, Message, ProtobufEnum};
 use steam_vent::{
     net::PROTO_MASK,
     net::NetworkError,
@@ -7,7 +7,58 @@ use steam_vent::{
     game_coordinator::ClientToGCMessage,
 };
 use tf2_protobuf::{
-    econ_gcmessages::EGCItemMsg,
+    tf_gcmessages::{
+        ETFGCMsg,
+        CMsgNotificationAcknowledge,
+        CMsgNotificationAcknowledgeReply,
+        CMsgGCNotificationQueue,
+        CMsgGC_Client2GCEconPreviewDataBlockRequest,
+        CMsgGC_Client2GCEconPreviewDataBlockResponse,
+        CMsgPartyInvitePlayer,
+        CMsgPartyRequestJoinPlayer,
+        CMsgPartyKickMember,
+        CMsgPartyPromoteToLeader,
+        CMsgPartySendChat,
+        CMsgPartyChatMsg,
+        CMsgPartySetOptions,
+        CMsgPartyQueueForMatch,
+        CMsgPartyQueueForMatchResponse,
+        CMsgPartyQueueForStandby,
+        CMsgPartyQueueForStandbyResponse,
+        CMsgPartyRemoveFromQueue,
+        CMsgPartyRemoveFromQueueResponse,
+        CMsgPartyRemoveFromStandbyQueue,
+        CMsgPartyRemoveFromStandbyQueueResponse,
+        CMsgExitMatchmaking,
+        CMsgMatchmakingProgress,
+        CMsgAcceptLobbyInvite,
+        CMsgGCMatchHistoryLoad,
+        CMsgAcknowledgeXP,
+        CGCMsgGC_War_JoinWar,
+        CGCMsgGC_War_RequestGlobalStats,
+        CGCMsgGC_War_GlobalStatsResponse,
+        CGCMsgGC_War_IndividualUpdate,
+        CGCMsgGC_PlayerDuckLeaderboard_IndividualUpdate,
+        CMsgGCRequestMatchMakerStats,
+        CMsgGCMatchMakerStatsResponse,
+        CMsgGCDataCenterPing_Update,
+        CMsgGCQuestMapUnlockNode,
+        CMsgGCQuestMapPurchaseReward,
+        CMsgGCQuestNodeTurnIn,
+        CMsgGCQuestResponse,
+        TF_GC_GameState,
+        ETFMatchGroup,
+    },
+    econ_gcmessages::{
+        EGCItemMsg,
+        CMsgGCQuestDiscard_Request,
+        CMsgLookupMultipleAccountNames,
+        CMsgLookupMultipleAccountNamesResponse,
+        CMsgCraftCollectionUpgrade,
+        CMsgCraftHalloweenOffering,
+        CMsgCraftCommonStatClock,
+        CMsgCraftingResponse,
+    },
     base_gcmessages::{
         CMsgSetItemPositions,
         CMsgSetItemPositions_ItemPosition,
@@ -15,18 +66,71 @@ use tf2_protobuf::{
         CMsgFulfillDynamicRecipeComponent,
         CMsgRecipeComponent,
         CMsgGCRemoveCustomizationAttributeSimple,
+        CMsgAdjustItemEquippedState,
+        CMsgSelectPresetForClass,
+        CMsgSetPresetItemPosition,
+        CMsgSortItems,
+        CMsgItemAcknowledged,
+        CMsgUpdateItemSchema,
+        CSOEconItem,
     },
 };
 use byteorder::{LittleEndian, WriteBytesExt};
 use bytes::{BufMut, BytesMut};
 use std::io::Write;
-use crate::{request::{self, ItemCustomization}, app::App};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::{
+    request::{self, ItemCustomization, SortType},
+    event::GCEvent,
+    recipe::{Recipe, RECIPE_CUSTOM},
+    metal::{MetalCounts, MetalCrafter, MetalCraftStep, MetalCraftStatus, MetalInventory},
+    dynamic_recipe::{self, DynamicRecipePlan},
+    so_cache::SOCache,
+    notification::Notification,
+    inspect::InspectOwner,
+    item::Item,
+    party::{Party, PartyHandle, PartyInvite, PartyChatMessage, PartyOptions, QueueEntry},
+    matchmaking::{MatchmakingProgress, MatchmakerStats, DataCenterPing},
+    lobby::Lobby,
+    ladder::{Rating, RatingType, LadderStats, MatchResult},
+    xp::{self, XPSource, XPSourceType, CasualProgression},
+    quest::{Quest, QuestMapNode, QuestRewardPurchase, QuestAction, QuestResponse},
+    war::{WarSide, WarContribution, WarStats, WarScoreUpdate, DuckLeaderboardUpdate},
+    backpack::{self, InventoryPosition},
+    schema::Schema,
+    response::{parse_proto, CraftingResponse},
+    loadout::{TFClass, LoadoutSlot},
+    app::App,
+    error::Error,
+};
 
 pub const JOBID_NONE: u64 = u64::MAX;
+/// Item id used to clear a loadout slot.
+pub const ITEMID_NONE: u64 = u64::MAX;
+/// Number of items of the same grade consumed by a collection upgrade (trade-up).
+pub const COLLECTION_UPGRADE_ITEM_COUNT: usize = 10;
+/// Number of items consumed by a Halloween offering.
+pub const HALLOWEEN_OFFERING_ITEM_COUNT: usize = 3;
+/// Number of items consumed when crafting a stat clock.
+pub const STAT_CLOCK_ITEM_COUNT: usize = 5;
+/// Maximum number of account ids sent in one account name lookup. The GC does not publish a
+/// limit, so lookups are split to keep each request and its response small.
+pub const ACCOUNT_NAME_LOOKUP_BATCH_SIZE: usize = 100;
 
 #[derive(Debug)]
 pub struct TeamFortress2 {
     source_job_id: u64,
+    so_cache: SOCache,
+    sorting_backpack: bool,
+    schema: Option<(Schema, u32)>,
+    latest_schema: Option<(u32, String)>,
+    /// Looked up persona names. `None` for accounts the GC had no name for.
+    account_names: HashMap<u32, Option<String>>,
+    /// Account ids of the account name lookups awaiting a response, in the order sent.
+    pending_account_names: VecDeque<Vec<u32>>,
+    /// Ids of the notifications acknowledged and awaiting a reply, in the order sent.
+    pending_notification_acks: VecDeque<u64>,
 }
 
 impl App for TeamFortress2 {
@@ -38,6 +142,506 @@ impl TeamFortress2 {
     pub fn new() -> Self {
         Self {
             source_job_id: 0,
+            so_cache: SOCache::new(),
+            sorting_backpack: false,
+            schema: None,
+            latest_schema: None,
+            account_names: HashMap::new(),
+            pending_account_names: VecDeque::new(),
+            pending_notification_acks: VecDeque::new(),
+        }
+    }
+    
+    pub fn so_cache(&self) -> &SOCache {
+        &self.so_cache
+    }
+    
+    /// The loaded item schema.
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref().map(|(schema, _version)| schema)
+    }
+    
+    /// Replaces the item schema. `version` is the `item_schema_version` the schema was 
+    /// downloaded for.
+    pub fn set_schema(&mut self, schema: Schema, version: u32) {
+        self.schema = Some((schema, version));
+    }
+    
+    /// The latest schema version and `items_game.txt` URL reported by the GC.
+    pub fn latest_schema(&self) -> Option<(u32, &str)> {
+        self.latest_schema
+            .as_ref()
+            .map(|(version, url)| (*version, url.as_str()))
+    }
+    
+    /// Whether the GC has reported a schema version other than the loaded one.
+    pub fn is_schema_outdated(&self) -> bool {
+        match (&self.schema, &self.latest_schema) {
+            (Some((_, version)), Some((latest, _))) => version != latest,
+            (None, Some(_)) => true,
+            _ => false,
+        }
+    }
+    
+    /// Whether a sort started with [`TeamFortress2::sort_backpack`] is still running.
+    pub fn is_sorting_backpack(&self) -> bool {
+        self.sorting_backpack
+    }
+    
+    /// The notifications waiting to be acknowledged.
+    pub fn notifications(&self) -> Vec<Notification> {
+        self.so_cache
+            .notifications()
+            .map(Notification::from)
+            .collect()
+    }
+    
+    /// The party the client is in.
+    pub fn party(&self) -> Option<Party> {
+        self.so_cache.party().map(Party::from)
+    }
+    
+    /// A handle for managing the party over `connection`.
+    pub fn party_handle<'a>(&'a mut self, connection: &'a mut Connection) -> PartyHandle<'a> {
+        PartyHandle::new(self, connection)
+    }
+    
+    /// Pending invites to other parties and requests to join ours.
+    pub fn party_invites(&self) -> Vec<PartyInvite> {
+        self.so_cache
+            .party_invites()
+            .map(PartyInvite::from)
+            .collect()
+    }
+    
+    /// The matchmaking queues the party is in, with the time each was joined.
+    pub fn matchmaking_queues(&self) -> Vec<QueueEntry> {
+        self.party()
+            .map(|party| party.queues)
+            .unwrap_or_default()
+    }
+    
+    /// The lobbies the client is in or has been invited to.
+    pub fn lobbies(&self) -> Vec<Lobby> {
+        self.so_cache
+            .lobbies()
+            .map(Lobby::from)
+            .collect()
+    }
+    
+    /// The lobby the party is playing in.
+    pub fn lobby(&self) -> Option<Lobby> {
+        let lobby_id = self.party()?.associated_lobby_id?;
+        
+        self.so_cache.lobby(lobby_id).map(Lobby::from)
+    }
+    
+    /// The client's matchmaking ratings. Ratings of unknown types are skipped.
+    pub fn ratings(&self) -> Vec<Rating> {
+        let account_id = self.so_cache.account_id();
+        
+        self.so_cache
+            .ratings()
+            .filter(|rating| Some(rating.get_account_id()) == account_id)
+            .filter_map(|rating| Rating::try_from(rating).ok())
+            .collect()
+    }
+    
+    pub fn rating(&self, rating_type: RatingType) -> Option<Rating> {
+        self.ratings()
+            .into_iter()
+            .find(|rating| rating.rating_type == rating_type)
+    }
+    
+    /// The client's ladder stats in `match_group` for each season, ordered by season.
+    pub fn ladder_stats(&self, match_group: ETFMatchGroup) -> Vec<LadderStats> {
+        let account_id = self.so_cache.account_id();
+        let mut stats = self.so_cache
+            .ladder_stats()
+            .filter(|stats| Some(stats.get_account_id()) == account_id)
+            .map(LadderStats::from)
+            .filter(|stats| stats.match_group == Some(match_group))
+            .collect::<Vec<_>>();
+        
+        stats.sort_by_key(|stats| stats.season_id);
+        stats
+    }
+    
+    /// The client's match results in `match_group` loaded with
+    /// [`TeamFortress2::load_match_history`], newest first.
+    pub fn match_history(&self, match_group: ETFMatchGroup) -> Vec<MatchResult> {
+        let account_id = self.so_cache.account_id();
+        let mut results = self.so_cache
+            .match_results()
+            .filter(|result| Some(result.get_account_id()) == account_id)
+            .map(MatchResult::from)
+            .filter(|result| result.match_group == Some(match_group))
+            .collect::<Vec<_>>();
+        
+        results.sort_by_key(|result| std::cmp::Reverse(result.endtime));
+        results
+    }
+    
+    /// Experience earned in `match_group` which has not been acknowledged.
+    pub fn pending_xp(&self, match_group: ETFMatchGroup) -> Vec<XPSource> {
+        self.so_cache
+            .xp_sources()
+            .map(XPSource::from)
+            .filter(|source| source.match_group == Some(match_group))
+            .collect()
+    }
+    
+    /// Experience earned in `match_group` which has not been acknowledged, summed by source.
+    pub fn pending_xp_by_source(&self, match_group: ETFMatchGroup) -> HashMap<XPSourceType, i32> {
+        xp::xp_by_source(&self.pending_xp(match_group))
+    }
+    
+    /// Casual experience and level, including what has not been acknowledged.
+    pub fn casual_progression(&self) -> Option<CasualProgression> {
+        CasualProgression::from_ratings(&self.ratings())
+    }
+    
+    /// The contracts the client owns.
+    pub fn quests(&self) -> Vec<Quest> {
+        self.so_cache
+            .quests()
+            .map(Quest::from)
+            .collect()
+    }
+    
+    /// The unlocked quest map nodes and the stars earned on each.
+    pub fn quest_map_nodes(&self) -> Vec<QuestMapNode> {
+        self.so_cache
+            .quest_map_nodes()
+            .map(QuestMapNode::from)
+            .collect()
+    }
+    
+    /// Rewards bought from the quest map store.
+    pub fn quest_reward_purchases(&self) -> Vec<QuestRewardPurchase> {
+        self.so_cache
+            .quest_reward_purchases()
+            .map(QuestRewardPurchase::from)
+            .collect()
+    }
+    
+    /// The sides joined in wars and the points scored for them.
+    pub fn war_contributions(&self) -> Vec<WarContribution> {
+        self.so_cache
+            .war_data()
+            .map(WarContribution::from)
+            .collect()
+    }
+    
+    pub fn war_contribution(&self, war_id: u32) -> Option<WarContribution> {
+        self.war_contributions()
+            .into_iter()
+            .find(|contribution| contribution.war_id == war_id)
+    }
+    
+    fn party_id(&self) -> Result<u64, Error> {
+        self.so_cache.party()
+            .map(|party| party.get_party_id())
+            .ok_or(Error::NotInParty)
+    }
+    
+    /// The persona name of `account_id`, if it has been looked up.
+    pub fn account_name(&self, account_id: u32) -> Option<&str> {
+        self.account_names.get(&account_id)?.as_deref()
+    }
+    
+    /// Forgets the account name lookups awaiting a response so their ids can be looked up
+    /// again, e.g. after the connection to the GC was lost.
+    pub fn clear_pending_account_names(&mut self) {
+        self.pending_account_names.clear();
+    }
+    
+    /// Handles a message received from the GC, updating the shared object cache and other 
+    /// state. Returns the events for messages which are not only state updates. A shared
+    /// object cache update gives one event for each change it made.
+    pub fn handle_message(
+        &mut self,
+        msg_type: i32,
+        payload: &[u8],
+    ) -> Result<Vec<GCEvent>, Error> {
+        let snapshot = self.so_cache_snapshot();
+        
+        if self.so_cache.handle_message(msg_type, payload)? {
+            return Ok(self.so_cache_events(snapshot));
+        }
+        
+        Ok(self.handle_item_message(msg_type, payload)?.into_iter().collect())
+    }
+    
+    fn handle_item_message(
+        &mut self,
+        msg_type: i32,
+        payload: &[u8],
+    ) -> Result<Option<GCEvent>, Error> {
+        match EGCItemMsg::from_i32(msg_type) {
+            Some(EGCItemMsg::k_EMsgGCBackpackSortFinished) => {
+                self.sorting_backpack = false;
+                Ok(Some(GCEvent::BackpackSortFinished))
+            },
+            Some(EGCItemMsg::k_EMsgGCUpdateItemSchema) => {
+                let message: CMsgUpdateItemSchema = parse_proto(payload)?;
+                let version = message.get_item_schema_version();
+                let items_game_url = message.get_items_game_url().to_string();
+                
+                self.latest_schema = Some((version, items_game_url.clone()));
+                
+                if !self.is_schema_outdated() {
+                    return Ok(None);
+                }
+                
+                Ok(Some(GCEvent::ItemSchemaUpdated {
+                    version,
+                    items_game_url,
+                }))
+            },
+            // Collection upgrades, Halloween offerings and stat clock crafts are answered with a
+            // CMsgCraftingResponse under the message type of the request.
+            Some(EGCItemMsg::k_EMsgGCCraftCollectionUpgrade) |
+            Some(EGCItemMsg::k_EMsgGCCraftHalloweenOffering) |
+            Some(EGCItemMsg::k_EMsgGCCraftCommonStatClock) => {
+                let message: CMsgCraftingResponse = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::CraftingResponse(CraftingResponse::from(&message))))
+            },
+            Some(EGCItemMsg::k_EMsgGCLookupMultipleAccountNamesResponse) => {
+                let message: CMsgLookupMultipleAccountNamesResponse = parse_proto(payload)?;
+                let names = message.get_accounts()
+                    .iter()
+                    .map(|account| (account.get_accountid(), account.get_persona().to_string()))
+                    .collect::<HashMap<_, _>>();
+                
+                // the response leaves out accounts the GC has no name for, so it is matched to
+                // the lookup containing the ids it does name, or the oldest one if it names none
+                let index = self.pending_account_names
+                    .iter()
+                    .position(|batch| names.keys().any(|account_id| batch.contains(account_id)))
+                    .unwrap_or_default();
+                
+                if let Some(batch) = self.pending_account_names.remove(index) {
+                    for account_id in batch {
+                        self.account_names.entry(account_id).or_insert(None);
+                    }
+                }
+                
+                for (account_id, name) in &names {
+                    self.account_names.insert(*account_id, Some(name.clone()));
+                }
+                
+                Ok(Some(GCEvent::AccountNames(names)))
+            },
+            Some(_) => Ok(None),
+            None => self.handle_tf_message(msg_type, payload),
+        }
+    }
+    
+    /// Handles a `CMsgGCNotificationQueue`, adding its notifications to the cache. `ETFGCMsg`
+    /// has no message type for the queue, so this is not reached through
+    /// [`TeamFortress2::handle_message`]. Returns [`GCEvent::Notifications`] for notifications
+    /// which were not already cached.
+    pub fn handle_notification_queue(&mut self, payload: &[u8]) -> Result<Vec<GCEvent>, Error> {
+        let queue: CMsgGCNotificationQueue = parse_proto(payload)?;
+        let snapshot = self.so_cache_snapshot();
+        
+        for notification in queue.notifications {
+            self.so_cache.insert_notification(notification);
+        }
+        
+        Ok(self.so_cache_events(snapshot))
+    }
+    
+    fn so_cache_snapshot(&self) -> SOCacheSnapshot {
+        SOCacheSnapshot {
+            notification_ids: self.so_cache
+                .notifications()
+                .map(|notification| notification.get_notification_id())
+                .collect(),
+            party_invite_ids: self.so_cache
+                .party_invites()
+                .map(|invite| invite.get_group_id())
+                .collect(),
+            party: self.party(),
+            queues: self.matchmaking_queues(),
+            lobbies: self.so_cache
+                .lobbies()
+                .map(|lobby| (lobby.get_lobby_id(), lobby.get_connect().to_string(), lobby.get_game_state()))
+                .collect(),
+        }
+    }
+    
+    /// Builds an event for each change in the cache since `snapshot` was taken.
+    fn so_cache_events(
+        &self,
+        snapshot: SOCacheSnapshot,
+    ) -> Vec<GCEvent> {
+        let mut events = Vec::new();
+        let notifications = self.so_cache
+            .notifications()
+            .filter(|notification| !snapshot.notification_ids.contains(&notification.get_notification_id()))
+            .map(Notification::from)
+            .collect::<Vec<_>>();
+        
+        if !notifications.is_empty() {
+            events.push(GCEvent::Notifications(notifications));
+        }
+        
+        let party_invites = self.so_cache
+            .party_invites()
+            .filter(|invite| !snapshot.party_invite_ids.contains(&invite.get_group_id()))
+            .map(PartyInvite::from)
+            .collect::<Vec<_>>();
+        
+        if !party_invites.is_empty() {
+            events.push(GCEvent::PartyInvites(party_invites));
+        }
+        
+        party_events(snapshot.party, self.party(), &mut events);
+        
+        let queues = self.matchmaking_queues();
+        let joined = queues
+            .iter()
+            .filter(|queue| !snapshot.queues.contains(queue))
+            .copied()
+            .collect::<Vec<_>>();
+        let left = snapshot.queues
+            .into_iter()
+            .filter(|queue| !queues.contains(queue))
+            .collect::<Vec<_>>();
+        
+        if !joined.is_empty() || !left.is_empty() {
+            events.push(GCEvent::MatchmakingQueuesChanged {
+                joined,
+                left,
+            });
+        }
+        
+        let changed_lobbies = self.so_cache
+            .lobbies()
+            .filter(|lobby| !snapshot.lobbies.iter().any(|(lobby_id, connect, game_state)| {
+                *lobby_id == lobby.get_lobby_id()
+                    && connect == lobby.get_connect()
+                    && *game_state == lobby.get_game_state()
+            }))
+            .map(|lobby| GCEvent::LobbyChanged(Box::new(Lobby::from(lobby))));
+        
+        events.extend(changed_lobbies);
+        
+        let removed_lobbies = snapshot.lobbies
+            .into_iter()
+            .filter(|(lobby_id, _connect, _game_state)| self.so_cache.lobby(*lobby_id).is_none())
+            .map(|(lobby_id, _connect, _game_state)| GCEvent::LobbyRemoved(lobby_id));
+        
+        events.extend(removed_lobbies);
+        events
+    }
+    
+    fn handle_tf_message(
+        &mut self,
+        msg_type: i32,
+        payload: &[u8],
+    ) -> Result<Option<GCEvent>, Error> {
+        match ETFGCMsg::from_i32(msg_type) {
+            Some(ETFGCMsg::k_EMsgGC_Client2GCEconPreviewDataBlockResponse) => {
+                let message: CMsgGC_Client2GCEconPreviewDataBlockResponse = parse_proto(payload)?;
+                let item = Item::new(message.get_iteminfo().get_econitem(), self.schema());
+                
+                Ok(Some(GCEvent::ItemInspected(Box::new(item))))
+            },
+            Some(ETFGCMsg::k_EMsgGCMatchmakingProgress) => {
+                let message: CMsgMatchmakingProgress = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::MatchmakingProgress(MatchmakingProgress::from(&message))))
+            },
+            Some(ETFGCMsg::k_EMsgGCMatchMakerStatsResponse) => {
+                let message: CMsgGCMatchMakerStatsResponse = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::MatchmakerStats(Box::new(MatchmakerStats::from(&message)))))
+            },
+            // Quest map actions are answered with a CMsgGCQuestResponse under the message
+            // type of the request.
+            Some(ETFGCMsg::k_EMsgGC_QuestMapUnlockNode) => {
+                let message: CMsgGCQuestResponse = parse_proto(payload)?;
+                let response = QuestResponse::new(QuestAction::UnlockNode, &message);
+                
+                Ok(Some(GCEvent::QuestResponse(response)))
+            },
+            Some(ETFGCMsg::k_EMsgGC_QuestMapPurchaseReward) => {
+                let message: CMsgGCQuestResponse = parse_proto(payload)?;
+                let response = QuestResponse::new(QuestAction::PurchaseReward, &message);
+                
+                Ok(Some(GCEvent::QuestResponse(response)))
+            },
+            Some(ETFGCMsg::k_EMsgGCQuestNodeTurnIn) => {
+                let message: CMsgGCQuestResponse = parse_proto(payload)?;
+                let response = QuestResponse::new(QuestAction::TurnIn, &message);
+                
+                Ok(Some(GCEvent::QuestResponse(response)))
+            },
+            Some(ETFGCMsg::k_EMsgGC_War_GlobalStatsResponse) => {
+                let message: CGCMsgGC_War_GlobalStatsResponse = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::WarGlobalStats(WarStats::from(&message))))
+            },
+            Some(ETFGCMsg::k_EMsgGC_War_IndividualUpdate) => {
+                let message: CGCMsgGC_War_IndividualUpdate = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::WarScoreUpdated(WarScoreUpdate::from(&message))))
+            },
+            Some(ETFGCMsg::k_EMsgGC_DuckLeaderboard_IndividualUpdate) => {
+                let message: CGCMsgGC_PlayerDuckLeaderboard_IndividualUpdate = parse_proto(payload)?;
+                let update = DuckLeaderboardUpdate::from(&message);
+                
+                Ok(Some(GCEvent::DuckLeaderboardUpdated(update)))
+            },
+            Some(ETFGCMsg::k_EMsgGCKickedFromMatchmakingQueue) => {
+                Ok(Some(GCEvent::KickedFromMatchmakingQueue))
+            },
+            Some(ETFGCMsg::k_EMsgGC_AcceptLobbyInviteReply) => {
+                Ok(Some(GCEvent::LobbyInviteAccepted))
+            },
+            Some(ETFGCMsg::k_EMsgGCParty_ChatMsg) => {
+                let message: CMsgPartyChatMsg = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::PartyChat(PartyChatMessage::from(&message))))
+            },
+            Some(ETFGCMsg::k_EMsgGCParty_SetOptionsResponse) => {
+                Ok(Some(GCEvent::PartyOptionsSet))
+            },
+            Some(ETFGCMsg::k_EMsgGCParty_QueueForMatchResponse) => {
+                let _response: CMsgPartyQueueForMatchResponse = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::QueuedForMatch))
+            },
+            Some(ETFGCMsg::k_EMsgGCParty_RemoveFromQueueResponse) => {
+                let _response: CMsgPartyRemoveFromQueueResponse = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::RemovedFromQueue))
+            },
+            Some(ETFGCMsg::k_EMsgGCParty_QueueForStandbyResponse) => {
+                let _response: CMsgPartyQueueForStandbyResponse = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::QueuedForStandby))
+            },
+            Some(ETFGCMsg::k_EMsgGCParty_RemoveFromStandbyQueueResponse) => {
+                let _response: CMsgPartyRemoveFromStandbyQueueResponse = parse_proto(payload)?;
+                
+                Ok(Some(GCEvent::RemovedFromStandbyQueue))
+            },
+            Some(ETFGCMsg::k_EMsgGC_NotificationAcknowledgeReply) => {
+                let _reply: CMsgNotificationAcknowledgeReply = parse_proto(payload)?;
+                
+                // the reply does not name the notification, so acknowledgements are matched in
+                // the order they were sent
+                Ok(self.pending_notification_acks.pop_front().map(|notification_id| {
+                    self.so_cache.remove_notification(notification_id);
+                    GCEvent::NotificationAcknowledged(notification_id)
+                }))
+            },
+            _ => Ok(None),
         }
     }
     
@@ -162,6 +766,16 @@ impl TeamFortress2 {
         self.send(connection, msg).await
     }
     
+    /// Picks items from the cached inventory for the inputs of the dynamic recipe tool
+    /// `tool_id`, e.g. a chemistry set. Send the planned components with
+    /// [`TeamFortress2::fulfill_recipe`].
+    pub fn plan_dynamic_recipe(&self, tool_id: u64) -> Result<DynamicRecipePlan, Error> {
+        let tool = self.so_cache.item(tool_id)
+            .ok_or(Error::UnknownItem(tool_id))?;
+        
+        Ok(dynamic_recipe::plan(tool, self.so_cache.items(), self.schema()))
+    }
+    
     pub async fn delete_item(
         &mut self,
         connection: &mut Connection,
@@ -245,105 +859,1492 @@ impl TeamFortress2 {
         self.send(connection, msg).await
     }
     
-    pub async fn craft(
+    /// Equips `item_id` in `slot` for `class`.
+    pub async fn equip(
         &mut self,
         connection: &mut Connection,
-        item_ids: &[u64],
+        item_id: u64,
+        class: TFClass,
+        slot: LoadoutSlot,
     ) -> Result<u64, NetworkError> {
-        self.craft_recipe(connection,-2, item_ids).await
+        let msgtype = EGCItemMsg::k_EMsgGCAdjustItemEquippedState as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgAdjustItemEquippedState::new();
+        
+        message.set_item_id(item_id);
+        message.set_new_class(class as u32);
+        message.set_new_slot(slot as u32);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
     }
     
-    pub async fn craft_recipe(
+    /// Clears `slot` for `class`, equipping the stock item.
+    pub async fn unequip(
         &mut self,
         connection: &mut Connection,
-        recipe: i16,
-        item_ids: &[u64],
+        class: TFClass,
+        slot: LoadoutSlot,
     ) -> Result<u64, NetworkError> {
-        let msgtype = EGCItemMsg::k_EMsgGCCraft as i32;
-        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, false);
-        let mut buff = BytesMut::with_capacity(2 + 2 + (8 * item_ids.len()));
-        let mut writer = (&mut buff).writer();
-        
-        writer.write_i16::<LittleEndian>(recipe)?;
-        writer.write_i16::<LittleEndian>(item_ids.len() as i16)?;
+        self.equip(connection, ITEMID_NONE, class, slot).await
+    }
+    
+    /// Switches `class` to loadout preset `preset_id` (0 to 3 for presets A to D).
+    pub async fn select_preset(
+        &mut self,
+        connection: &mut Connection,
+        class: TFClass,
+        preset_id: u32,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = EGCItemMsg::k_EMsgGCPresets_SelectPresetForClass as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgSelectPresetForClass::new();
         
-        for item_id in item_ids {
-            writer.write_u64::<LittleEndian>(*item_id)?;
-        }
+        message.set_class_id(class as u32);
+        message.set_preset_id(preset_id);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Sets the item in `slot` of preset `preset_id` for `class` without selecting the preset.
+    pub async fn set_preset_item(
+        &mut self,
+        connection: &mut Connection,
+        class: TFClass,
+        preset_id: u32,
+        slot: LoadoutSlot,
+        item_id: u64,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = EGCItemMsg::k_EMsgGCPresets_SetItemPosition as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgSetPresetItemPosition::new();
         
-        msg.set_payload(self.payload(
-            buff,
+        message.set_class_id(class as u32);
+        message.set_preset_id(preset_id);
+        message.set_slot_id(slot as u32);
+        message.set_item_id(item_id);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
         )?);
         self.send(connection, msg).await
     }
     
-    fn proto_payload<Msg: Message>(
+    /// Asks the GC to sort the backpack. A [`GCEvent::BackpackSortFinished`] is produced by 
+    /// [`TeamFortress2::handle_message`] once the GC is done.
+    pub async fn sort_backpack(
         &mut self,
-        message: Msg,
-        msg_type: i32,
-    ) -> Result<Vec<u8>, std::io::Error> {
-        fn encode_size(source_job_id: u64) -> usize {
-            let mut proto_header = CMsgProtoBufHeader::new();
-            proto_header.set_jobid_source(source_job_id);
-            
-            4 + 4 + proto_header.compute_size() as usize
-        }
+        connection: &mut Connection,
+        sort_type: SortType,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = EGCItemMsg::k_EMsgGCSortItems as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgSortItems::new();
         
-        fn write_header<W: WriteBytesExt>(
-            writer: &mut W,
-            msg_type: i32,
-            source_job_id: u64,
-        ) -> std::io::Result<()> {
-            let mut proto_header = CMsgProtoBufHeader::new();
-            // proto_header.set_jobid_target(self.target_job_id);
-            proto_header.set_jobid_source(source_job_id);
-            
-            writer.write_u32::<LittleEndian>(msg_type as u32 | PROTO_MASK)?;
-            writer.write_u32::<LittleEndian>(proto_header.compute_size())?;
-            proto_header.write_to_writer(writer)?;
-            Ok(())
-        }
+        message.set_sort_type(sort_type as u32);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
         
-        let source_job_id = self.next_jobid();
-        let mut buff = BytesMut::with_capacity(
-            encode_size(source_job_id) + message.compute_size() as usize
-        );
-        let mut writer = (&mut buff).writer();
+        let jobid = self.send(connection, msg).await?;
         
-        write_header(&mut writer, msg_type, source_job_id)?;
-        message.write_to_writer(&mut writer)?;
-    
-        Ok(buff.to_vec())
+        self.sorting_backpack = true;
+        Ok(jobid)
     }
     
-    fn payload(
+    /// Lays out the cached backpack in the order given by `compare`, moving only the items 
+    /// whose position changes. Returns `None` if the backpack is already in order.
+    pub async fn organize_backpack<F>(
         &mut self,
-        message: BytesMut,
-    ) -> Result<Vec<u8>, std::io::Error> {
-        fn encode_size() -> usize {
-            2 + 8 + 8 + 4
-        }
+        connection: &mut Connection,
+        compare: F,
+    ) -> Result<Option<u64>, NetworkError>
+    where
+        F: FnMut(&CSOEconItem, &CSOEconItem) -> Ordering,
+    {
+        let set_item_positions = backpack::organize(self.so_cache.items(), compare);
         
-        fn write_header<W: WriteBytesExt>(
-            writer: &mut W,
-            source_job_id: u64,
-        ) -> std::io::Result<()> {
-            writer.write_u16::<LittleEndian>(1)?;
-            writer.write_u64::<LittleEndian>(JOBID_NONE)?;
-            writer.write_u64::<LittleEndian>(source_job_id)?;
-            Ok(())
+        if set_item_positions.is_empty() {
+            return Ok(None);
         }
         
-        let source_job_id = self.next_jobid();
-        let mut buff = BytesMut::with_capacity(
-            encode_size() + message.len() as usize
-        );
-        let mut writer = (&mut buff).writer();
-        
-        write_header(&mut writer, source_job_id)?;
+        let jobid = self.set_positions(connection, set_item_positions).await?;
         
-        writer.write(&message[..])?;
-    
-        Ok(buff.to_vec())
+        Ok(Some(jobid))
     }
-}
+    
+    /// Acknowledges a notification so that it is no longer shown. It is removed from the cache
+    /// once the GC replies with [`GCEvent::NotificationAcknowledged`].
+    pub async fn acknowledge_notification(
+        &mut self,
+        connection: &mut Connection,
+        notification_id: u64,
+    ) -> Result<u64, Error> {
+        let notification = self.so_cache.notification(notification_id)
+            .ok_or(Error::UnknownNotification(notification_id))?;
+        let msgtype = ETFGCMsg::k_EMsgGC_NotificationAcknowledge as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgNotificationAcknowledge::new();
+        
+        message.set_account_id(notification.get_account_id());
+        message.set_notification_id(notification_id);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        let jobid = self.send(connection, msg).await?;
+        
+        self.pending_notification_acks.push_back(notification_id);
+        
+        Ok(jobid)
+    }
+    
+    /// Requests the full item data for the `S`/`M`, `A` and `D` parameters of an inspect link,
+    /// which can be parsed with [`crate::inspect::InspectLink`]. The item arrives as
+    /// [`GCEvent::ItemInspected`].
+    pub async fn inspect_item(
+        &mut self,
+        connection: &mut Connection,
+        owner: InspectOwner,
+        asset_id: u64,
+        d: u64,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGC_Client2GCEconPreviewDataBlockRequest as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgGC_Client2GCEconPreviewDataBlockRequest::new();
+        
+        match owner {
+            InspectOwner::SteamId(steamid) => message.set_param_s(steamid),
+            InspectOwner::MarketListing(listing_id) => message.set_param_m(listing_id),
+        }
+        
+        message.set_param_a(asset_id);
+        message.set_param_d(d);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Invites a player to the party. If the client is not in a party the GC creates one. Set
+    /// `expecting_request_to_join` when accepting a request from the player.
+    pub async fn invite_to_party(
+        &mut self,
+        connection: &mut Connection,
+        steamid: u64,
+        expecting_request_to_join: bool,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_InvitePlayer as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartyInvitePlayer::new();
+        
+        message.set_party_id(self.party_id().unwrap_or_default());
+        message.set_player_id(steamid);
+        message.set_expecting_request_to_join(expecting_request_to_join);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Asks to join the party of a player. Set `expecting_invite` when accepting an invite to
+    /// the party.
+    pub async fn request_join_party(
+        &mut self,
+        connection: &mut Connection,
+        steamid: u64,
+        party_id: u64,
+        expecting_invite: bool,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_RequestJoinPlayer as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartyRequestJoinPlayer::new();
+        
+        message.set_current_party_id(self.party_id().unwrap_or_default());
+        message.set_join_player_id(steamid);
+        message.set_join_party_id(party_id);
+        message.set_expecting_invite(expecting_invite);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Kicks a member from the party. Kicking ourselves leaves the party.
+    pub async fn kick_party_member(
+        &mut self,
+        connection: &mut Connection,
+        steamid: u64,
+    ) -> Result<u64, Error> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_KickMember as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartyKickMember::new();
+        
+        message.set_party_id(self.party_id()?);
+        message.set_target_id(steamid);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    pub async fn promote_to_party_leader(
+        &mut self,
+        connection: &mut Connection,
+        steamid: u64,
+    ) -> Result<u64, Error> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_PromoteToLeader as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartyPromoteToLeader::new();
+        
+        message.set_party_id(self.party_id()?);
+        message.set_new_leader_id(steamid);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// Sends a chat message to the party. Messages arrive as [`GCEvent::PartyChat`].
+    pub async fn send_party_chat(
+        &mut self,
+        connection: &mut Connection,
+        text: &str,
+    ) -> Result<u64, Error> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_SendChat as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartySendChat::new();
+        
+        message.set_party_id(self.party_id()?);
+        message.set_msg(text.to_string());
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    pub async fn set_party_options(
+        &mut self,
+        connection: &mut Connection,
+        options: &PartyOptions,
+    ) -> Result<u64, Error> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_SetOptions as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartySetOptions::new();
+        
+        message.set_party_id(self.party_id()?);
+        message.set_options(options.into());
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// Queues the party for a match. The GC replies with [`GCEvent::QueuedForMatch`], progress
+    /// arrives as [`GCEvent::MatchmakingProgress`] and the party's queues as
+    /// [`GCEvent::MatchmakingQueuesChanged`].
+    pub async fn queue_for_match(
+        &mut self,
+        connection: &mut Connection,
+        match_group: ETFMatchGroup,
+        options: &PartyOptions,
+    ) -> Result<u64, Error> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_QueueForMatch as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartyQueueForMatch::new();
+        
+        message.set_party_id(self.party_id()?);
+        message.set_final_options(options.into());
+        message.set_match_group(match_group);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// Removes the party from the queue for `match_group`. The GC replies with
+    /// [`GCEvent::RemovedFromQueue`].
+    pub async fn leave_queue(
+        &mut self,
+        connection: &mut Connection,
+        match_group: ETFMatchGroup,
+    ) -> Result<u64, Error> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_RemoveFromQueue as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartyRemoveFromQueue::new();
+        
+        message.set_party_id(self.party_id()?);
+        message.set_match_group(match_group);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// Queues the party to join the lobby a party member is playing in. The GC replies with
+    /// [`GCEvent::QueuedForStandby`].
+    pub async fn queue_for_standby(
+        &mut self,
+        connection: &mut Connection,
+        lobby_id: u64,
+    ) -> Result<u64, Error> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_QueueForStandby as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartyQueueForStandby::new();
+        
+        message.set_party_id(self.party_id()?);
+        message.set_party_lobby_id(lobby_id);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// The GC replies with [`GCEvent::RemovedFromStandbyQueue`].
+    pub async fn leave_standby_queue(
+        &mut self,
+        connection: &mut Connection,
+    ) -> Result<u64, Error> {
+        let msgtype = ETFGCMsg::k_EMsgGCParty_RemoveFromStandbyQueue as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgPartyRemoveFromStandbyQueue::new();
+        
+        message.set_party_id(self.party_id()?);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// Leaves all matchmaking. `explicit_abandon` abandons a match in progress.
+    pub async fn exit_matchmaking(
+        &mut self,
+        connection: &mut Connection,
+        explicit_abandon: bool,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGCExitMatchmaking as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgExitMatchmaking::new();
+        
+        message.set_explicit_abandon(explicit_abandon);
+        message.set_party_id(self.party_id().unwrap_or_default());
+        
+        if let Some(lobby_id) = self.party().and_then(|party| party.associated_lobby_id) {
+            message.set_lobby_id(lobby_id);
+        }
+        
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Accepts an invite to a lobby, abandoning the match currently being played, if any.
+    pub async fn accept_lobby_invite(
+        &mut self,
+        connection: &mut Connection,
+        lobby_id: u64,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGC_AcceptLobbyInvite as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgAcceptLobbyInvite::new();
+        
+        message.set_invited_lobby_id(lobby_id);
+        
+        if let Some(lobby) = self.lobby().filter(|lobby| lobby.lobby_id != lobby_id) {
+            if lobby.game_state != TF_GC_GameState::TF_GC_GAMESTATE_POST_GAME {
+                message.set_abandoning_match_id(lobby.match_id);
+            }
+        }
+        
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Abandons the match in progress. Abandoning a competitive match results in a
+    /// matchmaking ban.
+    pub async fn abandon_current_game(
+        &mut self,
+        connection: &mut Connection,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGCAbandonCurrentGame as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, false);
+        
+        msg.set_payload(self.payload(
+            BytesMut::new(),
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Unlocks a quest map node, choosing `quest_def_index` as its contract. The reply arrives
+    /// as [`GCEvent::QuestResponse`].
+    pub async fn unlock_quest_node(
+        &mut self,
+        connection: &mut Connection,
+        node_def_index: u32,
+        quest_def_index: u32,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGC_QuestMapUnlockNode as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgGCQuestMapUnlockNode::new();
+        
+        message.set_node_defindex(node_def_index);
+        message.set_quest_defindex(quest_def_index);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Turns in the completed contract on a quest map node. The reply arrives as
+    /// [`GCEvent::QuestResponse`].
+    pub async fn turn_in_quest_node(
+        &mut self,
+        connection: &mut Connection,
+        node_def_index: u32,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGCQuestNodeTurnIn as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgGCQuestNodeTurnIn::new();
+        
+        message.set_node_defindex(node_def_index);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Spends quest map credits (blood money) on a reward from the store. The reply arrives as
+    /// [`GCEvent::QuestResponse`].
+    pub async fn purchase_quest_reward(
+        &mut self,
+        connection: &mut Connection,
+        store_item_def_index: u32,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGC_QuestMapPurchaseReward as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgGCQuestMapPurchaseReward::new();
+        
+        message.set_store_item_defindex(store_item_def_index);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Discards a contract. The GC removes it from the shared object cache.
+    pub async fn discard_quest(
+        &mut self,
+        connection: &mut Connection,
+        quest_id: u64,
+    ) -> Result<u64, Error> {
+        if self.so_cache.quest(quest_id).is_none() {
+            return Err(Error::UnknownQuest(quest_id));
+        }
+        
+        let msgtype = EGCItemMsg::k_EMsgGCQuestDiscard_Request as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgGCQuestDiscard_Request::new();
+        
+        message.set_quest_id(quest_id);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// Requests matchmaking population per match group, datacenter and map. The stats arrive
+    /// as [`GCEvent::MatchmakerStats`].
+    pub async fn request_matchmaker_stats(
+        &mut self,
+        connection: &mut Connection,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGCRequestMatchMakerStats as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let message = CMsgGCRequestMatchMakerStats::new();
+        
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Reports the client's ping to each datacenter, which the GC uses to pick servers when
+    /// matchmaking.
+    pub async fn report_datacenter_pings(
+        &mut self,
+        connection: &mut Connection,
+        entries: &[DataCenterPing],
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGCDataCenterPing_Update as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgGCDataCenterPing_Update::new();
+        
+        message.set_pingdata(entries.iter().map(Into::into).collect());
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Requests the match history for `match_group`. The results are added to the shared
+    /// object cache and can be read with [`TeamFortress2::match_history`].
+    pub async fn load_match_history(
+        &mut self,
+        connection: &mut Connection,
+        match_group: ETFMatchGroup,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGCMatchHistoryLoad as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgGCMatchHistoryLoad::new();
+        
+        message.set_match_group(match_group);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Acknowledges the experience earned in `match_group`. The GC removes the pending
+    /// experience sources and updates the acknowledged ratings.
+    pub async fn acknowledge_xp(
+        &mut self,
+        connection: &mut Connection,
+        match_group: ETFMatchGroup,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGC_AcknowledgeXP as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgAcknowledgeXP::new();
+        
+        message.set_match_group(match_group.value());
+        
+        if xp::is_casual(match_group) {
+            if let Some(progression) = self.casual_progression() {
+                message.set_predicted_experience(progression.experience);
+            }
+        }
+        
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Acknowledges the experience in each match group with pending experience. Returns the
+    /// job id of each message sent.
+    pub async fn acknowledge_all_xp(
+        &mut self,
+        connection: &mut Connection,
+    ) -> Result<Vec<u64>, NetworkError> {
+        let mut match_groups = self.so_cache
+            .xp_sources()
+            .filter_map(|source| ETFMatchGroup::from_i32(source.get_match_group()))
+            .collect::<Vec<_>>();
+        let mut job_ids = Vec::with_capacity(match_groups.len());
+        
+        match_groups.sort_by_key(|match_group| match_group.value());
+        match_groups.dedup();
+        
+        for match_group in match_groups {
+            job_ids.push(self.acknowledge_xp(connection, match_group).await?);
+        }
+        
+        Ok(job_ids)
+    }
+    
+    /// Joins `side` in the war `war_id`. The side is added to the shared object cache and can
+    /// be read with [`TeamFortress2::war_contribution`].
+    pub async fn join_war(
+        &mut self,
+        connection: &mut Connection,
+        war_id: u32,
+        side: WarSide,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGC_War_JoinWar as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CGCMsgGC_War_JoinWar::new();
+        
+        message.set_war_id(war_id);
+        message.set_affiliation(side.into());
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Requests the score of each side in the war `war_id`. The scores arrive as
+    /// [`GCEvent::WarGlobalStats`].
+    pub async fn war_global_stats(
+        &mut self,
+        connection: &mut Connection,
+        war_id: u32,
+    ) -> Result<u64, NetworkError> {
+        let msgtype = ETFGCMsg::k_EMsgGC_War_RequestGlobalStats as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CGCMsgGC_War_RequestGlobalStats::new();
+        
+        message.set_war_id(war_id);
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Looks up the persona names of `account_ids`. Names which were already looked up are
+    /// returned immediately. The rest are requested in batches of
+    /// [`ACCOUNT_NAME_LOOKUP_BATCH_SIZE`], skipping ids with a lookup in flight or which the GC
+    /// had no name for, and arrive as [`GCEvent::AccountNames`] from
+    /// [`TeamFortress2::handle_message`], after which they are available from
+    /// [`TeamFortress2::account_name`].
+    pub async fn lookup_account_names(
+        &mut self,
+        connection: &mut Connection,
+        account_ids: &[u32],
+    ) -> Result<HashMap<u32, String>, NetworkError> {
+        let (names, batches) = self.account_name_batches(account_ids);
+        
+        for batch in batches {
+            let msgtype = EGCItemMsg::k_EMsgGCLookupMultipleAccountNames as i32;
+            let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+            let mut message = CMsgLookupMultipleAccountNames::new();
+            
+            message.set_accountids(batch.clone());
+            msg.set_payload(self.proto_payload(
+                message,
+                msgtype,
+            )?);
+            self.send(connection, msg).await?;
+            self.pending_account_names.push_back(batch);
+        }
+        
+        Ok(names)
+    }
+    
+    /// Splits `account_ids` into the cached names and batches of ids which are neither cached
+    /// nor pending.
+    fn account_name_batches(&self, account_ids: &[u32]) -> (HashMap<u32, String>, Vec<Vec<u32>>) {
+        let pending = self.pending_account_names
+            .iter()
+            .flatten()
+            .collect::<HashSet<_>>();
+        let mut names = HashMap::new();
+        let mut missing = Vec::new();
+        
+        for account_id in account_ids {
+            if let Some(name) = self.account_names.get(account_id) {
+                if let Some(name) = name {
+                    names.insert(*account_id, name.clone());
+                }
+            } else if !pending.contains(account_id) && !missing.contains(account_id) {
+                missing.push(*account_id);
+            }
+        }
+        
+        let batches = missing
+            .chunks(ACCOUNT_NAME_LOOKUP_BATCH_SIZE)
+            .map(<[u32]>::to_vec)
+            .collect();
+        
+        (names, batches)
+    }
+    
+    /// Acknowledges new items, placing them in the first free backpack slots. Items which are 
+    /// already placed are skipped. Returns `None` if there was nothing to acknowledge.
+    pub async fn acknowledge_items(
+        &mut self,
+        connection: &mut Connection,
+        item_ids: &[u64],
+    ) -> Result<Option<u64>, Error> {
+        let mut items = Vec::with_capacity(item_ids.len());
+        
+        for (i, item_id) in item_ids.iter().enumerate() {
+            let item = self.so_cache.item(*item_id)
+                .ok_or(Error::UnknownItem(*item_id))?;
+            
+            if InventoryPosition::from(item).is_new() && !item_ids[..i].contains(item_id) {
+                items.push(item);
+            }
+        }
+        
+        if items.is_empty() {
+            return Ok(None);
+        }
+        
+        let positions = backpack::free_positions(self.so_cache.items(), items.len());
+        let messages = items
+            .iter()
+            .map(|item| {
+                let mut message = CMsgItemAcknowledged::new();
+                
+                message.set_account_id(item.get_account_id());
+                message.set_inventory(item.get_inventory());
+                message.set_def_index(item.get_def_index());
+                message.set_quality(item.get_quality());
+                message.set_origin(item.get_origin());
+                
+                message
+            })
+            .collect::<Vec<_>>();
+        let set_item_positions = items
+            .iter()
+            .zip(positions)
+            .map(|(item, position)| request::SetItemPosition {
+                item_id: item.get_id(),
+                position,
+            })
+            .collect::<Vec<_>>();
+        
+        for message in messages {
+            let msgtype = EGCItemMsg::k_EMsgGCItemAcknowledged as i32;
+            let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+            
+            msg.set_payload(self.proto_payload(
+                message,
+                msgtype,
+            )?);
+            self.send(connection, msg).await?;
+        }
+        
+        let jobid = self.set_positions(connection, set_item_positions).await?;
+        
+        Ok(Some(jobid))
+    }
+    
+    pub async fn craft(
+        &mut self,
+        connection: &mut Connection,
+        item_ids: &[u64],
+    ) -> Result<u64, NetworkError> {
+        self.craft_recipe(connection, RECIPE_CUSTOM, item_ids).await
+    }
+    
+    /// Crafts `items` using `recipe` after checking them against the recipe's inputs.
+    pub async fn craft_with(
+        &mut self,
+        connection: &mut Connection,
+        recipe: Recipe,
+        items: &[request::CraftItem],
+    ) -> Result<u64, Error> {
+        recipe.validate(items)?;
+        
+        let item_ids = items
+            .iter()
+            .map(|item| item.item_id)
+            .collect::<Vec<_>>();
+        
+        Ok(self.craft_recipe(connection, recipe.id(), &item_ids).await?)
+    }
+    
+    pub async fn craft_recipe(
+        &mut self,
+        connection: &mut Connection,
+        recipe: i16,
+        item_ids: &[u64],
+    ) -> Result<u64, NetworkError> {
+        let msgtype = EGCItemMsg::k_EMsgGCCraft as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, false);
+        let mut buff = BytesMut::with_capacity(2 + 2 + (8 * item_ids.len()));
+        let mut writer = (&mut buff).writer();
+        
+        writer.write_i16::<LittleEndian>(recipe)?;
+        writer.write_i16::<LittleEndian>(item_ids.len() as i16)?;
+        
+        for item_id in item_ids {
+            writer.write_u64::<LittleEndian>(*item_id)?;
+        }
+        
+        msg.set_payload(self.payload(
+            buff,
+        )?);
+        self.send(connection, msg).await
+    }
+    
+    /// Trades up [`COLLECTION_UPGRADE_ITEM_COUNT`] items of the same grade for one item of the 
+    /// next grade. The created item arrives as [`GCEvent::CraftingResponse`].
+    pub async fn craft_collection_upgrade(
+        &mut self,
+        connection: &mut Connection,
+        item_ids: &[u64],
+    ) -> Result<u64, Error> {
+        check_craft_items(None, item_ids, COLLECTION_UPGRADE_ITEM_COUNT)?;
+        
+        let msgtype = EGCItemMsg::k_EMsgGCCraftCollectionUpgrade as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgCraftCollectionUpgrade::new();
+        
+        message.set_item_id(item_ids.to_vec());
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// Offers [`HALLOWEEN_OFFERING_ITEM_COUNT`] items using `tool_id`. The created item
+    /// arrives as [`GCEvent::CraftingResponse`].
+    pub async fn craft_halloween_offering(
+        &mut self,
+        connection: &mut Connection,
+        tool_id: u64,
+        item_ids: &[u64],
+    ) -> Result<u64, Error> {
+        check_craft_items(Some(tool_id), item_ids, HALLOWEEN_OFFERING_ITEM_COUNT)?;
+        
+        let msgtype = EGCItemMsg::k_EMsgGCCraftHalloweenOffering as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgCraftHalloweenOffering::new();
+        
+        message.set_tool_id(tool_id);
+        message.set_item_id(item_ids.to_vec());
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// Crafts a stat clock from [`STAT_CLOCK_ITEM_COUNT`] items using `tool_id`. The stat clock
+    /// arrives as [`GCEvent::CraftingResponse`].
+    pub async fn craft_stat_clock(
+        &mut self,
+        connection: &mut Connection,
+        tool_id: u64,
+        item_ids: &[u64],
+    ) -> Result<u64, Error> {
+        check_craft_items(Some(tool_id), item_ids, STAT_CLOCK_ITEM_COUNT)?;
+        
+        let msgtype = EGCItemMsg::k_EMsgGCCraftCommonStatClock as i32;
+        let mut msg = ClientToGCMessage::new(Self::APPID, msgtype, true);
+        let mut message = CMsgCraftCommonStatClock::new();
+        
+        message.set_tool_id(tool_id);
+        message.set_item_id(item_ids.to_vec());
+        msg.set_payload(self.proto_payload(
+            message,
+            msgtype,
+        )?);
+        Ok(self.send(connection, msg).await?)
+    }
+    
+    /// Plans the crafts needed to hold at least `target` of each metal type using the metal in
+    /// the shared object cache. Run the plan with [`TeamFortress2::craft_metal`].
+    pub fn plan_metal(&self, target: MetalCounts) -> Result<MetalCrafter, Error> {
+        MetalCrafter::new(MetalInventory::from_so_cache(&self.so_cache), target)
+    }
+    
+    /// Sends the next craft of `crafter`. Nothing is sent while the previous craft's
+    /// [`crate::response::CraftResponse`] has not been given to
+    /// [`MetalCrafter::handle_response`], or once all crafts are done.
+    pub async fn craft_metal(
+        &mut self,
+        connection: &mut Connection,
+        crafter: &mut MetalCrafter,
+    ) -> Result<MetalCraftStatus, Error> {
+        let (recipe, item_ids) = match crafter.next_craft() {
+            MetalCraftStep::Craft { recipe, item_ids } => (recipe, item_ids),
+            MetalCraftStep::Waiting => return Ok(MetalCraftStatus::Waiting),
+            MetalCraftStep::Done => return Ok(MetalCraftStatus::Done),
+        };
+        let jobid = self.craft_recipe(connection, recipe.id(), &item_ids).await?;
+        
+        crafter.start_craft();
+        Ok(MetalCraftStatus::Sent(jobid))
+    }
+    
+    fn proto_payload<Msg: Message>(
+        &mut self,
+        message: Msg,
+        msg_type: i32,
+    ) -> Result<Vec<u8>, std::io::Error> {
+        fn encode_size(source_job_id: u64) -> usize {
+            let mut proto_header = CMsgProtoBufHeader::new();
+            proto_header.set_jobid_source(source_job_id);
+            
+            4 + 4 + proto_header.compute_size() as usize
+        }
+        
+        fn write_header<W: WriteBytesExt>(
+            writer: &mut W,
+            msg_type: i32,
+            source_job_id: u64,
+        ) -> std::io::Result<()> {
+            let mut proto_header = CMsgProtoBufHeader::new();
+            // proto_header.set_jobid_target(self.target_job_id);
+            proto_header.set_jobid_source(source_job_id);
+            
+            writer.write_u32::<LittleEndian>(msg_type as u32 | PROTO_MASK)?;
+            writer.write_u32::<LittleEndian>(proto_header.compute_size())?;
+            proto_header.write_to_writer(writer)?;
+            Ok(())
+        }
+        
+        let source_job_id = self.next_jobid();
+        let mut buff = BytesMut::with_capacity(
+            encode_size(source_job_id) + message.compute_size() as usize
+        );
+        let mut writer = (&mut buff).writer();
+        
+        write_header(&mut writer, msg_type, source_job_id)?;
+        message.write_to_writer(&mut writer)?;
+    
+        Ok(buff.to_vec())
+    }
+    
+    fn payload(
+        &mut self,
+        message: BytesMut,
+    ) -> Result<Vec<u8>, std::io::Error> {
+        fn encode_size() -> usize {
+            2 + 8 + 8 + 4
+        }
+        
+        fn write_header<W: WriteBytesExt>(
+            writer: &mut W,
+            source_job_id: u64,
+        ) -> std::io::Result<()> {
+            writer.write_u16::<LittleEndian>(1)?;
+            writer.write_u64::<LittleEndian>(JOBID_NONE)?;
+            writer.write_u64::<LittleEndian>(source_job_id)?;
+            Ok(())
+        }
+        
+        let source_job_id = self.next_jobid();
+        let mut buff = BytesMut::with_capacity(
+            encode_size() + message.len() as usize
+        );
+        let mut writer = (&mut buff).writer();
+        
+        write_header(&mut writer, source_job_id)?;
+        
+        writer.write(&message[..])?;
+    
+        Ok(buff.to_vec())
+    }
+}
+
+/// Checks that exactly `expected` distinct items are given and that the tool is not one of them.
+fn check_craft_items(
+    tool_id: Option<u64>,
+    item_ids: &[u64],
+    expected: usize,
+) -> Result<(), Error> {
+    if item_ids.len() != expected {
+        return Err(Error::InvalidItemCount {
+            expected,
+            got: item_ids.len(),
+        });
+    }
+    
+    for (i, item_id) in item_ids.iter().enumerate() {
+        if Some(*item_id) == tool_id || item_ids[..i].contains(item_id) {
+            return Err(Error::DuplicateItem(*item_id));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Builds the events for changes to the party between `before` and `after`. Invites, kicks,
+/// promotions and join requests have no replies of their own; their results show up here.
+fn party_events(before: Option<Party>, after: Option<Party>, events: &mut Vec<GCEvent>) {
+    let (before, after) = match (before, after) {
+        (Some(before), Some(after)) if before.party_id == after.party_id => (before, after),
+        (before, after) => {
+            events.extend(before.map(|party| GCEvent::PartyLeft(party.party_id)));
+            events.extend(after.map(|party| GCEvent::PartyJoined(Box::new(party))));
+            return;
+        },
+    };
+    let is_pending = |party: &Party, steamid: u64| {
+        party.pending_members.iter().any(|member| member.steamid == steamid)
+    };
+    
+    events.extend(after.member_ids
+        .iter()
+        .filter(|steamid| !before.is_member(**steamid))
+        .map(|steamid| GCEvent::PartyMemberJoined(*steamid)));
+    events.extend(before.member_ids
+        .iter()
+        .filter(|steamid| !after.is_member(**steamid))
+        .map(|steamid| GCEvent::PartyMemberLeft(*steamid)));
+    
+    if before.leader_id != after.leader_id {
+        events.push(GCEvent::PartyLeaderChanged(after.leader_id));
+    }
+    
+    events.extend(after.pending_members
+        .iter()
+        .filter(|member| !is_pending(&before, member.steamid))
+        .map(|member| GCEvent::PartyPendingMemberAdded(*member)));
+    events.extend(before.pending_members
+        .iter()
+        .filter(|member| !is_pending(&after, member.steamid))
+        .map(|member| GCEvent::PartyPendingMemberRemoved(member.steamid)));
+}
+
+/// Shared objects in the cache before a message was applied, used to find what changed.
+struct SOCacheSnapshot {
+    notification_ids: Vec<u64>,
+    party_invite_ids: Vec<u64>,
+    party: Option<Party>,
+    queues: Vec<QueueEntry>,
+    /// The id, server address and game state of each lobby.
+    lobbies: Vec<(u64, String, TF_GC_GameState)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tf2_protobuf::econ_gcmessages::CMsgLookupMultipleAccountNamesResponse_Account;
+    use tf2_protobuf::tf_gcmessages::{
+        CMsgGCNotification,
+        CSOTFParty,
+        CSOTFGameServerLobby,
+        TFPendingPartyMember,
+    };
+    use tf2_protobuf::gcsystemmsgs::ESOMsg;
+    use tf2_protobuf::tf_gcmessages::{
+        CSOTFRatingData,
+        CSOTFLadderPlayerStats,
+        CSOTFMatchResultPlayerStats,
+    };
+    use tf2_protobuf::gcsdk_gcmessages::{
+        CMsgSOSingleObject,
+        CMsgSOMultipleObjects,
+        CMsgSOMultipleObjects_SingleObject,
+        CMsgSOCacheSubscribed,
+        CMsgSOCacheSubscribed_SubscribedType,
+    };
+    use crate::so_cache::{
+        SO_TYPE_NOTIFICATION,
+        SO_TYPE_PARTY,
+        SO_TYPE_LOBBY,
+        SO_TYPE_RATING_DATA,
+        SO_TYPE_LADDER_PLAYER_STATS,
+        SO_TYPE_MATCH_RESULT_PLAYER_STATS,
+    };
+    
+    #[test]
+    fn check_craft_items_rejects_wrong_counts_and_duplicates() {
+        assert!(check_craft_items(Some(1), &[2, 3, 4], HALLOWEEN_OFFERING_ITEM_COUNT).is_ok());
+        assert!(matches!(
+            check_craft_items(None, &[2, 3], HALLOWEEN_OFFERING_ITEM_COUNT),
+            Err(Error::InvalidItemCount { expected: 3, got: 2 }),
+        ));
+        assert!(matches!(
+            check_craft_items(None, &[2, 3, 2], HALLOWEEN_OFFERING_ITEM_COUNT),
+            Err(Error::DuplicateItem(2)),
+        ));
+        assert!(matches!(
+            check_craft_items(Some(3), &[2, 3, 4], HALLOWEEN_OFFERING_ITEM_COUNT),
+            Err(Error::DuplicateItem(3)),
+        ));
+    }
+    
+    #[test]
+    fn crafting_response_is_emitted_as_event() {
+        let mut tf2 = TeamFortress2::new();
+        let mut message = CMsgCraftingResponse::new();
+        
+        message.set_item_ids(vec![100, 101]);
+        
+        let payload = message.write_to_bytes().unwrap();
+        let event = tf2.handle_message(
+            EGCItemMsg::k_EMsgGCCraftCollectionUpgrade as i32,
+            &payload,
+        ).unwrap();
+        
+        match event.as_slice() {
+            [GCEvent::CraftingResponse(response)] => assert_eq!(response.assetids, vec![100, 101]),
+            event => panic!("unexpected events {:?}", event),
+        }
+    }
+    
+    #[test]
+    fn account_names_skip_cached_and_pending_ids() {
+        let mut tf2 = TeamFortress2::new();
+        
+        tf2.account_names.insert(1, Some("cached".into()));
+        tf2.account_names.insert(2, None);
+        tf2.pending_account_names.push_back(vec![3]);
+        
+        let account_ids = (1..=ACCOUNT_NAME_LOOKUP_BATCH_SIZE as u32 + 4).collect::<Vec<_>>();
+        let (names, batches) = tf2.account_name_batches(&account_ids);
+        
+        assert_eq!(names.len(), 1);
+        assert_eq!(names.get(&1).map(String::as_str), Some("cached"));
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0][0], 4);
+        assert_eq!(batches[0].len(), ACCOUNT_NAME_LOOKUP_BATCH_SIZE);
+        assert_eq!(batches[1], vec![104]);
+    }
+    
+    #[test]
+    fn account_names_response_clears_pending_lookup() {
+        let mut tf2 = TeamFortress2::new();
+        let mut message = CMsgLookupMultipleAccountNamesResponse::new();
+        let mut account = CMsgLookupMultipleAccountNamesResponse_Account::new();
+        
+        tf2.pending_account_names.push_back(vec![1, 2]);
+        tf2.pending_account_names.push_back(vec![5, 6]);
+        account.set_accountid(5);
+        account.set_persona("name".into());
+        message.mut_accounts().push(account);
+        
+        let payload = message.write_to_bytes().unwrap();
+        let event = tf2.handle_message(
+            EGCItemMsg::k_EMsgGCLookupMultipleAccountNamesResponse as i32,
+            &payload,
+        ).unwrap();
+        
+        assert!(matches!(event.as_slice(), [GCEvent::AccountNames(names)] if names.len() == 1));
+        assert_eq!(tf2.account_name(5), Some("name"));
+        // the response is matched to the lookup it names accounts from
+        assert_eq!(tf2.pending_account_names, vec![vec![1, 2]]);
+        // 6 had no name, so it is not requested again
+        assert_eq!(tf2.account_name(6), None);
+        assert!(tf2.account_name_batches(&[5, 6]).1.is_empty());
+        
+        tf2.clear_pending_account_names();
+        
+        assert_eq!(tf2.account_name_batches(&[1, 2]).1, vec![vec![1, 2]]);
+    }
+    
+    #[test]
+    fn notification_queue_adds_to_cache() {
+        let mut tf2 = TeamFortress2::new();
+        let mut queue = CMsgGCNotificationQueue::new();
+        let mut notification = CMsgGCNotification::new();
+        
+        notification.set_notification_id(9);
+        notification.set_notification_string("message".into());
+        queue.mut_notifications().push(notification);
+        
+        let payload = queue.write_to_bytes().unwrap();
+        
+        match tf2.handle_notification_queue(&payload).unwrap().as_slice() {
+            [GCEvent::Notifications(notifications)] => {
+                assert_eq!(notifications.len(), 1);
+                assert_eq!(notifications[0].id, 9);
+                assert_eq!(notifications[0].message, "message");
+            },
+            event => panic!("unexpected events {:?}", event),
+        }
+        
+        assert!(tf2.so_cache().notification(9).is_some());
+        // already cached
+        assert!(tf2.handle_notification_queue(&payload).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn notification_acknowledge_reply_is_parsed() {
+        let mut tf2 = TeamFortress2::new();
+        let msg_type = ETFGCMsg::k_EMsgGC_NotificationAcknowledgeReply as i32;
+        
+        // nothing was acknowledged
+        assert!(tf2.handle_message(msg_type, &[]).unwrap().is_empty());
+        assert!(tf2.handle_message(msg_type, &[0xFF]).is_err());
+    }
+    
+    #[test]
+    fn notification_acknowledge_reply_removes_notification() {
+        let mut tf2 = TeamFortress2::new();
+        let mut queue = CMsgGCNotificationQueue::new();
+        let msg_type = ETFGCMsg::k_EMsgGC_NotificationAcknowledgeReply as i32;
+        
+        for notification_id in [9, 10] {
+            let mut notification = CMsgGCNotification::new();
+            
+            notification.set_notification_id(notification_id);
+            queue.mut_notifications().push(notification);
+        }
+        
+        tf2.handle_notification_queue(&queue.write_to_bytes().unwrap()).unwrap();
+        tf2.pending_notification_acks.push_back(9);
+        
+        assert!(matches!(
+            tf2.handle_message(msg_type, &[]).unwrap().as_slice(),
+            [GCEvent::NotificationAcknowledged(9)],
+        ));
+        assert!(tf2.notifications().iter().all(|notification| notification.id != 9));
+        assert!(tf2.notifications().iter().any(|notification| notification.id == 10));
+        assert!(tf2.pending_notification_acks.is_empty());
+    }
+    
+    fn party(member_ids: Vec<u64>, leader_id: u64, pending: &[u64]) -> CSOTFParty {
+        let mut party = CSOTFParty::new();
+        
+        party.set_party_id(50);
+        party.set_leader_id(leader_id);
+        party.set_member_ids(member_ids);
+        
+        for steamid in pending {
+            let mut member = TFPendingPartyMember::new();
+            
+            member.set_steamid(*steamid);
+            member.set_inviter(leader_id);
+            party.mut_pending_members().push(member);
+        }
+        
+        party
+    }
+    
+    fn update_multiple(objects: Vec<(i32, Vec<u8>)>) -> Vec<u8> {
+        let mut message = CMsgSOMultipleObjects::new();
+        
+        for (type_id, object_data) in objects {
+            let mut object = CMsgSOMultipleObjects_SingleObject::new();
+            
+            object.set_type_id(type_id);
+            object.set_object_data(object_data);
+            message.mut_objects().push(object);
+        }
+        
+        message.write_to_bytes().unwrap()
+    }
+    
+    #[test]
+    fn so_cache_update_gives_an_event_per_change() {
+        let mut tf2 = TeamFortress2::new();
+        let msg_type = ESOMsg::k_ESOMsg_UpdateMultiple as i32;
+        let party_data = party(vec![1], 1, &[]).write_to_bytes().unwrap();
+        let events = tf2.handle_message(msg_type, &update_multiple(vec![
+            (SO_TYPE_PARTY, party_data),
+        ])).unwrap();
+        
+        assert!(matches!(events.as_slice(), [GCEvent::PartyJoined(party)] if party.party_id == 50));
+        
+        let mut notification = CMsgGCNotification::new();
+        
+        notification.set_notification_id(9);
+        
+        let party_data = party(vec![1, 2], 2, &[3]).write_to_bytes().unwrap();
+        let events = tf2.handle_message(msg_type, &update_multiple(vec![
+            (SO_TYPE_PARTY, party_data),
+            (SO_TYPE_NOTIFICATION, notification.write_to_bytes().unwrap()),
+        ])).unwrap();
+        
+        match events.as_slice() {
+            [
+                GCEvent::Notifications(notifications),
+                GCEvent::PartyMemberJoined(2),
+                GCEvent::PartyLeaderChanged(2),
+                GCEvent::PartyPendingMemberAdded(pending),
+            ] => {
+                assert_eq!(notifications[0].id, 9);
+                assert_eq!(pending.steamid, 3);
+                assert!(!pending.requested_to_join);
+            },
+            events => panic!("unexpected events {:?}", events),
+        }
+    }
+    
+    #[test]
+    fn party_events_report_kicks_and_leaving() {
+        let before = Party::from(&party(vec![1, 2], 1, &[3]));
+        let mut events = Vec::new();
+        
+        party_events(Some(before.clone()), Some(Party::from(&party(vec![1], 1, &[]))), &mut events);
+        
+        assert!(matches!(
+            events.as_slice(),
+            [GCEvent::PartyMemberLeft(2), GCEvent::PartyPendingMemberRemoved(3)],
+        ));
+        
+        events.clear();
+        party_events(Some(before), None, &mut events);
+        
+        assert!(matches!(events.as_slice(), [GCEvent::PartyLeft(50)]));
+    }
+    
+    #[test]
+    fn lobby_changes_are_emitted_as_events() {
+        let mut tf2 = TeamFortress2::new();
+        let mut lobby = CSOTFGameServerLobby::new();
+        let mut lobby_object = |msg_type: ESOMsg, lobby: &CSOTFGameServerLobby| {
+            let mut object = CMsgSOSingleObject::new();
+            
+            object.set_type_id(SO_TYPE_LOBBY);
+            object.set_object_data(lobby.write_to_bytes().unwrap());
+            tf2.handle_message(msg_type as i32, &object.write_to_bytes().unwrap()).unwrap()
+        };
+        
+        lobby.set_lobby_id(7);
+        
+        assert!(matches!(
+            lobby_object(ESOMsg::k_ESOMsg_Create, &lobby).as_slice(),
+            [GCEvent::LobbyChanged(lobby)] if lobby.lobby_id == 7 && lobby.connect.is_empty(),
+        ));
+        // unchanged
+        assert!(lobby_object(ESOMsg::k_ESOMsg_Update, &lobby).is_empty());
+        
+        lobby.set_connect("169.254.0.1:27015".into());
+        
+        assert!(matches!(
+            lobby_object(ESOMsg::k_ESOMsg_Update, &lobby).as_slice(),
+            [GCEvent::LobbyChanged(lobby)] if lobby.connect == "169.254.0.1:27015",
+        ));
+        assert!(matches!(
+            lobby_object(ESOMsg::k_ESOMsg_Destroy, &lobby).as_slice(),
+            [GCEvent::LobbyRemoved(7)],
+        ));
+    }
+    
+    #[test]
+    fn quest_responses_are_emitted_with_their_action() {
+        let mut tf2 = TeamFortress2::new();
+        let mut message = CMsgGCQuestResponse::new();
+        
+        message.set_success(true);
+        
+        let payload = message.write_to_bytes().unwrap();
+        let events = tf2.handle_message(
+            ETFGCMsg::k_EMsgGC_QuestMapPurchaseReward as i32,
+            &payload,
+        ).unwrap();
+        
+        assert!(matches!(
+            events.as_slice(),
+            [GCEvent::QuestResponse(response)]
+                if response.action == QuestAction::PurchaseReward && response.success,
+        ));
+        
+        let events = tf2.handle_message(ETFGCMsg::k_EMsgGCQuestNodeTurnIn as i32, &[]).unwrap();
+        
+        assert!(matches!(
+            events.as_slice(),
+            [GCEvent::QuestResponse(response)]
+                if response.action == QuestAction::TurnIn && !response.success,
+        ));
+    }
+    
+    #[test]
+    fn queue_responses_are_emitted_as_events() {
+        let mut tf2 = TeamFortress2::new();
+        let mut event = |msg_type: ETFGCMsg| tf2.handle_message(msg_type as i32, &[]).unwrap();
+        
+        assert!(matches!(
+            event(ETFGCMsg::k_EMsgGCParty_QueueForMatchResponse).as_slice(),
+            [GCEvent::QueuedForMatch],
+        ));
+        assert!(matches!(
+            event(ETFGCMsg::k_EMsgGCParty_RemoveFromQueueResponse).as_slice(),
+            [GCEvent::RemovedFromQueue],
+        ));
+        assert!(matches!(
+            event(ETFGCMsg::k_EMsgGCParty_QueueForStandbyResponse).as_slice(),
+            [GCEvent::QueuedForStandby],
+        ));
+        assert!(matches!(
+            event(ETFGCMsg::k_EMsgGCParty_RemoveFromStandbyQueueResponse).as_slice(),
+            [GCEvent::RemovedFromStandbyQueue],
+        ));
+    }
+    
+    #[test]
+    fn stats_are_filtered_by_own_account() {
+        const STEAMID: u64 = 76561197960265729;
+        const OTHER_ACCOUNT_ID: u32 = 2;
+        
+        let mut tf2 = TeamFortress2::new();
+        let mut message = CMsgSOCacheSubscribed::new();
+        let mut add = |type_id: i32, objects: Vec<Vec<u8>>| {
+            let mut subscribed_type = CMsgSOCacheSubscribed_SubscribedType::new();
+            
+            subscribed_type.set_type_id(type_id);
+            subscribed_type.set_object_data(objects.into());
+            message.mut_objects().push(subscribed_type);
+        };
+        let rating = |account_id: u32, primary: u32| {
+            let mut rating = CSOTFRatingData::new();
+            
+            rating.set_account_id(account_id);
+            rating.set_rating_type(RatingType::CasualXP as i32);
+            rating.set_rating_primary(primary);
+            rating.write_to_bytes().unwrap()
+        };
+        let ladder_stats = |account_id: u32, games: u32| {
+            let mut stats = CSOTFLadderPlayerStats::new();
+            
+            stats.set_account_id(account_id);
+            stats.set_match_group(ETFMatchGroup::k_eTFMatchGroup_Casual_Default as i32);
+            stats.set_games(games);
+            stats.write_to_bytes().unwrap()
+        };
+        let match_result = |account_id: u32, match_id: u64| {
+            let mut result = CSOTFMatchResultPlayerStats::new();
+            
+            result.set_account_id(account_id);
+            result.set_match_id(match_id);
+            result.set_match_group(ETFMatchGroup::k_eTFMatchGroup_Casual_Default as i32);
+            result.write_to_bytes().unwrap()
+        };
+        
+        add(SO_TYPE_RATING_DATA, vec![rating(1, 100), rating(OTHER_ACCOUNT_ID, 200)]);
+        add(SO_TYPE_LADDER_PLAYER_STATS, vec![ladder_stats(1, 10), ladder_stats(OTHER_ACCOUNT_ID, 20)]);
+        add(SO_TYPE_MATCH_RESULT_PLAYER_STATS, vec![
+            match_result(1, 5),
+            match_result(OTHER_ACCOUNT_ID, 6),
+        ]);
+        message.set_owner(STEAMID);
+        tf2.handle_message(
+            ESOMsg::k_ESOMsg_CacheSubscribed as i32,
+            &message.write_to_bytes().unwrap(),
+        ).unwrap();
+        
+        let match_group = ETFMatchGroup::k_eTFMatchGroup_Casual_Default;
+        
+        assert_eq!(tf2.so_cache().account_id(), Some(1));
+        assert_eq!(tf2.rating(RatingType::CasualXP).map(|rating| rating.primary), Some(100));
+        assert_eq!(tf2.ratings().len(), 1);
+        assert_eq!(
+            tf2.ladder_stats(match_group).iter().map(|stats| stats.games).collect::<Vec<_>>(),
+            vec![10],
+        );
+        assert_eq!(
+            tf2.match_history(match_group).iter().map(|result| result.match_id).collect::<Vec<_>>(),
+            vec![5],
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,570 @@
+//! A parser for the protobuf text format. The generated messages can only be decoded from the
+//! binary format, so text is encoded to it using the message descriptors and decoded from
+//! there.
+
+use std::collections::HashMap;
+use protobuf::{CodedOutputStream, Message};
+use protobuf::descriptor::{
+    FileDescriptorProto,
+    DescriptorProto,
+    EnumDescriptorProto,
+    FieldDescriptorProto,
+    FieldDescriptorProto_Type,
+};
+
+#[derive(thiserror::Error, Debug)]
+#[error("Line {}: {}", .line, .message)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// A value in a text format message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextValue {
+    /// A number or identifier, e.g. `1.5`, `true` or an enum value name.
+    Scalar(String),
+    String(Vec<u8>),
+    Message(TextMessage),
+}
+
+/// A field in a text format message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextField {
+    pub name: String,
+    pub value: TextValue,
+    pub line: usize,
+}
+
+/// A message in the text format. Fields are kept in order and repeated fields appear once per
+/// value.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextMessage {
+    fields: Vec<TextField>,
+}
+
+impl TextMessage {
+    
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut tokenizer = Tokenizer::new(text);
+        
+        parse_message(&mut tokenizer, None)
+    }
+    
+    pub fn fields(&self) -> impl Iterator<Item = &TextField> {
+        self.fields.iter()
+    }
+    
+    /// Encodes the message to the binary format as the message type `type_name`, e.g.
+    /// `.CMsgQuestDef`.
+    pub fn encode(
+        &self,
+        descriptors: &Descriptors,
+        type_name: &str,
+    ) -> Result<Vec<u8>, ParseError> {
+        let descriptor = descriptors.message(type_name)
+            .ok_or_else(|| ParseError {
+                line: 1,
+                message: format!("Unknown message type \"{}\"", type_name),
+            })?;
+        
+        encode_message(self, descriptors, descriptor)
+    }
+    
+    /// Decodes the message as `M`, which must be described by `descriptors`.
+    pub fn to_message<M: Message>(&self, descriptors: &Descriptors) -> Result<M, ParseError> {
+        let type_name = format!(".{}", M::descriptor_static().full_name());
+        let bytes = self.encode(descriptors, &type_name)?;
+        
+        M::parse_from_bytes(&bytes)
+            .map_err(|error| ParseError {
+                line: 1,
+                message: error.to_string(),
+            })
+    }
+}
+
+/// Message and enum descriptors keyed by their fully qualified name, e.g.
+/// `.CMsgQuestDef.MMCriteria`.
+#[derive(Debug, Default, Clone)]
+pub struct Descriptors<'a> {
+    messages: HashMap<String, &'a DescriptorProto>,
+    enums: HashMap<String, &'a EnumDescriptorProto>,
+}
+
+impl<'a> Descriptors<'a> {
+    
+    pub fn new(files: &[&'a FileDescriptorProto]) -> Self {
+        let mut descriptors = Self::default();
+        
+        for file in files {
+            let prefix = if file.get_package().is_empty() {
+                String::new()
+            } else {
+                format!(".{}", file.get_package())
+            };
+            
+            for message in file.get_message_type() {
+                descriptors.add_message(&prefix, message);
+            }
+            
+            for enumeration in file.get_enum_type() {
+                descriptors.enums.insert(format!("{}.{}", prefix, enumeration.get_name()), enumeration);
+            }
+        }
+        
+        descriptors
+    }
+    
+    fn add_message(&mut self, prefix: &str, message: &'a DescriptorProto) {
+        let name = format!("{}.{}", prefix, message.get_name());
+        
+        for nested in message.get_nested_type() {
+            self.add_message(&name, nested);
+        }
+        
+        for enumeration in message.get_enum_type() {
+            self.enums.insert(format!("{}.{}", name, enumeration.get_name()), enumeration);
+        }
+        
+        self.messages.insert(name, message);
+    }
+    
+    pub fn message(&self, type_name: &str) -> Option<&'a DescriptorProto> {
+        self.messages.get(type_name).copied()
+    }
+    
+    pub fn enumeration(&self, type_name: &str) -> Option<&'a EnumDescriptorProto> {
+        self.enums.get(type_name).copied()
+    }
+}
+
+fn encode_message(
+    message: &TextMessage,
+    descriptors: &Descriptors,
+    descriptor: &DescriptorProto,
+) -> Result<Vec<u8>, ParseError> {
+    let mut bytes = Vec::new();
+    let mut os = CodedOutputStream::vec(&mut bytes);
+    
+    for field in &message.fields {
+        let field_descriptor = descriptor.get_field()
+            .iter()
+            .find(|field_descriptor| field_descriptor.get_name() == field.name)
+            .ok_or_else(|| ParseError {
+                line: field.line,
+                message: format!("Unknown field \"{}\" in {}", field.name, descriptor.get_name()),
+            })?;
+        
+        encode_field(&mut os, descriptors, field_descriptor, field)?;
+    }
+    
+    os.flush()
+        .map_err(|error| ParseError {
+            line: 1,
+            message: error.to_string(),
+        })?;
+    drop(os);
+    
+    Ok(bytes)
+}
+
+fn encode_field(
+    os: &mut CodedOutputStream,
+    descriptors: &Descriptors,
+    descriptor: &FieldDescriptorProto,
+    field: &TextField,
+) -> Result<(), ParseError> {
+    use FieldDescriptorProto_Type::*;
+    
+    let invalid = |expected: &str| ParseError {
+        line: field.line,
+        message: format!("Expected {} for \"{}\"", expected, field.name),
+    };
+    let number = descriptor.get_number() as u32;
+    let scalar = match &field.value {
+        TextValue::Scalar(value) => Some(value.as_str()),
+        _ => None,
+    };
+    let result = match descriptor.get_field_type() {
+        TYPE_MESSAGE => {
+            let message = match &field.value {
+                TextValue::Message(message) => message,
+                _ => return Err(invalid("a message")),
+            };
+            let nested = descriptors.message(descriptor.get_type_name())
+                .ok_or_else(|| invalid("a known message type"))?;
+            let bytes = encode_message(message, descriptors, nested)?;
+            
+            os.write_bytes(number, &bytes)
+        },
+        TYPE_STRING |
+        TYPE_BYTES => match &field.value {
+            TextValue::String(bytes) => os.write_bytes(number, bytes),
+            _ => return Err(invalid("a string")),
+        },
+        TYPE_ENUM => {
+            let value = scalar.ok_or_else(|| invalid("an enum value"))?;
+            let value = match parse_integer::<i32>(value) {
+                Some(value) => value,
+                None => descriptors.enumeration(descriptor.get_type_name())
+                    .and_then(|enumeration| {
+                        enumeration.get_value()
+                            .iter()
+                            .find(|enum_value| enum_value.get_name() == value)
+                    })
+                    .map(|enum_value| enum_value.get_number())
+                    .ok_or_else(|| invalid("an enum value"))?,
+            };
+            
+            os.write_enum(number, value)
+        },
+        TYPE_BOOL => match scalar {
+            Some("true" | "True" | "t" | "1") => os.write_bool(number, true),
+            Some("false" | "False" | "f" | "0") => os.write_bool(number, false),
+            _ => return Err(invalid("a bool")),
+        },
+        TYPE_FLOAT => {
+            let value = scalar.and_then(parse_float).ok_or_else(|| invalid("a float"))?;
+            
+            os.write_float(number, value as f32)
+        },
+        TYPE_DOUBLE => {
+            let value = scalar.and_then(parse_float).ok_or_else(|| invalid("a double"))?;
+            
+            os.write_double(number, value)
+        },
+        TYPE_INT32 => os.write_int32(number, parse_scalar(scalar).ok_or_else(|| invalid("an int32"))?),
+        TYPE_INT64 => os.write_int64(number, parse_scalar(scalar).ok_or_else(|| invalid("an int64"))?),
+        TYPE_UINT32 => os.write_uint32(number, parse_scalar(scalar).ok_or_else(|| invalid("a uint32"))?),
+        TYPE_UINT64 => os.write_uint64(number, parse_scalar(scalar).ok_or_else(|| invalid("a uint64"))?),
+        TYPE_SINT32 => os.write_sint32(number, parse_scalar(scalar).ok_or_else(|| invalid("a sint32"))?),
+        TYPE_SINT64 => os.write_sint64(number, parse_scalar(scalar).ok_or_else(|| invalid("a sint64"))?),
+        TYPE_FIXED32 => os.write_fixed32(number, parse_scalar(scalar).ok_or_else(|| invalid("a fixed32"))?),
+        TYPE_FIXED64 => os.write_fixed64(number, parse_scalar(scalar).ok_or_else(|| invalid("a fixed64"))?),
+        TYPE_SFIXED32 => os.write_sfixed32(number, parse_scalar(scalar).ok_or_else(|| invalid("an sfixed32"))?),
+        TYPE_SFIXED64 => os.write_sfixed64(number, parse_scalar(scalar).ok_or_else(|| invalid("an sfixed64"))?),
+        TYPE_GROUP => return Err(invalid("a field which is not a group")),
+    };
+    
+    result.map_err(|error| ParseError {
+        line: field.line,
+        message: error.to_string(),
+    })
+}
+
+fn parse_scalar<T: TryFrom<i128>>(value: Option<&str>) -> Option<T> {
+    parse_integer(value?)
+}
+
+fn parse_integer<T: TryFrom<i128>>(value: &str) -> Option<T> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let value = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i128>().ok()?,
+    };
+    
+    T::try_from(if negative { -value } else { value }).ok()
+}
+
+fn parse_float(value: &str) -> Option<f64> {
+    value.trim_end_matches(['f', 'F']).parse::<f64>().ok()
+        .or_else(|| value.parse::<f64>().ok())
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Scalar(String),
+    String(Vec<u8>),
+    Symbol(char),
+}
+
+struct Tokenizer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    peeked: Option<Token>,
+}
+
+impl<'a> Tokenizer<'a> {
+    
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.trim_start_matches('\u{feff}').chars().peekable(),
+            line: 1,
+            peeked: None,
+        }
+    }
+    
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            line: self.line,
+            message: message.to_string(),
+        }
+    }
+    
+    fn peek(&mut self) -> Result<Option<&Token>, ParseError> {
+        if self.peeked.is_none() {
+            self.peeked = self.read_token()?;
+        }
+        
+        Ok(self.peeked.as_ref())
+    }
+    
+    fn next_token(&mut self) -> Result<Option<Token>, ParseError> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.read_token(),
+        }
+    }
+    
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(c) = self.chars.peek() {
+            match c {
+                '\n' => {
+                    self.line += 1;
+                    self.chars.next();
+                },
+                '#' => {
+                    while self.chars.next_if(|c| *c != '\n').is_some() {}
+                },
+                c if c.is_whitespace() => {
+                    self.chars.next();
+                },
+                _ => return,
+            }
+        }
+    }
+    
+    fn read_token(&mut self) -> Result<Option<Token>, ParseError> {
+        self.skip_whitespace_and_comments();
+        
+        let c = match self.chars.next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        
+        match c {
+            '"' | '\'' => self.read_string(c).map(|value| Some(Token::String(value))),
+            c if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+') => {
+                let mut value = c.to_string();
+                
+                while let Some(c) = self.chars.next_if(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+                }) {
+                    value.push(c);
+                }
+                
+                Ok(Some(Token::Scalar(value)))
+            },
+            c => Ok(Some(Token::Symbol(c))),
+        }
+    }
+    
+    fn read_string(&mut self, quote: char) -> Result<Vec<u8>, ParseError> {
+        let mut value = Vec::new();
+        let mut buffer = [0; 4];
+        
+        loop {
+            let c = match self.chars.next() {
+                Some('\n') | None => return Err(self.error("Unterminated string")),
+                Some(c) if c == quote => return Ok(value),
+                Some(c) => c,
+            };
+            
+            if c != '\\' {
+                value.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                continue;
+            }
+            
+            match self.chars.next() {
+                Some('n') => value.push(b'\n'),
+                Some('t') => value.push(b'\t'),
+                Some('r') => value.push(b'\r'),
+                Some('a') => value.push(0x07),
+                Some('b') => value.push(0x08),
+                Some('f') => value.push(0x0c),
+                Some('v') => value.push(0x0b),
+                Some('x') => {
+                    let mut byte = 0;
+                    
+                    for _ in 0..2 {
+                        match self.chars.next_if(char::is_ascii_hexdigit) {
+                            Some(digit) => byte = byte * 16 + digit.to_digit(16).unwrap_or(0),
+                            None => break,
+                        }
+                    }
+                    
+                    value.push(byte as u8);
+                },
+                Some(digit @ '0'..='7') => {
+                    let mut byte = digit.to_digit(8).unwrap_or(0);
+                    
+                    for _ in 0..2 {
+                        match self.chars.next_if(|c| matches!(c, '0'..='7')) {
+                            Some(digit) => byte = byte * 8 + digit.to_digit(8).unwrap_or(0),
+                            None => break,
+                        }
+                    }
+                    
+                    value.push(byte as u8);
+                },
+                Some(c) => value.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes()),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+}
+
+fn parse_message(
+    tokenizer: &mut Tokenizer,
+    close: Option<char>,
+) -> Result<TextMessage, ParseError> {
+    let mut message = TextMessage::default();
+    
+    loop {
+        let name = match tokenizer.next_token()? {
+            Some(Token::Scalar(name)) => name,
+            Some(Token::Symbol(c)) if Some(c) == close => return Ok(message),
+            None if close.is_none() => return Ok(message),
+            None => return Err(tokenizer.error("Unexpected end of file")),
+            Some(_) => return Err(tokenizer.error("Expected a field name")),
+        };
+        let line = tokenizer.line;
+        let has_colon = tokenizer.peek()? == Some(&Token::Symbol(':'));
+        
+        if has_colon {
+            tokenizer.next_token()?;
+        }
+        
+        let token = tokenizer.next_token()?;
+        
+        if has_colon && token == Some(Token::Symbol('[')) {
+            // A list of values for a repeated field.
+            if tokenizer.peek()? == Some(&Token::Symbol(']')) {
+                tokenizer.next_token()?;
+            } else {
+                loop {
+                    let token = tokenizer.next_token()?;
+                    let value = parse_value(tokenizer, token, &name)?;
+                    
+                    message.fields.push(TextField {
+                        name: name.clone(),
+                        value,
+                        line,
+                    });
+                    
+                    match tokenizer.next_token()? {
+                        Some(Token::Symbol(',')) => continue,
+                        Some(Token::Symbol(']')) => break,
+                        _ => return Err(tokenizer.error("Expected \",\" or \"]\"")),
+                    }
+                }
+            }
+        } else {
+            if !has_colon && !matches!(token, Some(Token::Symbol('{' | '<'))) {
+                return Err(tokenizer.error(&format!("Expected \":\" after \"{}\"", name)));
+            }
+            
+            let value = parse_value(tokenizer, token, &name)?;
+            
+            message.fields.push(TextField {
+                name,
+                value,
+                line,
+            });
+        }
+        
+        if matches!(tokenizer.peek()?, Some(Token::Symbol(';' | ','))) {
+            tokenizer.next_token()?;
+        }
+    }
+}
+
+fn parse_value(
+    tokenizer: &mut Tokenizer,
+    token: Option<Token>,
+    name: &str,
+) -> Result<TextValue, ParseError> {
+    match token {
+        Some(Token::Scalar(value)) => Ok(TextValue::Scalar(value)),
+        Some(Token::String(mut value)) => {
+            // Adjacent strings are concatenated.
+            while let Some(Token::String(_)) = tokenizer.peek()? {
+                if let Some(Token::String(next)) = tokenizer.next_token()? {
+                    value.extend(next);
+                }
+            }
+            
+            Ok(TextValue::String(value))
+        },
+        Some(Token::Symbol('{')) => Ok(TextValue::Message(parse_message(tokenizer, Some('}'))?)),
+        Some(Token::Symbol('<')) => Ok(TextValue::Message(parse_message(tokenizer, Some('>'))?)),
+        _ => Err(tokenizer.error(&format!("Expected a value for \"{}\"", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tf2_protobuf::tf_proto_def_messages::{self, CMsgQuestDef, CMsgQuestObjectiveDef, EQuestPoints};
+    
+    fn descriptors() -> Descriptors<'static> {
+        Descriptors::new(&[tf_proto_def_messages::file_descriptor_proto()])
+    }
+    
+    #[test]
+    fn parses_fields_in_order() {
+        let message = TextMessage::parse(r#"
+            # a comment
+            name: "a\tb" 'c\x41\101'
+            header < defindex: 1 >;
+            map: ["pl_upward", "pl_badwater"]
+        "#).unwrap();
+        let fields = message.fields().collect::<Vec<_>>();
+        
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[0].value, TextValue::String(b"a\tbcAA".to_vec()));
+        assert_eq!(fields[1].line, 4);
+        assert!(matches!(&fields[1].value, TextValue::Message(header) if header.fields().count() == 1));
+        assert_eq!(fields[2].name, "map");
+        assert_eq!(fields[3].value, TextValue::String(b"pl_badwater".to_vec()));
+    }
+    
+    #[test]
+    fn decodes_messages() {
+        let quest: CMsgQuestDef = TextMessage::parse(r#"
+            header { defindex: 0x10 tags: "easy" tags: "scout" }
+            max_points_0: 100
+            objectives { point_type: QUEST_POINTS_ADVANCED point_value: 5 }
+            objectives { point_type: 2 }
+            loaner_names: []
+        "#).unwrap().to_message(&descriptors()).unwrap();
+        
+        assert_eq!(quest.get_header().get_defindex(), 16);
+        assert_eq!(quest.get_header().get_tags(), ["easy", "scout"]);
+        assert_eq!(quest.get_max_points_0(), 100);
+        assert_eq!(quest.get_objectives()[0].get_point_type(), EQuestPoints::QUEST_POINTS_ADVANCED);
+        assert_eq!(quest.get_objectives()[0].get_point_value(), 5);
+        assert_eq!(quest.get_objectives()[1].get_point_type(), EQuestPoints::QUEST_POINTS_EXPERT);
+        assert!(quest.get_loaner_names().is_empty());
+    }
+    
+    #[test]
+    fn reports_errors_with_line() {
+        let descriptors = descriptors();
+        let error = |text: &str| {
+            TextMessage::parse(text)
+                .and_then(|message| message.to_message::<CMsgQuestObjectiveDef>(&descriptors))
+                .unwrap_err()
+        };
+        
+        assert_eq!(error("header { defindex: 1 }\npoints 5").line, 2);
+        assert_eq!(error("header { defindex: 1 }\n\nunknown: 5").line, 3);
+        assert_eq!(error("points: -1").message, "Expected a uint32 for \"points\"");
+        assert_eq!(error("loc_desctoken: \"unterminated\n\"").message, "Unterminated string");
+        assert_eq!(error("header { defindex: 1").message, "Unexpected end of file");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,535 @@
+//! A parser for Valve's KeyValues text format, as used by `items_game.txt` and localization
+//! files.
+
+use std::fmt;
+
+/// The conditionals which are true when parsing with [`KeyValues::parse`], as defined for the
+/// Windows client.
+pub const DEFAULT_CONDITIONS: &[&str] = &["WIN32", "WINDOWS"];
+
+#[derive(thiserror::Error, Debug)]
+#[error("Line {}: {}", .line, .message)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// A value in a KeyValues document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Object(KeyValues),
+}
+
+impl Value {
+    
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            Self::Object(_) => None,
+        }
+    }
+    
+    pub fn as_object(&self) -> Option<&KeyValues> {
+        match self {
+            Self::String(_) => None,
+            Self::Object(object) => Some(object),
+        }
+    }
+}
+
+/// An ordered list of keys and values. Keys may repeat and are looked up case-insensitively.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KeyValues {
+    entries: Vec<(String, Value)>,
+}
+
+impl KeyValues {
+    
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Parses a document. The returned object holds the root keys, e.g. `items_game`. Keys
+    /// with a platform conditional such as `[$X360]` are kept only if the conditional holds
+    /// for [`DEFAULT_CONDITIONS`].
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        Self::parse_with_conditions(text, DEFAULT_CONDITIONS)
+    }
+    
+    /// Parses a document, keeping keys with a conditional only if it holds when the names in
+    /// `conditions` (without the `$`) are true.
+    pub fn parse_with_conditions(text: &str, conditions: &[&str]) -> Result<Self, ParseError> {
+        let mut tokenizer = Tokenizer::new(text);
+        let object = parse_object(&mut tokenizer, conditions, false)?;
+        
+        Ok(object)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+    
+    /// Iterates the keys which have object values.
+    pub fn objects(&self) -> impl Iterator<Item = (&str, &KeyValues)> {
+        self.iter()
+            .filter_map(|(key, value)| value.as_object().map(|object| (key, object)))
+    }
+    
+    /// The first value for `key`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(k, _value)| k.eq_ignore_ascii_case(key))
+            .map(|(_key, value)| value)
+    }
+    
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+    
+    pub fn get_object(&self, key: &str) -> Option<&KeyValues> {
+        self.get(key).and_then(Value::as_object)
+    }
+    
+    /// Parses the string value for `key`.
+    pub fn get_parsed<T: std::str::FromStr>(&self, key: &str) -> Option<T> {
+        self.get_str(key).and_then(|value| value.trim().parse().ok())
+    }
+    
+    /// Reads a `"0"`/`"1"` flag, defaulting to `false`.
+    pub fn get_bool(&self, key: &str) -> bool {
+        self.get_parsed::<i64>(key).map(|value| value != 0).unwrap_or(false)
+    }
+    
+    pub fn insert(&mut self, key: String, value: Value) {
+        self.entries.push((key, value));
+    }
+    
+    /// Replaces the root `#base` and `#include` directives with the documents they name, as
+    /// loaded by `load`. Keys from `#base` documents are only added where missing, while keys
+    /// from `#include` documents are appended.
+    pub fn resolve_includes<F, E>(&mut self, mut load: F) -> Result<(), E>
+    where
+        F: FnMut(&str) -> Result<KeyValues, E>,
+    {
+        let entries = std::mem::take(&mut self.entries);
+        let mut bases = Vec::new();
+        let mut includes = Vec::new();
+        
+        for (key, value) in entries {
+            match (key.to_lowercase().as_str(), &value) {
+                ("#base", Value::String(name)) => bases.push(name.clone()),
+                ("#include", Value::String(name)) => includes.push(name.clone()),
+                _ => self.entries.push((key, value)),
+            }
+        }
+        
+        for name in includes {
+            self.entries.extend(load(&name)?.entries);
+        }
+        
+        for name in bases {
+            self.merge_missing(&load(&name)?);
+        }
+        
+        Ok(())
+    }
+    
+    /// Fills in the keys of `other` which are missing from this object, merging nested objects.
+    pub fn merge_missing(&mut self, other: &KeyValues) {
+        for (key, value) in other.iter() {
+            let existing = self.entries
+                .iter_mut()
+                .find(|(k, _value)| k.eq_ignore_ascii_case(key));
+            
+            match (existing, value) {
+                (Some((_key, Value::Object(existing))), Value::Object(value)) => {
+                    existing.merge_missing(value);
+                },
+                (Some(_), _) => {},
+                (None, value) => self.entries.push((key.to_string(), value.clone())),
+            }
+        }
+    }
+}
+
+impl fmt::Display for KeyValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_object(
+            f: &mut fmt::Formatter<'_>,
+            object: &KeyValues,
+            depth: usize,
+        ) -> fmt::Result {
+            for (key, value) in object.iter() {
+                let indent = "\t".repeat(depth);
+                
+                match value {
+                    Value::String(value) => {
+                        writeln!(f, "{}\"{}\"\t\"{}\"", indent, escape(key), escape(value))?;
+                    },
+                    Value::Object(object) => {
+                        writeln!(f, "{}\"{}\"", indent, escape(key))?;
+                        writeln!(f, "{}{{", indent)?;
+                        write_object(f, object, depth + 1)?;
+                        writeln!(f, "{}}}", indent)?;
+                    },
+                }
+            }
+            
+            Ok(())
+        }
+        
+        write_object(f, self, 0)
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    String(String),
+    Open,
+    Close,
+    /// A platform conditional such as `[$WIN32]`, without the brackets.
+    Conditional(String),
+}
+
+struct Tokenizer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    peeked: Option<Token>,
+}
+
+impl<'a> Tokenizer<'a> {
+    
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.trim_start_matches('\u{feff}').chars().peekable(),
+            line: 1,
+            peeked: None,
+        }
+    }
+    
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            line: self.line,
+            message: message.to_string(),
+        }
+    }
+    
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            match self.chars.peek() {
+                Some('\n') => {
+                    self.line += 1;
+                    self.chars.next();
+                },
+                Some(c) if c.is_whitespace() => {
+                    self.chars.next();
+                },
+                Some('/') => {
+                    let mut lookahead = self.chars.clone();
+                    
+                    lookahead.next();
+                    
+                    if lookahead.peek() != Some(&'/') {
+                        return;
+                    }
+                    
+                    for c in self.chars.by_ref() {
+                        if c == '\n' {
+                            self.line += 1;
+                            break;
+                        }
+                    }
+                },
+                _ => return,
+            }
+        }
+    }
+    
+    /// Reads a conditional if it is the next token.
+    fn next_conditional(&mut self) -> Result<Option<String>, ParseError> {
+        if self.peeked.is_none() {
+            self.peeked = self.read_token()?;
+        }
+        
+        match self.peeked.take() {
+            Some(Token::Conditional(condition)) => Ok(Some(condition)),
+            token => {
+                self.peeked = token;
+                Ok(None)
+            },
+        }
+    }
+    
+    fn next_token(&mut self) -> Result<Option<Token>, ParseError> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.read_token(),
+        }
+    }
+    
+    fn read_token(&mut self) -> Result<Option<Token>, ParseError> {
+        self.skip_whitespace_and_comments();
+        
+        let c = match self.chars.next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        
+        match c {
+            '{' => Ok(Some(Token::Open)),
+            '}' => Ok(Some(Token::Close)),
+            '"' => {
+                let mut value = String::new();
+                
+                loop {
+                    match self.chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match self.chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(c) => value.push(c),
+                            None => return Err(self.error("Unterminated string")),
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                self.line += 1;
+                            }
+                            
+                            value.push(c);
+                        },
+                        None => return Err(self.error("Unterminated string")),
+                    }
+                }
+                
+                Ok(Some(Token::String(value)))
+            },
+            '[' => {
+                let mut condition = String::new();
+                
+                for c in self.chars.by_ref() {
+                    if c == ']' {
+                        return Ok(Some(Token::Conditional(condition)));
+                    }
+                    
+                    condition.push(c);
+                }
+                
+                Err(self.error("Unterminated conditional"))
+            },
+            c => {
+                let mut value = c.to_string();
+                
+                while let Some(c) = self.chars.peek() {
+                    if c.is_whitespace() || matches!(c, '{' | '}' | '"') {
+                        break;
+                    }
+                    
+                    value.push(*c);
+                    self.chars.next();
+                }
+                
+                Ok(Some(Token::String(value)))
+            },
+        }
+    }
+}
+
+fn parse_object(
+    tokenizer: &mut Tokenizer,
+    conditions: &[&str],
+    nested: bool,
+) -> Result<KeyValues, ParseError> {
+    let mut object = KeyValues::new();
+    
+    loop {
+        let key = match tokenizer.next_token()? {
+            Some(Token::String(key)) => key,
+            Some(Token::Close) if nested => return Ok(object),
+            None if !nested => return Ok(object),
+            None => return Err(tokenizer.error("Unexpected end of file")),
+            Some(_) => return Err(tokenizer.error("Expected a key")),
+        };
+        // The conditional may come before or after the value.
+        let mut accepted = match tokenizer.next_conditional()? {
+            Some(condition) => evaluate_conditional(tokenizer, &condition, conditions)?,
+            None => true,
+        };
+        let value = match tokenizer.next_token()? {
+            Some(Token::String(value)) => Value::String(value),
+            Some(Token::Open) => Value::Object(parse_object(tokenizer, conditions, true)?),
+            _ => return Err(tokenizer.error(&format!("Expected a value for \"{}\"", key))),
+        };
+        
+        if let Some(condition) = tokenizer.next_conditional()? {
+            accepted &= evaluate_conditional(tokenizer, &condition, conditions)?;
+        }
+        
+        if accepted {
+            object.insert(key, value);
+        }
+    }
+}
+
+/// Evaluates a conditional such as `$WIN32`, `!$X360` or `$WIN32 && !$OSX`. `||` binds looser
+/// than `&&`.
+fn evaluate_conditional(
+    tokenizer: &Tokenizer,
+    condition: &str,
+    conditions: &[&str],
+) -> Result<bool, ParseError> {
+    let mut any = false;
+    
+    for alternative in condition.split("||") {
+        let mut all = true;
+        
+        for term in alternative.split("&&") {
+            let term = term.trim();
+            let (negated, term) = match term.strip_prefix('!') {
+                Some(term) => (true, term.trim_start()),
+                None => (false, term),
+            };
+            let name = term.strip_prefix('$')
+                .filter(|name| !name.is_empty())
+                .ok_or_else(|| tokenizer.error(&format!("Invalid conditional \"[{}]\"", condition)))?;
+            let defined = conditions
+                .iter()
+                .any(|condition| condition.eq_ignore_ascii_case(name));
+            
+            all &= defined != negated;
+        }
+        
+        any |= all;
+    }
+    
+    Ok(any)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_quoted_and_unquoted_tokens() {
+        let kv = KeyValues::parse(r#"
+            "root"
+            {
+                "quoted"    "a value"
+                unquoted    value
+                nested { key 1 }
+            }
+        "#).unwrap();
+        let root = kv.get_object("ROOT").unwrap();
+        
+        assert_eq!(root.get_str("quoted"), Some("a value"));
+        assert_eq!(root.get_str("unquoted"), Some("value"));
+        assert_eq!(root.get_object("nested").unwrap().get_parsed::<u32>("key"), Some(1));
+    }
+    
+    #[test]
+    fn parses_escapes_and_comments() {
+        let kv = KeyValues::parse(concat!(
+            "\u{feff}// leading comment\n",
+            "\"text\" \"line\\none\\t\\\"quoted\\\" \\\\ end\" // trailing comment\n",
+            "\"url\" \"http://example.com\"\n",
+        )).unwrap();
+        
+        assert_eq!(kv.get_str("text"), Some("line\none\t\"quoted\" \\ end"));
+        assert_eq!(kv.get_str("url"), Some("http://example.com"));
+        assert_eq!(kv.len(), 2);
+    }
+    
+    #[test]
+    fn display_round_trips() {
+        let kv = KeyValues::parse(r#""root" { "text" "a \"b\"\n" "nested" { "key" "value" } }"#)
+            .unwrap();
+        
+        assert_eq!(KeyValues::parse(&kv.to_string()).unwrap(), kv);
+    }
+    
+    #[test]
+    fn evaluates_conditionals() {
+        let text = r#"
+            "before"    [$WIN32] "windows"
+            "before"    [$X360] "xbox"
+            "after"     "not xbox"  [!$X360]
+            "object"    [$OSX] { "key" "value" }
+            "either"    "yes"   [$X360 || $WIN32]
+            "both"      "no"    [$WIN32 && $X360]
+        "#;
+        let kv = KeyValues::parse(text).unwrap();
+        
+        assert_eq!(kv.get_str("before"), Some("windows"));
+        assert_eq!(kv.get_str("after"), Some("not xbox"));
+        assert!(kv.get_object("object").is_none());
+        assert_eq!(kv.get_str("either"), Some("yes"));
+        assert!(kv.get_str("both").is_none());
+        assert_eq!(kv.len(), 3);
+        
+        let kv = KeyValues::parse_with_conditions(text, &["x360"]).unwrap();
+        
+        assert_eq!(kv.get_str("before"), Some("xbox"));
+        assert!(kv.get_str("after").is_none());
+    }
+    
+    #[test]
+    fn rejects_malformed_conditionals() {
+        assert!(KeyValues::parse(r#""key" "value" [WIN32]"#).is_err());
+        assert!(KeyValues::parse(r#""key" "value" [$WIN32"#).is_err());
+        assert!(KeyValues::parse(r#"[$WIN32] "key" "value""#).is_err());
+    }
+    
+    #[test]
+    fn rejects_malformed_documents() {
+        assert_eq!(KeyValues::parse("\"root\"\n{\n\"key\"").unwrap_err().line, 3);
+        assert!(KeyValues::parse("\"root\" { \"key\" \"value\"").is_err());
+        assert!(KeyValues::parse("\"key\" \"unterminated").is_err());
+        assert!(KeyValues::parse("}").is_err());
+    }
+    
+    #[test]
+    fn resolves_base_and_include() {
+        let mut kv = KeyValues::parse(r#"
+            #base "base.txt"
+            #include "include.txt"
+            "root" { "key" "value" }
+        "#).unwrap();
+        let mut loaded = Vec::new();
+        
+        kv.resolve_includes(|name| {
+            loaded.push(name.to_string());
+            
+            match name {
+                "base.txt" => KeyValues::parse(r#""root" { "key" "base" "other" "base" }"#),
+                _ => KeyValues::parse(r#""included" "1""#),
+            }
+        }).unwrap();
+        
+        let root = kv.get_object("root").unwrap();
+        
+        assert_eq!(loaded, ["include.txt", "base.txt"]);
+        assert_eq!(root.get_str("key"), Some("value"));
+        assert_eq!(root.get_str("other"), Some("base"));
+        assert_eq!(kv.get_str("included"), Some("1"));
+        assert!(kv.get("#base").is_none());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,215 @@
+//! Community wars, such as Heavy vs Pyro, and duck leaderboard scores.
+
+use tf2_protobuf::tf_gcmessages::{
+    CSOWarData,
+    CGCMsgGC_War_GlobalStatsResponse,
+    CGCMsgGC_War_IndividualUpdate,
+    CGCMsgGC_PlayerDuckLeaderboard_IndividualUpdate,
+};
+
+/// A side in a war. The GC only sends the side's number. Heavy and Pyro are assumed to be
+/// 0 and 1, following the order the Heavy vs Pyro war lists them in; this is not confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarSide {
+    Heavy,
+    Pyro,
+    Unknown(u32),
+}
+
+impl From<u32> for WarSide {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::Heavy,
+            1 => Self::Pyro,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<WarSide> for u32 {
+    fn from(side: WarSide) -> Self {
+        match side {
+            WarSide::Heavy => 0,
+            WarSide::Pyro => 1,
+            WarSide::Unknown(value) => value,
+        }
+    }
+}
+
+/// The side an account joined in a war and the points it has scored for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarContribution {
+    pub war_id: u32,
+    pub account_id: u32,
+    /// The side joined with [`crate::TeamFortress2::join_war`].
+    pub side: WarSide,
+    pub points_scored: u32,
+}
+
+impl From<&CSOWarData> for WarContribution {
+    fn from(war_data: &CSOWarData) -> Self {
+        Self {
+            war_id: war_data.get_war_id(),
+            account_id: war_data.get_account_id(),
+            side: WarSide::from(war_data.get_affiliation()),
+            points_scored: war_data.get_points_scored(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarSideScore {
+    pub side: WarSide,
+    pub score: u64,
+}
+
+/// The total score of each side in a war.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarStats {
+    pub war_id: u32,
+    pub side_scores: Vec<WarSideScore>,
+}
+
+impl WarStats {
+    
+    pub fn score(&self, side: WarSide) -> Option<u64> {
+        self.side_scores
+            .iter()
+            .find(|side_score| side_score.side == side)
+            .map(|side_score| side_score.score)
+    }
+    
+    /// The side with the highest score. Returns `None` if there are no scores or the lead is
+    /// tied.
+    pub fn leading_side(&self) -> Option<WarSide> {
+        let leader = self.side_scores.iter().max_by_key(|side_score| side_score.score)?;
+        let is_tied = self.side_scores
+            .iter()
+            .any(|side_score| side_score.side != leader.side && side_score.score == leader.score);
+        
+        if is_tied {
+            return None;
+        }
+        
+        Some(leader.side)
+    }
+}
+
+impl From<&CGCMsgGC_War_GlobalStatsResponse> for WarStats {
+    fn from(response: &CGCMsgGC_War_GlobalStatsResponse) -> Self {
+        Self {
+            war_id: response.get_war_id(),
+            side_scores: response.get_side_scores()
+                .iter()
+                .map(|side_score| WarSideScore {
+                    side: WarSide::from(side_score.get_side()),
+                    score: side_score.get_score(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Points scored by a player towards their side in a war.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarScoreUpdate {
+    pub steamid: u64,
+    pub war_id: u32,
+    pub score: u32,
+}
+
+impl From<&CGCMsgGC_War_IndividualUpdate> for WarScoreUpdate {
+    fn from(update: &CGCMsgGC_War_IndividualUpdate) -> Self {
+        Self {
+            steamid: update.get_steam_id(),
+            war_id: update.get_war_id(),
+            score: update.get_score(),
+        }
+    }
+}
+
+/// A score on one of the duck leaderboards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuckLeaderboardUpdate {
+    pub score: u32,
+    pub leaderboard_type: u32,
+    pub score_id: Vec<u8>,
+    pub score_check: u32,
+}
+
+impl From<&CGCMsgGC_PlayerDuckLeaderboard_IndividualUpdate> for DuckLeaderboardUpdate {
+    fn from(update: &CGCMsgGC_PlayerDuckLeaderboard_IndividualUpdate) -> Self {
+        Self {
+            score: update.get_score(),
+            leaderboard_type: update.get_field_type(),
+            score_id: update.get_score_id().to_vec(),
+            score_check: update.get_score_check(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tf2_protobuf::tf_gcmessages::CGCMsgGC_War_GlobalStatsResponse_SideScore;
+    
+    fn stats(scores: &[(u32, u64)]) -> WarStats {
+        let mut response = CGCMsgGC_War_GlobalStatsResponse::new();
+        
+        response.set_war_id(1);
+        
+        for (side, score) in scores {
+            let mut side_score = CGCMsgGC_War_GlobalStatsResponse_SideScore::new();
+            
+            side_score.set_side(*side);
+            side_score.set_score(*score);
+            response.mut_side_scores().push(side_score);
+        }
+        
+        WarStats::from(&response)
+    }
+    
+    #[test]
+    fn converts_sides() {
+        assert_eq!(WarSide::from(0), WarSide::Heavy);
+        assert_eq!(WarSide::from(1), WarSide::Pyro);
+        assert_eq!(WarSide::from(7), WarSide::Unknown(7));
+        
+        for side in [WarSide::Heavy, WarSide::Pyro, WarSide::Unknown(7)] {
+            assert_eq!(WarSide::from(u32::from(side)), side);
+        }
+    }
+    
+    #[test]
+    fn reads_contribution() {
+        let mut war_data = CSOWarData::new();
+        
+        war_data.set_war_id(1);
+        war_data.set_account_id(2);
+        war_data.set_affiliation(1);
+        war_data.set_points_scored(30);
+        
+        assert_eq!(WarContribution::from(&war_data), WarContribution {
+            war_id: 1,
+            account_id: 2,
+            side: WarSide::Pyro,
+            points_scored: 30,
+        });
+    }
+    
+    #[test]
+    fn finds_leading_side() {
+        let stats = stats(&[(0, 100), (1, 250), (5, 10)]);
+        
+        assert_eq!(stats.score(WarSide::Heavy), Some(100));
+        assert_eq!(stats.score(WarSide::Unknown(5)), Some(10));
+        assert_eq!(stats.leading_side(), Some(WarSide::Pyro));
+    }
+    
+    #[test]
+    fn tied_or_empty_stats_have_no_leader() {
+        assert_eq!(stats(&[(0, 100), (1, 100)]).leading_side(), None);
+        assert_eq!(stats(&[]).leading_side(), None);
+        assert_eq!(stats(&[]).score(WarSide::Heavy), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,212 @@
+//! Experience earned from matches and casual level progression.
+
+use std::collections::HashMap;
+use protobuf::ProtobufEnum;
+use tf2_protobuf::tf_gcmessages::{CMsgTFXPSource, CMsgTFXPSource_XPSourceType, ETFMatchGroup};
+use crate::ladder::{Rating, RatingType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XPSourceType {
+    Score,
+    ObjectiveBonus,
+    CompletedMatch,
+    CompetitiveAbandon,
+    CompetitiveWin,
+    CompetitiveLoss,
+    AutobalanceBonus,
+    PrestigeBonus,
+}
+
+impl From<CMsgTFXPSource_XPSourceType> for XPSourceType {
+    fn from(source_type: CMsgTFXPSource_XPSourceType) -> Self {
+        use CMsgTFXPSource_XPSourceType::*;
+        
+        match source_type {
+            SOURCE_SCORE => Self::Score,
+            SOURCE_OBJECTIVE_BONUS => Self::ObjectiveBonus,
+            SOURCE_COMPLETED_MATCH => Self::CompletedMatch,
+            SOURCE_COMPETITIVE_ABANDON => Self::CompetitiveAbandon,
+            SOURCE_COMPETITIVE_WIN => Self::CompetitiveWin,
+            SOURCE_COMPETITIVE_LOSS => Self::CompetitiveLoss,
+            SOURCE_AUTOBALANCE_BONUS => Self::AutobalanceBonus,
+            SOURCE_PRESTIGE_BONUS => Self::PrestigeBonus,
+        }
+    }
+}
+
+/// Experience earned in a match which has not been acknowledged. It is kept by the GC until
+/// acknowledged with [`crate::TeamFortress2::acknowledge_xp`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct XPSource {
+    pub source_type: XPSourceType,
+    /// Negative for losses, such as abandoning a competitive match.
+    pub amount: i32,
+    pub match_group: Option<ETFMatchGroup>,
+    pub account_id: u32,
+    pub match_id: u64,
+}
+
+impl From<&CMsgTFXPSource> for XPSource {
+    fn from(source: &CMsgTFXPSource) -> Self {
+        Self {
+            source_type: source.get_field_type().into(),
+            amount: source.get_amount(),
+            match_group: ETFMatchGroup::from_i32(source.get_match_group()),
+            account_id: source.get_account_id(),
+            match_id: source.get_match_id(),
+        }
+    }
+}
+
+/// Sums the amounts of `sources` by their type.
+pub fn xp_by_source<'a, I>(sources: I) -> HashMap<XPSourceType, i32>
+where
+    I: IntoIterator<Item = &'a XPSource>,
+{
+    let mut totals = HashMap::new();
+    
+    for source in sources {
+        *totals.entry(source.source_type).or_insert(0) += source.amount;
+    }
+    
+    totals
+}
+
+/// Whether experience in `match_group` counts towards the casual level.
+pub fn is_casual(match_group: ETFMatchGroup) -> bool {
+    matches!(
+        match_group,
+        ETFMatchGroup::k_eTFMatchGroup_Casual_6v6 |
+        ETFMatchGroup::k_eTFMatchGroup_Casual_9v9 |
+        ETFMatchGroup::k_eTFMatchGroup_Casual_Default
+    )
+}
+
+/// Casual experience and level, both as held by the GC and as last acknowledged by the player.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CasualProgression {
+    pub experience: u32,
+    pub acknowledged_experience: u32,
+    pub level: u32,
+    pub acknowledged_level: u32,
+}
+
+impl CasualProgression {
+    
+    /// Reads the progression from the casual experience and rank ratings. Returns `None` if
+    /// there is no casual experience rating.
+    pub fn from_ratings(ratings: &[Rating]) -> Option<Self> {
+        let primary = |rating_type: RatingType| {
+            ratings
+                .iter()
+                .find(|rating| rating.rating_type == rating_type)
+                .map(|rating| rating.primary)
+        };
+        let experience = primary(RatingType::CasualXP)?;
+        let level = primary(RatingType::CasualRank12v12).unwrap_or_default();
+        
+        Some(Self {
+            experience,
+            acknowledged_experience: primary(RatingType::CasualXPPlayerAcknowledged)
+                .unwrap_or(experience),
+            level,
+            acknowledged_level: primary(RatingType::CasualRank12v12PlayerAcknowledged)
+                .unwrap_or(level),
+        })
+    }
+    
+    /// Experience earned since it was last acknowledged.
+    pub fn pending_experience(&self) -> u32 {
+        self.experience.saturating_sub(self.acknowledged_experience)
+    }
+    
+    /// Levels gained since they were last acknowledged.
+    pub fn levels_gained(&self) -> u32 {
+        self.level.saturating_sub(self.acknowledged_level)
+    }
+    
+    pub fn has_pending(&self) -> bool {
+        self.experience != self.acknowledged_experience || self.level != self.acknowledged_level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn rating(rating_type: RatingType, primary: u32) -> Rating {
+        Rating {
+            rating_type,
+            primary,
+            secondary: 0,
+            tertiary: 0,
+        }
+    }
+    
+    fn source(source_type: XPSourceType, amount: i32) -> XPSource {
+        XPSource {
+            source_type,
+            amount,
+            match_group: Some(ETFMatchGroup::k_eTFMatchGroup_Casual_Default),
+            account_id: 1,
+            match_id: 2,
+        }
+    }
+    
+    #[test]
+    fn reads_source() {
+        let mut message = CMsgTFXPSource::new();
+        
+        message.set_field_type(CMsgTFXPSource_XPSourceType::SOURCE_COMPETITIVE_ABANDON);
+        message.set_amount(-50);
+        message.set_match_group(ETFMatchGroup::k_eTFMatchGroup_Ladder_Default as i32);
+        
+        let source = XPSource::from(&message);
+        
+        assert_eq!(source.source_type, XPSourceType::CompetitiveAbandon);
+        assert_eq!(source.amount, -50);
+        assert_eq!(source.match_group, Some(ETFMatchGroup::k_eTFMatchGroup_Ladder_Default));
+        assert!(!is_casual(ETFMatchGroup::k_eTFMatchGroup_Ladder_Default));
+        assert!(is_casual(ETFMatchGroup::k_eTFMatchGroup_Casual_Default));
+    }
+    
+    #[test]
+    fn sums_sources_by_type() {
+        let sources = [
+            source(XPSourceType::Score, 100),
+            source(XPSourceType::CompletedMatch, 200),
+            source(XPSourceType::Score, 25),
+        ];
+        let totals = xp_by_source(&sources);
+        
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[&XPSourceType::Score], 125);
+        assert_eq!(totals[&XPSourceType::CompletedMatch], 200);
+    }
+    
+    #[test]
+    fn reads_casual_progression() {
+        let progression = CasualProgression::from_ratings(&[
+            rating(RatingType::CasualXP, 1500),
+            rating(RatingType::CasualXPPlayerAcknowledged, 1200),
+            rating(RatingType::CasualRank12v12, 4),
+            rating(RatingType::CasualRank12v12PlayerAcknowledged, 3),
+        ]).unwrap();
+        
+        assert_eq!(progression.pending_experience(), 300);
+        assert_eq!(progression.levels_gained(), 1);
+        assert!(progression.has_pending());
+    }
+    
+    #[test]
+    fn missing_acknowledged_ratings_are_not_pending() {
+        let progression = CasualProgression::from_ratings(&[
+            rating(RatingType::CasualXP, 1500),
+        ]).unwrap();
+        
+        assert_eq!(progression.acknowledged_experience, 1500);
+        assert_eq!(progression.level, 0);
+        assert!(!progression.has_pending());
+        assert!(CasualProgression::from_ratings(&[rating(RatingType::CasualRank12v12, 4)]).is_none());
+    }
+}
\ No newline at end of file